@@ -1,3 +1,4 @@
+use crate::util::trim_ows;
 use bstr::{BString, ByteSlice};
 use core::cmp::Ordering;
 use std::ops::{Deref, DerefMut};
@@ -41,6 +42,18 @@ impl Bstr {
         self.s.as_bstr().split_str(splitter.as_ref()).collect()
     }
 
+    /// Split the Bstr on the given separator byte, as when tokenizing a header value.
+    /// Empty fields, including the one after a trailing separator, are yielded too.
+    pub fn split_on(&self, sep: u8) -> impl Iterator<Item = &[u8]> {
+        self.as_slice().split(move |c| *c == sep)
+    }
+
+    /// Same as split_on, but each field has leading and trailing optional whitespace (SP and
+    /// HTAB) removed. Other whitespace, such as VT or FF, is kept.
+    pub fn split_on_trimmed(&self, sep: u8) -> impl Iterator<Item = &[u8]> {
+        self.split_on(sep).map(trim_ows)
+    }
+
     /// Compare this bstr with the given slice
     pub fn cmp<B: AsRef<[u8]>>(&self, other: B) -> Ordering {
         self.as_slice().cmp(other.as_ref())
@@ -79,6 +92,18 @@ impl Bstr {
         self.cmp_nocase_nozero(other) == Ordering::Equal
     }
 
+    /// Returns a copy of this bstr with ASCII letters lowercased. Other bytes, including
+    /// those of multibyte UTF-8 sequences, are copied unchanged.
+    pub fn to_ascii_lowercase(&self) -> Bstr {
+        Bstr::from(self.as_slice().to_ascii_lowercase())
+    }
+
+    /// Returns a copy of this bstr with ASCII letters uppercased. Other bytes, including
+    /// those of multibyte UTF-8 sequences, are copied unchanged.
+    pub fn to_ascii_uppercase(&self) -> Bstr {
+        Bstr::from(self.as_slice().to_ascii_uppercase())
+    }
+
     /// Extend this bstr with the given slice
     pub fn add<B: AsRef<[u8]>>(&mut self, other: B) {
         self.extend_from_slice(other.as_ref())
@@ -104,6 +129,20 @@ impl Bstr {
         self.as_slice()[..len].eq_ignore_ascii_case(&other.as_ref()[..len])
     }
 
+    /// Return true if this bstr ends with other
+    pub fn ends_with<B: AsRef<[u8]>>(&self, other: B) -> bool {
+        self.as_slice().ends_with(other.as_ref())
+    }
+
+    /// Return true if this bstr ends with other, ignoring ascii case
+    pub fn ends_with_nocase<B: AsRef<[u8]>>(&self, other: B) -> bool {
+        let other = other.as_ref();
+        if self.len() < other.len() {
+            return false;
+        }
+        self.as_slice()[self.len() - other.len()..].eq_ignore_ascii_case(other)
+    }
+
     /// Find the index of the given slice
     pub fn index_of<B: AsRef<[u8]>>(&self, other: B) -> Option<usize> {
         self.find(other.as_ref())
@@ -166,6 +205,29 @@ impl From<Vec<u8>> for Bstr {
     }
 }
 
+/// Let a Bstr be passed wherever a byte slice is expected, such as to cmp_nocase.
+impl AsRef<[u8]> for Bstr {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl Eq for Bstr {}
+
+/// Order Bstrs byte for byte, consistent with eq.
+impl PartialOrd for Bstr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(Ord::cmp(self, other))
+    }
+}
+
+/// Order Bstrs byte for byte, consistent with eq.
+impl Ord for Bstr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
 /// Compare a Bstr to a &str byte for byte
 impl PartialEq<&str> for Bstr {
     fn eq(&self, rhs: &&str) -> bool {
@@ -400,6 +462,27 @@ fn StartsWith() {
 fn StartsWithNocase() {
     let b = Bstr::from("ABCD");
     assert!(b.starts_with_nocase("Ab"));
+    assert!(b.starts_with_nocase(""));
+    assert!(!b.starts_with_nocase("aBcDe"));
+    assert!(!b.starts_with_nocase("Bc"));
+    assert!(Bstr::new().starts_with_nocase(""));
+}
+
+#[test]
+fn EndsWith() {
+    let b = Bstr::from("ABCD");
+    assert!(b.ends_with("CD"));
+    assert!(!b.ends_with("cd"));
+}
+
+#[test]
+fn EndsWithNocase() {
+    let b = Bstr::from("ABCD");
+    assert!(b.ends_with_nocase("cD"));
+    assert!(b.ends_with_nocase(""));
+    assert!(!b.ends_with_nocase("zabcd"));
+    assert!(!b.ends_with_nocase("bc"));
+    assert!(Bstr::new().ends_with_nocase(""));
 }
 
 #[test]
@@ -439,3 +522,91 @@ fn IndexOfNocaseNozero() {
     assert_eq!(None, b.index_of_nocase_nozero("Hi"));
     assert_eq!(None, b.index_of_nocase_nozero("ghi"));
 }
+
+#[test]
+fn SplitOn() {
+    let b = Bstr::from("gzip, chunked");
+    assert_eq!(
+        vec![b"gzip".as_ref(), b" chunked".as_ref()],
+        b.split_on(b',').collect::<Vec<&[u8]>>()
+    );
+    assert_eq!(
+        vec![b"gzip".as_ref(), b"chunked".as_ref()],
+        b.split_on_trimmed(b',').collect::<Vec<&[u8]>>()
+    );
+    let b = Bstr::from("a,,b,");
+    assert_eq!(
+        vec![b"a".as_ref(), b"".as_ref(), b"b".as_ref(), b"".as_ref()],
+        b.split_on(b',').collect::<Vec<&[u8]>>()
+    );
+    let b = Bstr::from(" no-cache ;\tmax-age=0 ; ");
+    assert_eq!(
+        vec![b"no-cache".as_ref(), b"max-age=0".as_ref(), b"".as_ref()],
+        b.split_on_trimmed(b';').collect::<Vec<&[u8]>>()
+    );
+    let b = Bstr::from("gzip,\x0bchunked");
+    assert_eq!(
+        vec![b"gzip".as_ref(), b"\x0bchunked".as_ref()],
+        b.split_on_trimmed(b',').collect::<Vec<&[u8]>>()
+    );
+    assert_eq!(
+        vec![b"".as_ref()],
+        Bstr::new().split_on(b',').collect::<Vec<&[u8]>>()
+    );
+}
+
+#[test]
+fn SortOrder() {
+    let a = Bstr::from("abc");
+    let b = Bstr::from("ABC");
+    let prefix = Bstr::from("ab");
+    let empty = Bstr::new();
+
+    assert_eq!(Ordering::Greater, Ord::cmp(&a, &b));
+    assert_eq!(Ordering::Less, prefix.partial_cmp(&a).unwrap());
+    assert_eq!(Ordering::Less, Ord::cmp(&empty, &prefix));
+    assert_eq!(Ordering::Equal, Ord::cmp(&empty, &Bstr::new()));
+    assert_eq!(a.eq(&b), Ord::cmp(&a, &b) == Ordering::Equal);
+
+    assert_eq!(Ordering::Equal, a.cmp_nocase(&b));
+    assert!(a.eq_nocase(&b));
+    assert_eq!(Ordering::Greater, b.cmp_nocase(&prefix));
+    assert_eq!(Ordering::Less, Bstr::from("AB").cmp_nocase(&a));
+    assert_eq!(Ordering::Less, empty.cmp_nocase(&prefix));
+    assert_eq!(Ordering::Equal, empty.cmp_nocase(&Bstr::new()));
+
+    let mut headers = vec![
+        (Bstr::from("b"), 1),
+        (Bstr::from("B"), 2),
+        (Bstr::from("a"), 3),
+        (Bstr::from(""), 4),
+    ];
+    headers.sort();
+    assert_eq!(
+        vec![4, 2, 3, 1],
+        headers.iter().map(|h| h.1).collect::<Vec<_>>()
+    );
+    headers.sort_by(|l, r| l.0.cmp_nocase(&r.0));
+    assert_eq!(
+        vec![4, 3, 2, 1],
+        headers.iter().map(|h| h.1).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn AsciiCaseCopies() {
+    let b = Bstr::from("Content-TYPE: \u{c4}\u{e4}\u{1f600}\x7f");
+    let lower = b.to_ascii_lowercase();
+    let upper = b.to_ascii_uppercase();
+    assert!(lower.eq("content-type: \u{c4}\u{e4}\u{1f600}\x7f"));
+    assert!(upper.eq("CONTENT-TYPE: \u{c4}\u{e4}\u{1f600}\x7f"));
+    assert!(b.eq("Content-TYPE: \u{c4}\u{e4}\u{1f600}\x7f"));
+    let raw = Bstr::from(&[b'A', 0x80, 0xc3, 0x84, 0xff, b'z'][..]);
+    assert!(raw
+        .to_ascii_lowercase()
+        .eq(&[b'a', 0x80, 0xc3, 0x84, 0xff, b'z'][..]));
+    assert!(raw
+        .to_ascii_uppercase()
+        .eq(&[b'A', 0x80, 0xc3, 0x84, 0xff, b'Z'][..]));
+    assert!(Bstr::new().to_ascii_lowercase().is_empty());
+}
@@ -51,6 +51,28 @@ pub unsafe extern "C" fn bstr_cmp_c(b: *const Bstr, c: *const libc::c_char) -> l
     }
 }
 
+/// Case-insensitive check whether a bstring starts with a NUL-terminated string.
+/// returns 1 if it does, 0 otherwise
+#[no_mangle]
+pub unsafe extern "C" fn bstr_starts_with_nocase(
+    b: *const Bstr,
+    c: *const libc::c_char,
+) -> libc::c_int {
+    let cs = CStr::from_ptr(c);
+    (*b).starts_with_nocase(cs.to_bytes()) as libc::c_int
+}
+
+/// Case-insensitive check whether a bstring ends with a NUL-terminated string.
+/// returns 1 if it does, 0 otherwise
+#[no_mangle]
+pub unsafe extern "C" fn bstr_ends_with_nocase(
+    b: *const Bstr,
+    c: *const libc::c_char,
+) -> libc::c_int {
+    let cs = CStr::from_ptr(c);
+    (*b).ends_with_nocase(cs.to_bytes()) as libc::c_int
+}
+
 /// Create a new bstring by copying the provided NUL-terminated string.
 #[no_mangle]
 pub unsafe extern "C" fn bstr_dup_c(cstr: *const libc::c_char) -> *mut Bstr {
@@ -60,6 +82,15 @@ pub unsafe extern "C" fn bstr_dup_c(cstr: *const libc::c_char) -> *mut Bstr {
     new
 }
 
+/// Create a copy of the provided bstring, with ASCII letters converted to lowercase.
+/// Returns NULL if b is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn bstr_dup_lower(b: *const Bstr) -> *mut Bstr {
+    b.as_ref()
+        .map(|b| Box::into_raw(Box::new(b.to_ascii_lowercase())))
+        .unwrap_or(std::ptr::null_mut())
+}
+
 /// Create a new NUL-terminated string out of the provided bstring. If NUL bytes
 /// are contained in the bstring, each will be replaced with "\0" (two characters).
 /// The caller is responsible to keep track of the allocated memory area and free
@@ -1,7 +1,10 @@
 #![deny(missing_docs)]
 use crate::{
     config::{Config, HtpServerPersonality, HtpUrlEncodingHandling},
-    hook::{DataExternalCallbackFn, LogExternalCallbackFn, TxExternalCallbackFn},
+    hook::{
+        BufferExternalCallbackFn, DataExternalCallbackFn, ErrorExternalCallbackFn,
+        FieldSoftLimitExternalCallbackFn, LogExternalCallbackFn, TxExternalCallbackFn,
+    },
     HtpStatus,
 };
 
@@ -15,6 +18,18 @@ pub unsafe extern "C" fn htp_config_create() -> *mut Config {
     Box::into_raw(b)
 }
 
+/// Creates a copy of a configuration structure, including all registered callbacks.
+/// The copy can be changed, for example to register per-connection callbacks, without
+/// affecting the original, and each of them must be destroyed separately.
+///
+/// Returns a null pointer if cfg is null.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_copy(cfg: *const Config) -> *mut Config {
+    cfg.as_ref()
+        .map(|cfg| Box::into_raw(Box::new(cfg.clone())))
+        .unwrap_or(std::ptr::null_mut())
+}
+
 /// Destroy a configuration structure.
 #[no_mangle]
 pub unsafe extern "C" fn htp_config_destroy(cfg: *mut Config) {
@@ -40,6 +55,17 @@ pub unsafe extern "C" fn htp_config_register_request_body_data(
         .map(|cfg| cfg.hook_request_body_data.register_extern(cbk_fn));
 }
 
+/// Registers a REQUEST_BODY_DATA_RAW callback, which receives the request body
+/// data before decompression.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_register_request_body_data_raw(
+    cfg: *mut Config,
+    cbk_fn: DataExternalCallbackFn,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.hook_request_body_data_raw.register_extern(cbk_fn));
+}
+
 /// Registers a REQUEST_COMPLETE callback.
 #[no_mangle]
 pub unsafe extern "C" fn htp_config_register_request_complete(
@@ -121,6 +147,17 @@ pub unsafe extern "C" fn htp_config_register_response_body_data(
         .map(|cfg| cfg.hook_response_body_data.register_extern(cbk_fn));
 }
 
+/// Registers a RESPONSE_BODY_DATA_RAW callback, which receives the response body
+/// data before decompression.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_register_response_body_data_raw(
+    cfg: *mut Config,
+    cbk_fn: DataExternalCallbackFn,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.hook_response_body_data_raw.register_extern(cbk_fn));
+}
+
 /// Registers a RESPONSE_COMPLETE callback.
 #[no_mangle]
 pub unsafe extern "C" fn htp_config_register_response_complete(
@@ -191,6 +228,51 @@ pub unsafe extern "C" fn htp_config_register_transaction_complete(
         .map(|cfg| cfg.hook_transaction_complete.register_extern(cbk_fn));
 }
 
+/// Registers a FIELD_SOFT_LIMIT callback, invoked when buffered request or response
+/// data grows over the field soft limit.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_register_field_soft_limit(
+    cfg: *mut Config,
+    cbk_fn: FieldSoftLimitExternalCallbackFn,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.hook_field_soft_limit.register_extern(cbk_fn));
+}
+
+/// Registers an ERROR callback, invoked once when the request or response stream moves
+/// to the ERROR state. The log is the message issued last, or NULL if there is none.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_register_error(
+    cfg: *mut Config,
+    cbk_fn: ErrorExternalCallbackFn,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.hook_error.register_extern(cbk_fn));
+}
+
+/// Registers a BUFFER_ALLOC callback, invoked with the number of bytes added every time
+/// the buffers holding an incomplete request or response line or header grow. The
+/// connection parser is passed along, so that its user data can serve as context.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_register_buffer_alloc(
+    cfg: *mut Config,
+    cbk_fn: BufferExternalCallbackFn,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.hook_buffer_alloc.register_extern(cbk_fn));
+}
+
+/// Registers a BUFFER_FREE callback, invoked with the number of bytes released every
+/// time those buffers shrink, and when the connection parser is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_register_buffer_free(
+    cfg: *mut Config,
+    cbk_fn: BufferExternalCallbackFn,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.hook_buffer_free.register_extern(cbk_fn));
+}
+
 /// Configures whether backslash characters are treated as path segment separators. They
 /// are not on Unix systems, but are on Windows systems. If this setting is enabled, a path
 /// such as "/one\two/three" will be converted to "/one/two/three".
@@ -248,6 +330,77 @@ pub unsafe extern "C" fn htp_config_set_field_limit(cfg: *mut Config, field_limi
     cfg.as_mut().map(|cfg| cfg.set_field_limit(field_limit));
 }
 
+/// Configures the maximum size of the buffer LibHTP will use for request data when all data
+/// is not available in the current buffer. Overrides the field_limit for the request direction.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_request_field_limit(
+    cfg: *mut Config,
+    field_limit: libc::size_t,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.set_request_field_limit(field_limit));
+}
+
+/// Configures the maximum size of the buffer LibHTP will use for response data when all data
+/// is not available in the current buffer. Overrides the field_limit for the response direction.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_response_field_limit(
+    cfg: *mut Config,
+    field_limit: libc::size_t,
+) {
+    cfg.as_mut()
+        .map(|cfg| cfg.set_response_field_limit(field_limit));
+}
+
+/// Configures the buffering soft limit. When buffered request or response data grows over
+/// it, but stays within the field limit, the FIELD_SOFT_LIMIT callbacks are invoked.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_field_soft_limit(
+    cfg: *mut Config,
+    soft_limit: libc::size_t,
+) {
+    cfg.as_mut().map(|cfg| cfg.set_field_soft_limit(soft_limit));
+}
+
+/// Configures the maximum length of the request URI path. Longer normalized paths are
+/// truncated to the limit.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_uri_path_limit(cfg: *mut Config, limit: libc::size_t) {
+    cfg.as_mut().map(|cfg| cfg.set_uri_path_limit(limit));
+}
+
+/// Configures whether a response without a matching request fails parsing, instead of
+/// being attached to a synthetic transaction.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_tx_match_strict(cfg: *mut Config, strict: libc::c_int) {
+    cfg.as_mut().map(|cfg| cfg.set_tx_match_strict(strict == 1));
+}
+
+/// Configures whether the server personality is inferred from the Server header of the
+/// first response on a connection.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_auto_personality(cfg: *mut Config, enabled: libc::c_int) {
+    cfg.as_mut()
+        .map(|cfg| cfg.set_auto_personality(enabled == 1));
+}
+
+/// Configures whether header names with surrounding whitespace or non-token characters
+/// cause parsing to fail.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_strict_header_names(cfg: *mut Config, strict: libc::c_int) {
+    cfg.as_mut()
+        .map(|cfg| cfg.set_strict_header_names(strict == 1));
+}
+
+/// Configures the maximum number of header lines parsed for a request or a response.
+#[no_mangle]
+pub unsafe extern "C" fn htp_config_set_number_headers_limit(
+    cfg: *mut Config,
+    limit: libc::size_t,
+) {
+    cfg.as_mut().map(|cfg| cfg.set_number_headers_limit(limit));
+}
+
 /// Configures the maximum memlimit LibHTP will pass to liblzma.
 #[no_mangle]
 pub unsafe extern "C" fn htp_config_set_lzma_memlimit(cfg: *mut Config, memlimit: libc::size_t) {
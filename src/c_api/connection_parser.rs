@@ -4,6 +4,7 @@ use crate::{
     connection::Connection,
     connection_parser::{ConnectionParser, Data, HtpStreamState},
     transaction::Transaction,
+    HtpStatus,
 };
 use chrono::{DateTime, NaiveDateTime, Utc};
 use std::{convert::TryFrom, ffi::CStr};
@@ -278,6 +279,81 @@ pub unsafe extern "C" fn htp_connp_flush_incomplete_transactions(connp: *mut Con
         .map(|connp| connp.flush_incomplete_transactions());
 }
 
+/// Forces the request transaction currently being parsed to completion, running the
+/// request and transaction complete callbacks, without closing the connection.
+///
+/// Returns HTP_STATUS_OK on success or if no request is in progress; HTP_STATUS_ERROR otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_force_complete_in_tx(connp: *mut ConnectionParser) -> HtpStatus {
+    connp
+        .as_mut()
+        .map(|connp| connp.request_force_complete().into())
+        .unwrap_or(HtpStatus::ERROR)
+}
+
+/// Forces the response transaction currently being parsed to completion, running the
+/// response and transaction complete callbacks, without closing the connection.
+///
+/// Returns HTP_STATUS_OK on success or if no response is in progress; HTP_STATUS_ERROR otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_force_complete_out_tx(
+    connp: *mut ConnectionParser,
+) -> HtpStatus {
+    connp
+        .as_mut()
+        .map(|connp| connp.response_force_complete().into())
+        .unwrap_or(HtpStatus::ERROR)
+}
+
+/// Skips the rest of the response body of the transaction currently being parsed,
+/// without invoking the response body data callbacks for it.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_skip_out_body(connp: *mut ConnectionParser) {
+    connp.as_mut().map(|connp| connp.response_skip_body());
+}
+
+/// Returns the current state of the inbound (request) stream.
+///
+/// Returns HTP_STREAM_STATE_ERROR if connp is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_get_in_status(connp: *const ConnectionParser) -> HtpStreamState {
+    connp
+        .as_ref()
+        .map(|connp| connp.request_status)
+        .unwrap_or(HtpStreamState::ERROR)
+}
+
+/// Returns the current state of the outbound (response) stream.
+///
+/// Returns HTP_STREAM_STATE_ERROR if connp is NULL.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_get_out_status(
+    connp: *const ConnectionParser,
+) -> HtpStreamState {
+    connp
+        .as_ref()
+        .map(|connp| connp.response_status)
+        .unwrap_or(HtpStreamState::ERROR)
+}
+
+/// Returns the number of inbound bytes buffered while waiting for more data or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_get_in_buffered_size(connp: *const ConnectionParser) -> i64 {
+    connp
+        .as_ref()
+        .map(|connp| connp.request_buffered_size() as i64)
+        .unwrap_or(-1)
+}
+
+/// Returns the number of outbound bytes buffered while waiting for more data or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_connp_get_out_buffered_size(connp: *const ConnectionParser) -> i64 {
+    connp
+        .as_ref()
+        .map(|connp| connp.response_buffered_size() as i64)
+        .unwrap_or(-1)
+}
+
 /// Returns the number of bytes consumed from the current data chunks so far or -1 on error.
 #[no_mangle]
 pub unsafe extern "C" fn htp_connp_request_data_consumed(connp: *const ConnectionParser) -> i64 {
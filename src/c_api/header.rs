@@ -2,7 +2,7 @@
 use crate::{
     bstr::Bstr,
     c_api::bstr::bstr_ptr,
-    transaction::{Header, Headers},
+    transaction::{Header, Headers, HtpHeaderTerminator},
 };
 use std::convert::TryFrom;
 
@@ -139,3 +139,29 @@ pub unsafe extern "C" fn htp_header_value_len(header: *const Header) -> isize {
         .map(|header| isize::try_from(header.value.len()).unwrap_or(-1))
         .unwrap_or(-1)
 }
+
+/// Get how a header line was terminated.
+///
+/// header: Header pointer.
+///
+/// Returns the terminator or HTP_HEADER_TERMINATOR_ERROR on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_header_terminator(header: *const Header) -> HtpHeaderTerminator {
+    header
+        .as_ref()
+        .map(|header| header.terminator)
+        .unwrap_or(HtpHeaderTerminator::ERROR)
+}
+
+/// Get the header parsing flags of a header; a combination of HeaderParserFlags.
+///
+/// header: Header pointer.
+///
+/// Returns the flags or 0 on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_header_parser_flags(header: *const Header) -> u64 {
+    header
+        .as_ref()
+        .map(|header| header.parser_flags)
+        .unwrap_or(0)
+}
@@ -192,7 +192,7 @@ pub unsafe extern "C" fn htp_tx_request_headers(tx: *const Transaction) -> *cons
 #[no_mangle]
 pub unsafe extern "C" fn htp_tx_request_headers_size(tx: *const Transaction) -> isize {
     tx.as_ref()
-        .map(|tx| isize::try_from(tx.request_headers.size()).unwrap_or(-1))
+        .map(|tx| isize::try_from(tx.request_header_count()).unwrap_or(-1))
         .unwrap_or(-1)
 }
 
@@ -298,6 +298,32 @@ pub unsafe extern "C" fn htp_tx_request_auth_type(tx: *const Transaction) -> Htp
         .unwrap_or(HtpAuthType::ERROR)
 }
 
+/// Get the transaction's request authentication username.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the username or NULL on error or when no username was extracted.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_request_auth_username(tx: *const Transaction) -> *const Bstr {
+    tx.as_ref()
+        .and_then(|tx| tx.request_auth_username.as_ref())
+        .map(|username| username as *const Bstr)
+        .unwrap_or(std::ptr::null())
+}
+
+/// Get the transaction's request authentication password.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the password or NULL on error or when no password was extracted.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_request_auth_password(tx: *const Transaction) -> *const Bstr {
+    tx.as_ref()
+        .and_then(|tx| tx.request_auth_password.as_ref())
+        .map(|password| password as *const Bstr)
+        .unwrap_or(std::ptr::null())
+}
+
 /// Get a transaction's request hostname.
 ///
 /// tx: Transaction pointer.
@@ -344,6 +370,16 @@ pub unsafe extern "C" fn htp_tx_request_entity_len(tx: *const Transaction) -> i6
     tx.as_ref().map(|tx| tx.request_entity_len).unwrap_or(-1)
 }
 
+/// Get the number of raw bytes of the request stream attributed to a transaction.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the request total size or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_request_total_size(tx: *const Transaction) -> i64 {
+    tx.as_ref().map(|tx| tx.request_total_size).unwrap_or(-1)
+}
+
 /// Get a transaction's response line.
 ///
 /// tx: Transaction pointer.
@@ -382,6 +418,34 @@ pub unsafe extern "C" fn htp_tx_response_protocol_number(tx: *const Transaction)
         .unwrap_or(HtpProtocol::ERROR)
 }
 
+/// Get the connection disposition requested by the client.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the connection disposition or ERROR on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_request_connection(
+    tx: *const Transaction,
+) -> HtpConnectionDisposition {
+    tx.as_ref()
+        .map(|tx| tx.request_connection())
+        .unwrap_or(HtpConnectionDisposition::ERROR)
+}
+
+/// Get the connection disposition announced by the server.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the connection disposition or ERROR on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_response_connection(
+    tx: *const Transaction,
+) -> HtpConnectionDisposition {
+    tx.as_ref()
+        .map(|tx| tx.response_connection())
+        .unwrap_or(HtpConnectionDisposition::ERROR)
+}
+
 /// Get the transaction's response status.
 ///
 /// tx: Transaction pointer.
@@ -455,7 +519,7 @@ pub unsafe extern "C" fn htp_tx_response_headers(tx: *const Transaction) -> *con
 #[no_mangle]
 pub unsafe extern "C" fn htp_tx_response_headers_size(tx: *const Transaction) -> isize {
     tx.as_ref()
-        .map(|tx| isize::try_from(tx.response_headers.size()).unwrap_or(-1))
+        .map(|tx| isize::try_from(tx.response_header_count()).unwrap_or(-1))
         .unwrap_or(-1)
 }
 
@@ -516,6 +580,29 @@ pub unsafe extern "C" fn htp_tx_response_entity_len(tx: *const Transaction) -> i
     tx.as_ref().map(|tx| tx.response_entity_len).unwrap_or(-1)
 }
 
+/// Get the number of raw bytes of the response stream attributed to a transaction.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the response total size or -1 on error.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_response_total_size(tx: *const Transaction) -> i64 {
+    tx.as_ref().map(|tx| tx.response_total_size).unwrap_or(-1)
+}
+
+/// Get a transaction's raw response body, before dechunking and decompression.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the raw response body or NULL if it was not stored.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_response_body_raw(tx: *const Transaction) -> *const Bstr {
+    tx.as_ref()
+        .and_then(|tx| tx.response_body_raw.as_ref())
+        .map(|response_body_raw| response_body_raw as *const Bstr)
+        .unwrap_or(std::ptr::null())
+}
+
 /// Get a transaction's response content length.
 ///
 /// tx: Transaction pointer.
@@ -552,6 +639,17 @@ pub unsafe extern "C" fn htp_tx_flags(tx: *const Transaction) -> u64 {
     tx.as_ref().map(|tx| tx.flags).unwrap_or(0)
 }
 
+/// Get the transaction's second set of bit flags, see HtpFlags2.
+///
+/// tx: Transaction pointer.
+///
+/// Returns the flags represented as an integer or 0 if the flags are empty
+/// or a NULL ptr is passed as an argument.
+#[no_mangle]
+pub unsafe extern "C" fn htp_tx_flags2(tx: *const Transaction) -> u64 {
+    tx.as_ref().map(|tx| tx.flags2).unwrap_or(0)
+}
+
 /// Get the transaction's request progress.
 ///
 /// tx: Transaction pointer.
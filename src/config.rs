@@ -2,16 +2,22 @@ use crate::decompressors::Options;
 use crate::{
     error::Result,
     hook::{
-        DataHook, DataNativeCallbackFn, FileDataHook, LogHook, LogNativeCallbackFn, TxHook,
+        BufferHook, BufferNativeCallbackFn, DataHook, DataNativeCallbackFn, ErrorHook,
+        ErrorNativeCallbackFn, FieldSoftLimitHook, FieldSoftLimitNativeCallbackFn, FileDataHook,
+        LogHook, LogNativeCallbackFn, MultipartPartHook, MultipartPartNativeCallbackFn, TxHook,
         TxNativeCallbackFn,
     },
     log::HtpLogLevel,
+    request::HtpMethod,
     transaction::Param,
     unicode_bestfit_map::UnicodeBestfitMap,
     HtpStatus,
 };
 
 /// Configuration for libhtp parsing.
+///
+/// Cloning a Config copies the registered hooks too, so hooks registered on the clone
+/// are not seen by the original.
 #[derive(Clone)]
 pub struct Config {
     /// The maximum size of the buffer that is used when the current
@@ -61,9 +67,17 @@ pub struct Config {
     /// is not currently implemented. At the end of the request body
     /// there will be a call with the data set to None.
     pub hook_request_body_data: DataHook,
+    /// Raw request body data hook, invoked every time body data is available, before
+    /// any decompression takes place. Chunked data will be dechunked, but compressed
+    /// data is passed as seen on the wire. At the end of the request body there will
+    /// be a call with the data set to None.
+    pub hook_request_body_data_raw: DataHook,
     /// Request file data hook, which is invoked whenever request file data is
     /// available. Currently used only by the Multipart parser.
     pub hook_request_file_data: FileDataHook,
+    /// Multipart part complete hook, invoked every time the Multipart parser
+    /// finishes a part, with the part headers and accumulated data.
+    pub hook_multipart_part_complete: MultipartPartHook,
     /// Receives raw request trailer data, which can be available on requests that have
     /// chunked bodies. The data starts immediately after the zero-length chunk
     /// and includes the terminating empty line.
@@ -92,6 +106,11 @@ pub struct Config {
     /// in configuration. At the end of the response body there will be a call
     /// with the data pointer set to NULL.
     pub hook_response_body_data: DataHook,
+    /// Raw response body data hook, invoked every time body data is available, before
+    /// any decompression takes place. Chunked data will be dechunked, but compressed
+    /// data is passed as seen on the wire. At the end of the response body there will
+    /// be a call with the data pointer set to NULL.
+    pub hook_response_body_data_raw: DataHook,
     /// Receives raw response trailer data, which can be available on responses that have
     /// chunked bodies. The data starts immediately after the zero-length chunk
     /// and includes the terminating empty line.
@@ -109,6 +128,18 @@ pub struct Config {
     pub hook_transaction_complete: TxHook,
     /// Log hook, invoked every time the library wants to log.
     pub hook_log: LogHook,
+    /// Field soft limit hook, invoked with the transaction and the buffered size when
+    /// buffered request or response data grows over field_soft_limit.
+    pub hook_field_soft_limit: FieldSoftLimitHook,
+    /// Error hook, invoked with the connection parser and the most recent log message
+    /// when the request or response stream moves to the ERROR state.
+    pub hook_error: ErrorHook,
+    /// Buffer allocation hook, invoked with the connection parser and the number of bytes
+    /// by which the line and header buffers grew.
+    pub hook_buffer_alloc: BufferHook,
+    /// Buffer release hook, invoked with the connection parser and the number of bytes
+    /// by which the line and header buffers shrank or were released.
+    pub hook_buffer_free: BufferHook,
     /// Reaction to leading whitespace on the request line
     pub requestline_leading_whitespace_unwanted: HtpUnwanted,
     /// Whether to decompress compressed request bodies.
@@ -117,6 +148,96 @@ pub struct Config {
     pub compression_options: Options,
     /// Multipart configurations for file extraction.
     pub multipart_cfg: MultipartConfig,
+    /// Response status codes that cause the STATUS_INTERESTING flag to be raised
+    /// on the transaction. This does not change parsing in any way.
+    pub interesting_status_codes: Vec<u16>,
+    /// The maximum number of bytes a single transaction may accumulate across its
+    /// headers, parameters and bodies, or None for no limit.
+    pub tx_bytes_limit: Option<usize>,
+    /// Whether to stop processing the body data of a transaction once it exceeds
+    /// tx_bytes_limit. The connection itself continues to be parsed.
+    pub tx_bytes_limit_stop: bool,
+    /// Transfer-Encoding tokens which are considered acceptable. When empty, no
+    /// allowlist checking is performed.
+    pub transfer_encoding_allowlist: Vec<String>,
+    /// Whether a Transfer-Encoding token outside of the allowlist is treated as an error.
+    pub transfer_encoding_disallowed_error: bool,
+    /// Whether to store the raw response body bytes (before dechunking and
+    /// decompression) on the transaction.
+    pub response_body_raw_enabled: bool,
+    /// The maximum number of raw response body bytes stored per transaction.
+    pub response_body_raw_limit: usize,
+    /// Whether a bare CR is accepted as a line terminator in request headers.
+    pub request_bare_cr_eol: bool,
+    /// Whether a request path containing control characters is treated as an error.
+    pub path_control_chars_reject: bool,
+    /// Whether response header names are stored in canonical form, with the
+    /// original name kept in Header::raw_name.
+    pub response_header_name_canonicalize: bool,
+    /// The buffering limit used for request data, overriding field_limit when set.
+    pub request_field_limit: Option<usize>,
+    /// The buffering limit used for response data, overriding field_limit when set.
+    pub response_field_limit: Option<usize>,
+    /// The header value length above which headers are flagged with FIELD_VALUE_SOFT_LIMIT.
+    pub header_value_soft_limit: Option<usize>,
+    /// Whether a URI fragment is included in the query string passed to parameter parsing.
+    pub query_fragment_included: bool,
+    /// Whether HTTP/1.1 responses with a body and no Date header are flagged.
+    pub response_date_required: bool,
+    /// The maximum number of header lines parsed per request or response.
+    pub number_headers_limit: Option<usize>,
+    /// The maximum number of continuation lines folded into a single header value.
+    pub header_folding_limit: Option<usize>,
+    /// Whether a second response line after a final status is treated as an error.
+    pub response_multiple_status_lines_reject: bool,
+    /// Whether a request line containing raw bytes with the high bit set is treated as an error.
+    pub request_line_high_bit_reject: bool,
+    /// Whether the request body content type is sniffed from the first body bytes.
+    pub request_body_sniffing: bool,
+    /// The maximum number of cookies parsed per transaction.
+    pub cookies_limit: Option<usize>,
+    /// The maximum number of cookie bytes (names and values) parsed per transaction.
+    pub cookies_size_limit: Option<usize>,
+    /// Whether HTTP/0.9 requests are accepted. See set_allow_http_0_9 for responses.
+    pub allow_http_0_9: bool,
+    /// When data which does not look like a response line is treated as response body.
+    pub response_body_fallback: HtpResponseBodyFallback,
+    /// Whether whitespace between a chunk size and the line ending is treated as an error.
+    pub chunked_length_whitespace_reject: bool,
+    /// The maximum number of chunks parsed in a chunked response body.
+    pub max_chunks: Option<u32>,
+    /// Whether the response body length is checked against Content-Length on completion.
+    pub response_content_length_validate: bool,
+    /// Whether a response body length mismatch causes parsing to fail.
+    pub response_content_length_mismatch_reject: bool,
+    /// Whether response headers are checked for injection from request CR/LF data.
+    pub response_header_injection_detect: bool,
+    /// Whether to extract matrix parameters from the request path.
+    pub parse_matrix_params: bool,
+    /// The request methods that are allowed. Empty means that all methods are allowed.
+    pub allowed_methods: Vec<HtpMethod>,
+    /// Reaction to a request method that is not allowed.
+    pub method_disallowed_unwanted: HtpUnwanted,
+    /// Whether a request target with more than one "?" causes parsing to fail.
+    pub uri_multiple_query_marks_reject: bool,
+    /// Whether a response status code that is not exactly three digits causes parsing to fail.
+    pub response_status_format_reject: bool,
+    /// Reaction to a repeated singular response header, such as Content-Length.
+    pub response_dup_header_policy: HtpDupHeaderPolicy,
+    /// The buffering size over which the field soft limit hook is invoked. Only takes
+    /// effect when below the field limit of the direction.
+    pub field_soft_limit: Option<usize>,
+    /// The maximum length of the request URI path; longer normalized paths are truncated.
+    pub uri_path_limit: Option<usize>,
+    /// Whether a response without a matching request causes parsing to fail, instead of
+    /// being attached to a synthetic transaction.
+    pub tx_match_strict: bool,
+    /// Whether the server personality is inferred from the Server header of the first
+    /// response on a connection.
+    pub auto_personality: bool,
+    /// Whether header names with surrounding whitespace or non-token characters cause
+    /// parsing to fail.
+    pub strict_header_names: bool,
 }
 
 impl Default for Config {
@@ -139,7 +260,9 @@ impl Default for Config {
             hook_request_header_data: DataHook::default(),
             hook_request_headers: TxHook::default(),
             hook_request_body_data: DataHook::default(),
+            hook_request_body_data_raw: DataHook::default(),
             hook_request_file_data: FileDataHook::default(),
+            hook_multipart_part_complete: MultipartPartHook::default(),
             hook_request_trailer_data: DataHook::default(),
             hook_request_trailer: TxHook::default(),
             hook_request_complete: TxHook::default(),
@@ -148,15 +271,60 @@ impl Default for Config {
             hook_response_header_data: DataHook::default(),
             hook_response_headers: TxHook::default(),
             hook_response_body_data: DataHook::default(),
+            hook_response_body_data_raw: DataHook::default(),
             hook_response_trailer_data: DataHook::default(),
             hook_response_trailer: TxHook::default(),
             hook_response_complete: TxHook::default(),
             hook_transaction_complete: TxHook::default(),
             hook_log: LogHook::default(),
+            hook_field_soft_limit: FieldSoftLimitHook::default(),
+            hook_error: ErrorHook::default(),
+            hook_buffer_alloc: BufferHook::default(),
+            hook_buffer_free: BufferHook::default(),
             requestline_leading_whitespace_unwanted: HtpUnwanted::IGNORE,
             request_decompression_enabled: false,
             compression_options: Options::default(),
             multipart_cfg: Default::default(),
+            interesting_status_codes: Vec::new(),
+            tx_bytes_limit: None,
+            tx_bytes_limit_stop: false,
+            transfer_encoding_allowlist: Vec::new(),
+            transfer_encoding_disallowed_error: false,
+            response_body_raw_enabled: false,
+            response_body_raw_limit: 1_048_576,
+            request_bare_cr_eol: false,
+            path_control_chars_reject: false,
+            response_header_name_canonicalize: false,
+            request_field_limit: None,
+            response_field_limit: None,
+            header_value_soft_limit: None,
+            query_fragment_included: false,
+            response_date_required: false,
+            number_headers_limit: None,
+            header_folding_limit: None,
+            response_multiple_status_lines_reject: false,
+            request_line_high_bit_reject: false,
+            request_body_sniffing: false,
+            cookies_limit: None,
+            cookies_size_limit: None,
+            allow_http_0_9: true,
+            response_body_fallback: HtpResponseBodyFallback::ALWAYS,
+            chunked_length_whitespace_reject: false,
+            max_chunks: None,
+            response_content_length_validate: false,
+            response_content_length_mismatch_reject: false,
+            response_header_injection_detect: false,
+            parse_matrix_params: false,
+            allowed_methods: Vec::new(),
+            method_disallowed_unwanted: HtpUnwanted::IGNORE,
+            uri_multiple_query_marks_reject: false,
+            response_status_format_reject: false,
+            response_dup_header_policy: HtpDupHeaderPolicy::ALLOW,
+            field_soft_limit: None,
+            uri_path_limit: None,
+            tx_match_strict: false,
+            auto_personality: false,
+            strict_header_names: false,
         }
     }
 }
@@ -209,6 +377,8 @@ pub struct DecoderConfig {
     pub utf8_invalid_unwanted: HtpUnwanted,
     /// Convert UTF-8 characters into bytes using best-fit mapping.
     pub utf8_convert_bestfit: bool,
+    /// Convert UTF-8 characters in query string parameters into bytes using best-fit mapping.
+    pub utf8_convert_bestfit_query: bool,
     /// Best-fit map for UTF-8 decoding.
     pub bestfit_map: UnicodeBestfitMap,
 }
@@ -236,6 +406,7 @@ impl Default for DecoderConfig {
             normalized_uri_include_all: false,
             utf8_invalid_unwanted: HtpUnwanted::IGNORE,
             utf8_convert_bestfit: false,
+            utf8_convert_bestfit_query: false,
             bestfit_map: UnicodeBestfitMap::default(),
         }
     }
@@ -262,6 +433,25 @@ impl Default for MultipartConfig {
     }
 }
 
+/// Summary of the active server personality and the decoder settings in effect.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PersonalitySettings {
+    /// The active server personality.
+    pub personality: HtpServerPersonality,
+    /// Whether backslash characters are converted to forward slashes.
+    pub backslash_convert_slashes: bool,
+    /// Whether %u-encoded sequences are decoded.
+    pub u_encoding_decode: bool,
+    /// Whether UTF-8 data is converted using best-fit mapping.
+    pub utf8_convert_bestfit: bool,
+    /// Whether encoded path segment separators are decoded.
+    pub path_separators_decode: bool,
+    /// Whether consecutive path segment separators are compressed.
+    pub path_separators_compress: bool,
+    /// Whether input data is converted to lowercase.
+    pub convert_lowercase: bool,
+}
+
 /// Enumerates the possible server personalities.
 /// cbindgen:rename-all=QualifiedScreamingSnakeCase
 #[repr(C)]
@@ -290,6 +480,32 @@ pub enum HtpServerPersonality {
     APACHE_2,
 }
 
+impl HtpServerPersonality {
+    /// Infers the personality from a Server header value, such as "Microsoft-IIS/7.5" or
+    /// "Apache/2.4.41 (Unix)". Only the first product token is considered and names are
+    /// compared without case.
+    ///
+    /// Returns None if the product is not clearly recognized.
+    pub fn from_server_header(server: &[u8]) -> Option<Self> {
+        let product = server
+            .split(|c| c.is_ascii_whitespace())
+            .find(|token| !token.is_empty())?
+            .to_ascii_lowercase();
+        let mut parts = product.splitn(2, |c| *c == b'/');
+        let name = parts.next()?;
+        let version = parts.next();
+        match (name, version) {
+            (b"apache", _) => Some(Self::APACHE_2),
+            (b"nginx", _) => Some(Self::GENERIC),
+            (b"microsoft-iis", Some(b"5.1")) => Some(Self::IIS_5_1),
+            (b"microsoft-iis", Some(b"6.0")) => Some(Self::IIS_6_0),
+            (b"microsoft-iis", Some(b"7.0")) => Some(Self::IIS_7_0),
+            (b"microsoft-iis", Some(b"7.5")) => Some(Self::IIS_7_5),
+            _ => None,
+        }
+    }
+}
+
 /// Enumerates the ways in which servers respond to malformed data.
 /// cbindgen:rename-all=QualifiedScreamingSnakeCase
 #[repr(C)]
@@ -316,6 +532,34 @@ pub enum HtpUrlEncodingHandling {
     PROCESS_INVALID,
 }
 
+/// Enumerates when data which does not look like a response line is treated as
+/// response body, as browsers do.
+/// cbindgen:rename-all=QualifiedScreamingSnakeCase
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HtpResponseBodyFallback {
+    /// Treat an invalid response line, and unexpected data after a response, as body.
+    ALWAYS,
+    /// Never fall back to body: flag STATUS_LINE_INVALID and fail parsing instead.
+    NEVER,
+    /// Only treat an invalid response line as body, not unexpected data after a response.
+    ONLY_FIRST_LINE,
+}
+
+/// Enumerates the possible reactions to a repeated response header which must appear
+/// only once (Content-Length, Transfer-Encoding and Content-Type).
+/// cbindgen:rename-all=QualifiedScreamingSnakeCase
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HtpDupHeaderPolicy {
+    /// Only mark the header itself with FIELD_REPEATED.
+    ALLOW,
+    /// Also flag the transaction with FIELD_REPEATED and log a warning.
+    FLAG,
+    /// Flag the transaction and fail parsing of the stream.
+    ERROR,
+}
+
 impl Config {
     /// Registers a callback that is invoked every time there is a log message with
     /// severity equal and higher than the configured log level.
@@ -335,6 +579,14 @@ impl Config {
         self.hook_request_body_data.register(cbk_fn);
     }
 
+    /// Registers a request_body_data_raw callback, which is invoked with the request
+    /// body data before decompression. The decompressed data continues to be delivered to
+    /// the request_body_data callbacks. When the body is not compressed, both callbacks
+    /// receive identical data.
+    pub fn register_request_body_data_raw(&mut self, cbk_fn: DataNativeCallbackFn) {
+        self.hook_request_body_data_raw.register(cbk_fn);
+    }
+
     /// Registers a request_header_data callback, which is invoked when we see header
     /// data. This callback receives raw header data as seen on the connection, including
     /// the terminating line and anything seen after the request line.
@@ -379,6 +631,16 @@ impl Config {
         self.hook_response_body_data.register(cbk_fn);
     }
 
+    /// Registers a response_body_data_raw callback, which is invoked with the response
+    /// body data before decompression. The decompressed data continues to be delivered to
+    /// the response_body_data callbacks. Because this hook runs before decompression, it
+    /// will still see the compressed bytes of a chunk that subsequently triggers the
+    /// compression bomb or time limits. When the body is not compressed, both callbacks
+    /// receive identical data.
+    pub fn register_response_body_data_raw(&mut self, cbk_fn: DataNativeCallbackFn) {
+        self.hook_response_body_data_raw.register(cbk_fn);
+    }
+
     /// Registers a response_complete callback, which is invoked when we see the
     /// first bytes of data from a response.
     pub fn register_response_complete(&mut self, cbk_fn: TxNativeCallbackFn) {
@@ -431,6 +693,40 @@ impl Config {
         self.hook_transaction_complete.register(cbk_fn);
     }
 
+    /// Registers a multipart_part_complete callback, which is invoked every time the
+    /// Multipart parser finishes a part.
+    pub fn register_multipart_part_complete(&mut self, cbk_fn: MultipartPartNativeCallbackFn) {
+        self.hook_multipart_part_complete.register(cbk_fn);
+    }
+
+    /// Registers a field_soft_limit callback, which is invoked when buffered request or
+    /// response data grows over the soft limit set with set_field_soft_limit.
+    pub fn register_field_soft_limit(&mut self, cbk_fn: FieldSoftLimitNativeCallbackFn) {
+        self.hook_field_soft_limit.register(cbk_fn);
+    }
+
+    /// Registers an error callback, which is invoked once when the request or response
+    /// stream moves to the ERROR state, with the log message that was issued last.
+    pub fn register_error(&mut self, cbk_fn: ErrorNativeCallbackFn) {
+        self.hook_error.register(cbk_fn);
+    }
+
+    /// Registers a buffer_alloc callback, which is invoked with the number of bytes added
+    /// every time the buffers holding an incomplete request or response line or header
+    /// grow. Buffers are still allocated by the global allocator; together with the
+    /// buffer_free callback, this lets integrators account for the memory held by
+    /// buffering. An error returned by the callback fails the stream.
+    pub fn register_buffer_alloc(&mut self, cbk_fn: BufferNativeCallbackFn) {
+        self.hook_buffer_alloc.register(cbk_fn);
+    }
+
+    /// Registers a buffer_free callback, which is invoked with the number of bytes
+    /// released every time those buffers shrink, and when the connection parser is
+    /// dropped. Every byte reported to buffer_alloc is eventually reported here.
+    pub fn register_buffer_free(&mut self, cbk_fn: BufferNativeCallbackFn) {
+        self.hook_buffer_free.register(cbk_fn);
+    }
+
     /// Enable or disable the double decoding of the path in the normalized uri
     pub fn set_double_decode_normalized_path(&mut self, double_decode_normalized_path: bool) {
         self.decoder_cfg.double_decode_normalized_path = double_decode_normalized_path;
@@ -455,9 +751,12 @@ impl Config {
 
     /// Configures the maximum size of the buffer LibHTP will use when all data is not available
     /// in the current buffer (e.g., a very long header line that might span several packets). This
-    /// limit is controlled by the field_limit parameter.
+    /// limit is controlled by the field_limit parameter. The limit applies to both directions,
+    /// replacing any limit previously set with set_request_field_limit or set_response_field_limit.
     pub fn set_field_limit(&mut self, field_limit: usize) {
         self.field_limit = field_limit;
+        self.request_field_limit = None;
+        self.response_field_limit = None;
     }
 
     /// Enable or disable request cookie parsing. Enabled by default.
@@ -530,6 +829,41 @@ impl Config {
         Ok(())
     }
 
+    /// Resets every setting that a server personality controls to its default value,
+    /// leaving all other settings as they are. Applying a personality afterwards
+    /// gives the same result regardless of which personality was active before.
+    pub fn reset_server_personality(&mut self) {
+        let defaults = Config::default();
+        self.set_backslash_convert_slashes(defaults.decoder_cfg.backslash_convert_slashes);
+        self.set_path_separators_decode(defaults.decoder_cfg.path_separators_decode);
+        self.set_path_separators_compress(defaults.decoder_cfg.path_separators_compress);
+        self.set_convert_lowercase(defaults.decoder_cfg.convert_lowercase);
+        self.set_utf8_convert_bestfit(defaults.decoder_cfg.utf8_convert_bestfit);
+        self.set_u_encoding_decode(defaults.decoder_cfg.u_encoding_decode);
+        self.set_u_encoding_unwanted(defaults.decoder_cfg.u_encoding_unwanted);
+        self.set_url_encoding_invalid_handling(defaults.decoder_cfg.url_encoding_invalid_handling);
+        self.set_url_encoding_invalid_unwanted(defaults.decoder_cfg.url_encoding_invalid_unwanted);
+        self.set_control_chars_unwanted(defaults.decoder_cfg.control_chars_unwanted);
+        self.set_requestline_leading_whitespace_unwanted(
+            defaults.requestline_leading_whitespace_unwanted,
+        );
+        self.server_personality = defaults.server_personality;
+    }
+
+    /// Reports the active server personality along with the effective decoder
+    /// settings, which allows verifying what a personality has configured.
+    pub fn get_server_personality_settings(&self) -> PersonalitySettings {
+        PersonalitySettings {
+            personality: self.server_personality,
+            backslash_convert_slashes: self.decoder_cfg.backslash_convert_slashes,
+            u_encoding_decode: self.decoder_cfg.u_encoding_decode,
+            utf8_convert_bestfit: self.decoder_cfg.utf8_convert_bestfit,
+            path_separators_decode: self.decoder_cfg.path_separators_decode,
+            path_separators_compress: self.decoder_cfg.path_separators_compress,
+            convert_lowercase: self.decoder_cfg.convert_lowercase,
+        }
+    }
+
     /// Configures whether transactions will be automatically destroyed once they
     /// are processed and all callbacks invoked. This option is appropriate for
     /// programs that process transactions as they are processed.
@@ -615,6 +949,12 @@ impl Config {
         self.decoder_cfg.utf8_convert_bestfit = enabled;
     }
 
+    /// Controls whether decoded query string parameters should be treated as UTF-8 and
+    /// converted to a single-byte stream using best-fit mapping. Disabled by default.
+    pub fn set_utf8_convert_bestfit_query(&mut self, enabled: bool) {
+        self.decoder_cfg.utf8_convert_bestfit_query = enabled;
+    }
+
     /// Configures reaction to %u-encoded sequences in input data.
     pub fn set_u_encoding_unwanted(&mut self, unwanted: HtpUnwanted) {
         self.decoder_cfg.u_encoding_unwanted = unwanted;
@@ -649,4 +989,773 @@ impl Config {
     pub fn set_decompression_layer_limit(&mut self, limit: Option<usize>) {
         self.compression_options.set_layer_limit(limit);
     }
+
+    /// Configures the response status codes which are considered interesting. When a
+    /// response status matches one of them, the STATUS_INTERESTING flag is set on the
+    /// transaction. Only the flag is affected; parsing continues as usual.
+    pub fn set_interesting_status_codes(&mut self, codes: &[u16]) {
+        self.interesting_status_codes = codes.to_vec();
+    }
+
+    /// Configures the maximum number of bytes a single transaction may accumulate
+    /// (headers, parameters, cookies and bodies combined). Exceeding the limit raises
+    /// the TX_BYTES_LIMIT flag on the transaction. This complements the field_limit,
+    /// which only applies to individual buffered fields.
+    pub fn set_tx_bytes_limit(&mut self, limit: Option<usize>) {
+        self.tx_bytes_limit = limit;
+    }
+
+    /// Configures whether body data of a transaction that exceeded the tx_bytes_limit
+    /// is still processed. When enabled, no further body data is decompressed, parsed
+    /// or passed to callbacks for that transaction, while subsequent transactions on
+    /// the same connection are processed as usual.
+    pub fn set_tx_bytes_limit_stop(&mut self, stop: bool) {
+        self.tx_bytes_limit_stop = stop;
+    }
+
+    /// Configures the allowlist of acceptable Transfer-Encoding tokens (e.g. "chunked").
+    /// Tokens are compared case-insensitively. Any request or response Transfer-Encoding
+    /// token outside the list raises the TRANSFER_ENCODING_DISALLOWED flag. An empty list
+    /// disables the check.
+    pub fn set_transfer_encoding_allowlist(&mut self, tokens: &[&str]) {
+        self.transfer_encoding_allowlist = tokens.iter().map(|token| token.to_string()).collect();
+    }
+
+    /// Configures whether a Transfer-Encoding token outside of the allowlist causes
+    /// parsing of the stream to fail, instead of only being flagged.
+    pub fn set_transfer_encoding_disallowed_error(&mut self, error: bool) {
+        self.transfer_encoding_disallowed_error = error;
+    }
+
+    /// Configures whether the raw response body, as seen on the wire (still chunk-framed
+    /// and compressed), is stored in Transaction::response_body_raw. Disabled by default.
+    pub fn set_response_body_raw(&mut self, enabled: bool) {
+        self.response_body_raw_enabled = enabled;
+    }
+
+    /// Configures the maximum number of raw response body bytes stored per transaction.
+    /// Any bytes beyond the limit are not stored.
+    pub fn set_response_body_raw_limit(&mut self, limit: usize) {
+        self.response_body_raw_limit = limit;
+    }
+
+    /// Configures whether a bare CR (not followed by LF) terminates a request header
+    /// line, as some servers do. When it happens, the transaction is flagged with
+    /// REQUEST_BARE_CR_EOL. Disabled by default.
+    pub fn set_request_bare_cr_eol(&mut self, enabled: bool) {
+        self.request_bare_cr_eol = enabled;
+    }
+
+    /// Configures whether a request path containing control characters (other than
+    /// NUL, CR and LF) causes parsing of the stream to fail, instead of only being
+    /// flagged with PATH_CONTROL_CHARS.
+    pub fn set_path_control_chars_reject(&mut self, reject: bool) {
+        self.path_control_chars_reject = reject;
+    }
+
+    /// Configures whether response header names are converted to canonical form
+    /// (e.g. "content-TYPE" becomes "Content-Type"). The name as seen on the wire is
+    /// kept in Header::raw_name. Disabled by default.
+    pub fn set_response_header_name_canonicalize(&mut self, enabled: bool) {
+        self.response_header_name_canonicalize = enabled;
+    }
+
+    /// Configures the maximum size of the buffer LibHTP will use for request data when all data
+    /// is not available in the current buffer. Overrides field_limit for the request direction.
+    pub fn set_request_field_limit(&mut self, field_limit: usize) {
+        self.request_field_limit = Some(field_limit);
+    }
+
+    /// Configures the maximum size of the buffer LibHTP will use for response data when all data
+    /// is not available in the current buffer. Overrides field_limit for the response direction.
+    pub fn set_response_field_limit(&mut self, field_limit: usize) {
+        self.response_field_limit = Some(field_limit);
+    }
+
+    /// Configures the soft limit on the length of a single header value, in either direction.
+    /// Longer values are kept and parsing continues, but the header and the transaction are
+    /// flagged with FIELD_VALUE_SOFT_LIMIT. Disabled (None) by default.
+    pub fn set_header_value_soft_limit(&mut self, limit: Option<usize>) {
+        self.header_value_soft_limit = limit;
+    }
+
+    /// Configures whether a fragment present in the request URI is appended to the
+    /// query string before parsing the query parameters, as some servers do, instead
+    /// of being stripped. Disabled by default.
+    pub fn set_query_fragment_included(&mut self, included: bool) {
+        self.query_fragment_included = included;
+    }
+
+    /// Configures whether an HTTP/1.1 response with a body that lacks a Date header
+    /// flags the transaction with RESPONSE_DATE_MISSING. Disabled by default.
+    pub fn set_response_date_required(&mut self, required: bool) {
+        self.response_date_required = required;
+    }
+
+    /// Configures the maximum number of header lines parsed for a request or a response,
+    /// counting repeated headers and trailers. A stream that goes over the limit fails
+    /// with an error and the transaction is flagged with HEADERS_TOO_MANY, so that no
+    /// header, framing headers included, is ever silently dropped. Unlimited by default.
+    pub fn set_number_headers_limit(&mut self, limit: usize) {
+        self.number_headers_limit = Some(limit);
+    }
+
+    /// Configures the maximum number of continuation lines folded into a single request
+    /// or response header value. Further continuation lines are dropped, and the header
+    /// and the transaction are flagged with FIELD_FOLDING_LIMIT. Unlimited by default.
+    pub fn set_header_folding_limit(&mut self, limit: usize) {
+        self.header_folding_limit = Some(limit);
+    }
+
+    /// Configures whether a second response line, seen for a transaction which already
+    /// had a final (non-1xx) status, causes parsing of the stream to fail, instead of only
+    /// being flagged with RESPONSE_MULTIPLE_STATUS_LINES.
+    pub fn set_response_multiple_status_lines_reject(&mut self, reject: bool) {
+        self.response_multiple_status_lines_reject = reject;
+    }
+
+    /// Configures whether a request line containing raw bytes >= 0x80 causes parsing of
+    /// the stream to fail, instead of only being flagged with REQUEST_LINE_HIGH_BIT. Such
+    /// bytes often mean a binary protocol was sent to an HTTP port.
+    pub fn set_request_line_high_bit_reject(&mut self, reject: bool) {
+        self.request_line_high_bit_reject = reject;
+    }
+
+    /// Configures whether the content type of request bodies is guessed from the first
+    /// body bytes and stored in Transaction::request_content_type_sniffed. A sniffed type
+    /// from a different family than the declared Content-Type (see util::content_type_matches)
+    /// is flagged with REQUEST_CONTENT_TYPE_MISMATCH.
+    /// Sniffing is best-effort only. Disabled by default.
+    pub fn set_request_body_sniffing(&mut self, enabled: bool) {
+        self.request_body_sniffing = enabled;
+    }
+
+    /// Configures the maximum number of cookies parsed per transaction. Any further
+    /// cookies are dropped and the transaction is flagged with COOKIES_LIMIT.
+    pub fn set_cookies_limit(&mut self, limit: usize) {
+        self.cookies_limit = Some(limit);
+    }
+
+    /// Configures the maximum number of cookie bytes, counting names and values, parsed
+    /// per transaction. The cookie crossing the limit and any further cookies are dropped
+    /// and the transaction is flagged with COOKIES_LIMIT.
+    pub fn set_cookies_size_limit(&mut self, limit: usize) {
+        self.cookies_size_limit = Some(limit);
+    }
+
+    /// Configures whether HTTP/0.9 is accepted. When disabled, a request line without a
+    /// protocol that is not followed by headers causes parsing of the stream to fail.
+    /// Enabled by default.
+    ///
+    /// On the response side, HTTP/0.9 is a response line which does not look like one
+    /// and is treated as the start of the body, so this also sets response_body_fallback:
+    /// to NEVER when disabled, and back to the default ALWAYS when enabled. Call
+    /// set_response_body_fallback afterwards to pick another response behavior.
+    pub fn set_allow_http_0_9(&mut self, allow: bool) {
+        self.allow_http_0_9 = allow;
+        self.response_body_fallback = if allow {
+            HtpResponseBodyFallback::ALWAYS
+        } else {
+            HtpResponseBodyFallback::NEVER
+        };
+    }
+
+    /// Configures when data which does not look like a response line is treated as
+    /// response body: both when a response line is expected and for unexpected data after
+    /// a response (ALWAYS, the default), never (NEVER), or only when a response line is
+    /// expected (ONLY_FIRST_LINE).
+    pub fn set_response_body_fallback(&mut self, fallback: HtpResponseBodyFallback) {
+        self.response_body_fallback = fallback;
+    }
+
+    /// Configures whether whitespace between a chunk size and the line ending, as in
+    /// "1a \r\n", causes parsing of the stream to fail, instead of only being flagged with
+    /// CHUNKED_LENGTH_WHITESPACE. Servers disagree on accepting such lines.
+    pub fn set_chunked_length_whitespace_reject(&mut self, reject: bool) {
+        self.chunked_length_whitespace_reject = reject;
+    }
+
+    /// Configures the maximum number of non-empty chunks parsed in a chunked response body.
+    /// Once the limit is exceeded, the transaction is flagged with RESPONSE_TOO_MANY_CHUNKS
+    /// and parsing of the response stream fails with an error. Unlimited by default.
+    pub fn set_max_chunks(&mut self, limit: u32) {
+        self.max_chunks = Some(limit);
+    }
+
+    /// Configures whether the number of body bytes delivered for a response with a
+    /// Content-Length is compared with the declared length when the response completes.
+    /// A short or over-long body flags the transaction with RESPONSE_CONTENT_LENGTH_MISMATCH.
+    pub fn set_response_content_length_validate(&mut self, validate: bool) {
+        self.response_content_length_validate = validate;
+    }
+
+    /// Configures whether a response body length mismatch, detected when
+    /// response_content_length_validate is enabled, causes parsing of the stream to fail
+    /// instead of only being flagged.
+    pub fn set_response_content_length_mismatch_reject(&mut self, reject: bool) {
+        self.response_content_length_mismatch_reject = reject;
+    }
+
+    /// Configures whether response headers are checked for header injection. When a request
+    /// parameter or header value contains CR or LF, each line after the first is compared
+    /// with the response headers, and a match sets the RESPONSE_HEADER_INJECTION flag.
+    /// The check is best-effort: it only spots injected lines reflected verbatim.
+    pub fn set_response_header_injection_detect(&mut self, detect: bool) {
+        self.response_header_injection_detect = detect;
+    }
+
+    /// Configures whether matrix parameters, as in "/a;jsessionid=x/b", are extracted from
+    /// the raw request path into Transaction::request_matrix_params. Disabled by default.
+    pub fn set_parse_matrix_params(&mut self, parse_matrix_params: bool) {
+        self.parse_matrix_params = parse_matrix_params;
+    }
+
+    /// Configures the request methods that are allowed. Requests using any other method,
+    /// including unknown ones unless HtpMethod::UNKNOWN is listed, are flagged with
+    /// REQUEST_METHOD_DISALLOWED in Transaction::flags2. An empty list, the default,
+    /// allows all methods.
+    pub fn set_allowed_methods(&mut self, methods: &[HtpMethod]) {
+        self.allowed_methods = methods.to_vec();
+    }
+
+    /// Configures the desired response status code for a request method that is not
+    /// allowed.
+    pub fn set_method_disallowed_unwanted(&mut self, unwanted: HtpUnwanted) {
+        self.method_disallowed_unwanted = unwanted;
+    }
+
+    /// Configures whether a request target with more than one "?", as in "/p?a=1?b=2",
+    /// causes parsing of the stream to fail. By default everything after the first "?" is
+    /// the query and the transaction is only flagged with URI_MULTIPLE_QUERY_MARKS.
+    pub fn set_uri_multiple_query_marks_reject(&mut self, reject: bool) {
+        self.uri_multiple_query_marks_reject = reject;
+    }
+
+    /// Configures whether a response status code that is not exactly three digits, such as
+    /// "0200" or "20", causes parsing of the stream to fail, instead of only being flagged
+    /// with STATUS_LINE_INVALID. A status with leading zeros is still parsed as its value.
+    pub fn set_response_status_format_reject(&mut self, reject: bool) {
+        self.response_status_format_reject = reject;
+    }
+
+    /// Configures the reaction to a repeated Content-Length, Transfer-Encoding or
+    /// Content-Type response header: only mark the header (ALLOW, the default), also flag
+    /// the transaction with FIELD_REPEATED (FLAG), or fail parsing of the stream (ERROR).
+    pub fn set_response_dup_header_policy(&mut self, policy: HtpDupHeaderPolicy) {
+        self.response_dup_header_policy = policy;
+    }
+
+    /// Configures the buffering soft limit. When the data buffered for a request or response
+    /// field grows over it, but stays within the field limit, the field_soft_limit callbacks
+    /// are invoked with the buffered size and parsing continues. Not set by default.
+    /// A soft limit at or above the field limit of a direction never fires there, as the
+    /// hard limit fails parsing first.
+    pub fn set_field_soft_limit(&mut self, soft_limit: usize) {
+        self.field_soft_limit = Some(soft_limit);
+    }
+
+    /// Configures the maximum length of the request URI path. When the path is longer, the
+    /// transaction is flagged with REQUEST_PATH_TOO_LONG in flags2, a warning is logged
+    /// and the normalized path is truncated to the limit; the raw path is kept intact.
+    /// Unlimited by default.
+    pub fn set_uri_path_limit(&mut self, limit: usize) {
+        self.uri_path_limit = Some(limit);
+    }
+
+    /// Configures whether responses must match a request seen on the connection. By
+    /// default a response without a request is attached to a transaction with the
+    /// synthetic request URI "/libhtp::request_uri_not_seen". When enabled, such a
+    /// response fails parsing of the response stream instead, which suits request and
+    /// response streams that are fed independently and may be out of sync.
+    pub fn set_tx_match_strict(&mut self, strict: bool) {
+        self.tx_match_strict = strict;
+    }
+
+    /// Configures whether the server personality is inferred from the Server header of
+    /// the first response on a connection. When the header clearly names a known product
+    /// (Apache, Microsoft-IIS 5.1 to 7.5, or nginx, which gets the generic personality),
+    /// the matching personality is applied to the following transactions of that
+    /// connection only. Disabled by default.
+    pub fn set_auto_personality(&mut self, enabled: bool) {
+        self.auto_personality = enabled;
+    }
+
+    /// Configures whether request and response header names are parsed strictly. By
+    /// default a name with leading or trailing whitespace, or with characters that are not
+    /// token characters, is accepted and the header flagged. When enabled, such a header
+    /// fails parsing of the stream instead.
+    pub fn set_strict_header_names(&mut self, strict: bool) {
+        self.strict_header_names = strict;
+    }
+}
+
+/// Errors reported when building a Config with a ConfigBuilder.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ConfigError {
+    /// The requested server personality is not supported.
+    UnsupportedPersonality(HtpServerPersonality),
+    /// The best-fit replacement byte is not a printable ASCII character.
+    InvalidReplacementByte(u8),
+}
+
+/// Builder producing an owned Config. Each method mirrors the Config setter of the
+/// same name, and is applied in the order it is called.
+///
+/// ```
+/// use htp::config::{ConfigBuilder, HtpServerPersonality};
+///
+/// let cfg = ConfigBuilder::new()
+///     .server_personality(HtpServerPersonality::IIS_7_5)
+///     .parse_urlencoded(true)
+///     .build()
+///     .unwrap();
+/// assert!(cfg.parse_urlencoded);
+/// ```
+#[derive(Clone)]
+pub struct ConfigBuilder {
+    cfg: Config,
+    error: Option<ConfigError>,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigBuilder {
+    /// Make a new builder starting from the default configuration.
+    pub fn new() -> Self {
+        Self {
+            cfg: Config::default(),
+            error: None,
+        }
+    }
+
+    /// Same as Config::set_double_decode_normalized_path.
+    pub fn double_decode_normalized_path(mut self, double_decode_normalized_path: bool) -> Self {
+        self.cfg
+            .set_double_decode_normalized_path(double_decode_normalized_path);
+        self
+    }
+
+    /// Same as Config::set_double_decode_normalized_query.
+    pub fn double_decode_normalized_query(mut self, double_decode_normalized_query: bool) -> Self {
+        self.cfg
+            .set_double_decode_normalized_query(double_decode_normalized_query);
+        self
+    }
+
+    /// Same as Config::set_parse_urlencoded.
+    pub fn parse_urlencoded(mut self, parse_urlencoded: bool) -> Self {
+        self.cfg.set_parse_urlencoded(parse_urlencoded);
+        self
+    }
+
+    /// Same as Config::set_parse_multipart.
+    pub fn parse_multipart(mut self, parse_multipart: bool) -> Self {
+        self.cfg.set_parse_multipart(parse_multipart);
+        self
+    }
+
+    /// Same as Config::set_field_limit.
+    pub fn field_limit(mut self, field_limit: usize) -> Self {
+        self.cfg.set_field_limit(field_limit);
+        self
+    }
+
+    /// Same as Config::set_parse_request_cookies.
+    pub fn parse_request_cookies(mut self, parse_request_cookies: bool) -> Self {
+        self.cfg.set_parse_request_cookies(parse_request_cookies);
+        self
+    }
+
+    /// Configure desired server personality. An unsupported personality is reported by build().
+    pub fn server_personality(mut self, personality: HtpServerPersonality) -> Self {
+        if self.cfg.set_server_personality(personality).is_err() && self.error.is_none() {
+            self.error = Some(ConfigError::UnsupportedPersonality(personality));
+        }
+        self
+    }
+
+    /// Same as Config::set_tx_auto_destroy.
+    pub fn tx_auto_destroy(mut self, tx_auto_destroy: bool) -> Self {
+        self.cfg.set_tx_auto_destroy(tx_auto_destroy);
+        self
+    }
+
+    /// Same as Config::set_bestfit_map.
+    pub fn bestfit_map(mut self, map: UnicodeBestfitMap) -> Self {
+        self.cfg.set_bestfit_map(map);
+        self
+    }
+
+    /// Same as Config::set_bestfit_replacement_byte.
+    pub fn bestfit_replacement_byte(mut self, b: u8) -> Self {
+        self.cfg.set_bestfit_replacement_byte(b);
+        self
+    }
+
+    /// Same as Config::set_url_encoding_invalid_handling.
+    pub fn url_encoding_invalid_handling(mut self, handling: HtpUrlEncodingHandling) -> Self {
+        self.cfg.set_url_encoding_invalid_handling(handling);
+        self
+    }
+
+    /// Same as Config::set_nul_raw_terminates.
+    pub fn nul_raw_terminates(mut self, enabled: bool) -> Self {
+        self.cfg.set_nul_raw_terminates(enabled);
+        self
+    }
+
+    /// Same as Config::set_nul_encoded_terminates.
+    pub fn nul_encoded_terminates(mut self, enabled: bool) -> Self {
+        self.cfg.set_nul_encoded_terminates(enabled);
+        self
+    }
+
+    /// Same as Config::set_u_encoding_decode.
+    pub fn u_encoding_decode(mut self, enabled: bool) -> Self {
+        self.cfg.set_u_encoding_decode(enabled);
+        self
+    }
+
+    /// Same as Config::set_backslash_convert_slashes.
+    pub fn backslash_convert_slashes(mut self, enabled: bool) -> Self {
+        self.cfg.set_backslash_convert_slashes(enabled);
+        self
+    }
+
+    /// Same as Config::set_path_separators_decode.
+    pub fn path_separators_decode(mut self, enabled: bool) -> Self {
+        self.cfg.set_path_separators_decode(enabled);
+        self
+    }
+
+    /// Same as Config::set_path_separators_compress.
+    pub fn path_separators_compress(mut self, enabled: bool) -> Self {
+        self.cfg.set_path_separators_compress(enabled);
+        self
+    }
+
+    /// Same as Config::set_plusspace_decode.
+    pub fn plusspace_decode(mut self, enabled: bool) -> Self {
+        self.cfg.set_plusspace_decode(enabled);
+        self
+    }
+
+    /// Same as Config::set_convert_lowercase.
+    pub fn convert_lowercase(mut self, enabled: bool) -> Self {
+        self.cfg.set_convert_lowercase(enabled);
+        self
+    }
+
+    /// Same as Config::set_utf8_convert_bestfit.
+    pub fn utf8_convert_bestfit(mut self, enabled: bool) -> Self {
+        self.cfg.set_utf8_convert_bestfit(enabled);
+        self
+    }
+
+    /// Same as Config::set_utf8_convert_bestfit_query.
+    pub fn utf8_convert_bestfit_query(mut self, enabled: bool) -> Self {
+        self.cfg.set_utf8_convert_bestfit_query(enabled);
+        self
+    }
+
+    /// Same as Config::set_u_encoding_unwanted.
+    pub fn u_encoding_unwanted(mut self, unwanted: HtpUnwanted) -> Self {
+        self.cfg.set_u_encoding_unwanted(unwanted);
+        self
+    }
+
+    /// Same as Config::set_control_chars_unwanted.
+    pub fn control_chars_unwanted(mut self, unwanted: HtpUnwanted) -> Self {
+        self.cfg.set_control_chars_unwanted(unwanted);
+        self
+    }
+
+    /// Same as Config::set_normalized_uri_include_all.
+    pub fn normalized_uri_include_all(mut self, set: bool) -> Self {
+        self.cfg.set_normalized_uri_include_all(set);
+        self
+    }
+
+    /// Same as Config::set_url_encoding_invalid_unwanted.
+    pub fn url_encoding_invalid_unwanted(mut self, unwanted: HtpUnwanted) -> Self {
+        self.cfg.set_url_encoding_invalid_unwanted(unwanted);
+        self
+    }
+
+    /// Same as Config::set_requestline_leading_whitespace_unwanted.
+    pub fn requestline_leading_whitespace_unwanted(mut self, unwanted: HtpUnwanted) -> Self {
+        self.cfg
+            .set_requestline_leading_whitespace_unwanted(unwanted);
+        self
+    }
+
+    /// Same as Config::set_request_decompression.
+    pub fn request_decompression(mut self, set: bool) -> Self {
+        self.cfg.set_request_decompression(set);
+        self
+    }
+
+    /// Same as Config::set_decompression_layer_limit.
+    pub fn decompression_layer_limit(mut self, limit: Option<usize>) -> Self {
+        self.cfg.set_decompression_layer_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_interesting_status_codes.
+    pub fn interesting_status_codes(mut self, codes: &[u16]) -> Self {
+        self.cfg.set_interesting_status_codes(codes);
+        self
+    }
+
+    /// Same as Config::set_tx_bytes_limit.
+    pub fn tx_bytes_limit(mut self, limit: Option<usize>) -> Self {
+        self.cfg.set_tx_bytes_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_tx_bytes_limit_stop.
+    pub fn tx_bytes_limit_stop(mut self, stop: bool) -> Self {
+        self.cfg.set_tx_bytes_limit_stop(stop);
+        self
+    }
+
+    /// Same as Config::set_transfer_encoding_allowlist.
+    pub fn transfer_encoding_allowlist(mut self, tokens: &[&str]) -> Self {
+        self.cfg.set_transfer_encoding_allowlist(tokens);
+        self
+    }
+
+    /// Same as Config::set_transfer_encoding_disallowed_error.
+    pub fn transfer_encoding_disallowed_error(mut self, error: bool) -> Self {
+        self.cfg.set_transfer_encoding_disallowed_error(error);
+        self
+    }
+
+    /// Same as Config::set_response_body_raw.
+    pub fn response_body_raw(mut self, enabled: bool) -> Self {
+        self.cfg.set_response_body_raw(enabled);
+        self
+    }
+
+    /// Same as Config::set_response_body_raw_limit.
+    pub fn response_body_raw_limit(mut self, limit: usize) -> Self {
+        self.cfg.set_response_body_raw_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_request_bare_cr_eol.
+    pub fn request_bare_cr_eol(mut self, enabled: bool) -> Self {
+        self.cfg.set_request_bare_cr_eol(enabled);
+        self
+    }
+
+    /// Same as Config::set_path_control_chars_reject.
+    pub fn path_control_chars_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_path_control_chars_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_response_header_name_canonicalize.
+    pub fn response_header_name_canonicalize(mut self, enabled: bool) -> Self {
+        self.cfg.set_response_header_name_canonicalize(enabled);
+        self
+    }
+
+    /// Same as Config::set_request_field_limit.
+    pub fn request_field_limit(mut self, field_limit: usize) -> Self {
+        self.cfg.set_request_field_limit(field_limit);
+        self
+    }
+
+    /// Same as Config::set_response_field_limit.
+    pub fn response_field_limit(mut self, field_limit: usize) -> Self {
+        self.cfg.set_response_field_limit(field_limit);
+        self
+    }
+
+    /// Same as Config::set_header_value_soft_limit.
+    pub fn header_value_soft_limit(mut self, limit: Option<usize>) -> Self {
+        self.cfg.set_header_value_soft_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_query_fragment_included.
+    pub fn query_fragment_included(mut self, included: bool) -> Self {
+        self.cfg.set_query_fragment_included(included);
+        self
+    }
+
+    /// Same as Config::set_response_date_required.
+    pub fn response_date_required(mut self, required: bool) -> Self {
+        self.cfg.set_response_date_required(required);
+        self
+    }
+
+    /// Same as Config::set_number_headers_limit.
+    pub fn number_headers_limit(mut self, limit: usize) -> Self {
+        self.cfg.set_number_headers_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_header_folding_limit.
+    pub fn header_folding_limit(mut self, limit: usize) -> Self {
+        self.cfg.set_header_folding_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_response_multiple_status_lines_reject.
+    pub fn response_multiple_status_lines_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_response_multiple_status_lines_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_request_line_high_bit_reject.
+    pub fn request_line_high_bit_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_request_line_high_bit_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_request_body_sniffing.
+    pub fn request_body_sniffing(mut self, enabled: bool) -> Self {
+        self.cfg.set_request_body_sniffing(enabled);
+        self
+    }
+
+    /// Same as Config::set_cookies_limit.
+    pub fn cookies_limit(mut self, limit: usize) -> Self {
+        self.cfg.set_cookies_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_cookies_size_limit.
+    pub fn cookies_size_limit(mut self, limit: usize) -> Self {
+        self.cfg.set_cookies_size_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_allow_http_0_9.
+    pub fn allow_http_0_9(mut self, allow: bool) -> Self {
+        self.cfg.set_allow_http_0_9(allow);
+        self
+    }
+
+    /// Same as Config::set_response_body_fallback.
+    pub fn response_body_fallback(mut self, fallback: HtpResponseBodyFallback) -> Self {
+        self.cfg.set_response_body_fallback(fallback);
+        self
+    }
+
+    /// Same as Config::set_chunked_length_whitespace_reject.
+    pub fn chunked_length_whitespace_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_chunked_length_whitespace_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_max_chunks.
+    pub fn max_chunks(mut self, limit: u32) -> Self {
+        self.cfg.set_max_chunks(limit);
+        self
+    }
+
+    /// Same as Config::set_response_content_length_validate.
+    pub fn response_content_length_validate(mut self, validate: bool) -> Self {
+        self.cfg.set_response_content_length_validate(validate);
+        self
+    }
+
+    /// Same as Config::set_response_content_length_mismatch_reject.
+    pub fn response_content_length_mismatch_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_response_content_length_mismatch_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_response_header_injection_detect.
+    pub fn response_header_injection_detect(mut self, detect: bool) -> Self {
+        self.cfg.set_response_header_injection_detect(detect);
+        self
+    }
+
+    /// Same as Config::set_parse_matrix_params.
+    pub fn parse_matrix_params(mut self, parse_matrix_params: bool) -> Self {
+        self.cfg.set_parse_matrix_params(parse_matrix_params);
+        self
+    }
+
+    /// Same as Config::set_allowed_methods.
+    pub fn allowed_methods(mut self, methods: &[HtpMethod]) -> Self {
+        self.cfg.set_allowed_methods(methods);
+        self
+    }
+
+    /// Same as Config::set_method_disallowed_unwanted.
+    pub fn method_disallowed_unwanted(mut self, unwanted: HtpUnwanted) -> Self {
+        self.cfg.set_method_disallowed_unwanted(unwanted);
+        self
+    }
+
+    /// Same as Config::set_uri_multiple_query_marks_reject.
+    pub fn uri_multiple_query_marks_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_uri_multiple_query_marks_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_response_status_format_reject.
+    pub fn response_status_format_reject(mut self, reject: bool) -> Self {
+        self.cfg.set_response_status_format_reject(reject);
+        self
+    }
+
+    /// Same as Config::set_response_dup_header_policy.
+    pub fn response_dup_header_policy(mut self, policy: HtpDupHeaderPolicy) -> Self {
+        self.cfg.set_response_dup_header_policy(policy);
+        self
+    }
+
+    /// Same as Config::set_field_soft_limit.
+    pub fn field_soft_limit(mut self, soft_limit: usize) -> Self {
+        self.cfg.set_field_soft_limit(soft_limit);
+        self
+    }
+
+    /// Same as Config::set_uri_path_limit.
+    pub fn uri_path_limit(mut self, limit: usize) -> Self {
+        self.cfg.set_uri_path_limit(limit);
+        self
+    }
+
+    /// Same as Config::set_tx_match_strict.
+    pub fn tx_match_strict(mut self, strict: bool) -> Self {
+        self.cfg.set_tx_match_strict(strict);
+        self
+    }
+
+    /// Same as Config::set_auto_personality.
+    pub fn auto_personality(mut self, enabled: bool) -> Self {
+        self.cfg.set_auto_personality(enabled);
+        self
+    }
+
+    /// Same as Config::set_strict_header_names.
+    pub fn strict_header_names(mut self, strict: bool) -> Self {
+        self.cfg.set_strict_header_names(strict);
+        self
+    }
+
+    /// Validates the options and produces the Config.
+    ///
+    /// Returns the first error encountered while applying the options.
+    pub fn build(self) -> std::result::Result<Config, ConfigError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let replacement_byte = self.cfg.decoder_cfg.bestfit_map.replacement_byte;
+        if !replacement_byte.is_ascii_graphic() {
+            return Err(ConfigError::InvalidReplacementByte(replacement_byte));
+        }
+        Ok(self.cfg)
+    }
 }
@@ -4,14 +4,21 @@ use crate::{
     connection::{Connection, Flags},
     error::Result,
     hook::DataHook,
-    log::Logger,
-    transaction::Transaction,
+    log::{Log, Logger},
+    transaction::{HtpRequestProgress, HtpResponseProgress, Transaction},
     transactions::Transactions,
-    util::{File, FlagOperations},
+    util::{File, FlagOperations, HtpFlags},
     HtpStatus,
 };
 use chrono::{DateTime, Utc};
-use std::{any::Any, io::Cursor, net::IpAddr, rc::Rc, time::SystemTime};
+use std::{
+    any::Any,
+    cmp::{min, Ordering},
+    io::Cursor,
+    net::IpAddr,
+    rc::Rc,
+    time::SystemTime,
+};
 
 /// Enumerates parsing state.
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -240,6 +247,8 @@ pub struct ConnectionParser {
     /// Stores the current value of a folded request header. Such headers span
     /// multiple lines, and are processed only when all data is available.
     pub request_header: Option<Bstr>,
+    /// The capacity of the inbound buffers last reported to the buffer callbacks.
+    request_buffers_reported: usize,
     /// The request body length declared in a valid request header. The key here
     /// is "valid". This field will not be populated if the request contains both
     /// a Transfer-Encoding header and a Content-Length header.
@@ -272,6 +281,8 @@ pub struct ConnectionParser {
     /// Stores the current value of a folded response header. Such headers span
     /// multiple lines, and are processed only when all data is available.
     pub response_header: Option<Bstr>,
+    /// The capacity of the outbound buffers last reported to the buffer callbacks.
+    response_buffers_reported: usize,
     /// The length of the current response body as presented in the
     /// Content-Length response header.
     pub response_content_length: i64,
@@ -288,6 +299,8 @@ pub struct ConnectionParser {
     pub response_data_receiver_hook: Option<DataHook>,
     /// On request body data, this field contains additional file data.
     pub request_file: Option<File>,
+    /// Number of request and response completions seen so far, used to number them.
+    pub completion_count: u64,
 
     /// Transactions processed by this parser
     transactions: Transactions,
@@ -325,6 +338,7 @@ impl ConnectionParser {
             request_chunk_request_index: 0,
             request_buf: Bstr::new(),
             request_header: None,
+            request_buffers_reported: 0,
             request_content_length: 0,
             request_body_data_left: 0,
             request_chunked_length: None,
@@ -336,6 +350,7 @@ impl ConnectionParser {
             response_current_receiver_offset: 0,
             response_buf: Bstr::new(),
             response_header: None,
+            response_buffers_reported: 0,
             response_content_length: 0,
             response_body_data_left: 0,
             response_chunked_length: None,
@@ -343,6 +358,7 @@ impl ConnectionParser {
             response_state_previous: State::NONE,
             response_data_receiver_hook: None,
             request_file: None,
+            completion_count: 0,
             transactions: Transactions::new(&cfg, &logger),
         }
     }
@@ -408,6 +424,76 @@ impl ConnectionParser {
         self.transactions.get_mut(index)
     }
 
+    /// Get the current request transaction, if it has been created, without creating it.
+    pub fn request_tx(&self) -> Option<&Transaction> {
+        self.tx(self.request_index())
+    }
+
+    /// Get the current response transaction, if it has been created, without creating it.
+    pub fn response_tx(&self) -> Option<&Transaction> {
+        self.tx(self.response_index())
+    }
+
+    /// Returns a stable name for the current request parsing state, such as "REQ_HEADERS",
+    /// for diagnosing where a connection is stuck.
+    pub fn request_state_name(&self) -> &'static str {
+        match self.request_state {
+            State::NONE => "REQ_NONE",
+            State::IDLE => "REQ_IDLE",
+            State::LINE => "REQ_LINE",
+            State::HEADERS => "REQ_HEADERS",
+            State::BODY_CHUNKED_DATA_END => "REQ_BODY_CHUNKED_DATA_END",
+            State::BODY_CHUNKED_DATA => "REQ_BODY_CHUNKED_DATA",
+            State::BODY_CHUNKED_LENGTH => "REQ_BODY_CHUNKED_LENGTH",
+            State::BODY_DETERMINE => "REQ_BODY_DETERMINE",
+            State::FINALIZE => "REQ_FINALIZE",
+            State::PROTOCOL => "REQ_PROTOCOL",
+            State::CONNECT_CHECK => "REQ_CONNECT_CHECK",
+            State::CONNECT_PROBE_DATA => "REQ_CONNECT_PROBE_DATA",
+            State::CONNECT_WAIT_RESPONSE => "REQ_CONNECT_WAIT_RESPONSE",
+            State::BODY_IDENTITY => "REQ_BODY_IDENTITY",
+            State::IGNORE_DATA_AFTER_HTTP_0_9 => "REQ_IGNORE_DATA_AFTER_HTTP_0_9",
+            State::BODY_IDENTITY_STREAM_CLOSE => "REQ_BODY_IDENTITY_STREAM_CLOSE",
+            State::BODY_IDENTITY_CL_KNOWN => "REQ_BODY_IDENTITY_CL_KNOWN",
+        }
+    }
+
+    /// Returns a stable name for the current response parsing state, such as "RES_HEADERS",
+    /// for diagnosing where a connection is stuck.
+    pub fn response_state_name(&self) -> &'static str {
+        match self.response_state {
+            State::NONE => "RES_NONE",
+            State::IDLE => "RES_IDLE",
+            State::LINE => "RES_LINE",
+            State::HEADERS => "RES_HEADERS",
+            State::BODY_CHUNKED_DATA_END => "RES_BODY_CHUNKED_DATA_END",
+            State::BODY_CHUNKED_DATA => "RES_BODY_CHUNKED_DATA",
+            State::BODY_CHUNKED_LENGTH => "RES_BODY_CHUNKED_LENGTH",
+            State::BODY_DETERMINE => "RES_BODY_DETERMINE",
+            State::FINALIZE => "RES_FINALIZE",
+            State::PROTOCOL => "RES_PROTOCOL",
+            State::CONNECT_CHECK => "RES_CONNECT_CHECK",
+            State::CONNECT_PROBE_DATA => "RES_CONNECT_PROBE_DATA",
+            State::CONNECT_WAIT_RESPONSE => "RES_CONNECT_WAIT_RESPONSE",
+            State::BODY_IDENTITY => "RES_BODY_IDENTITY",
+            State::IGNORE_DATA_AFTER_HTTP_0_9 => "RES_IGNORE_DATA_AFTER_HTTP_0_9",
+            State::BODY_IDENTITY_STREAM_CLOSE => "RES_BODY_IDENTITY_STREAM_CLOSE",
+            State::BODY_IDENTITY_CL_KNOWN => "RES_BODY_IDENTITY_CL_KNOWN",
+        }
+    }
+
+    /// Runs the error callbacks after the request or response stream moved to the
+    /// ERROR state, passing the most recent log message.
+    pub fn run_hook_error(&mut self) {
+        let log = self
+            .logger
+            .last_message()
+            .map(|message| Log::new(&self.conn, message));
+        let cfg = self.cfg.clone();
+        // Ignore result: the stream is in the ERROR state already.
+        let _ = cfg.hook_error.run_all(self, log.as_ref());
+    }
+
     /// Handle the current state to be processed.
     pub fn handle_request_state(&mut self, data: &mut Data) -> Result<()> {
         data.set_position(self.request_curr_data.position() as usize);
@@ -435,7 +521,9 @@ impl ConnectionParser {
     /// Handle the current state to be processed.
     pub fn handle_response_state(&mut self, data: &mut Data) -> Result<()> {
         data.set_position(self.response_curr_data.position() as usize);
-        match self.response_state {
+        let state = self.response_state;
+        let start = self.response_curr_data.position() as usize;
+        let rc = match self.response_state {
             State::NONE => Err(HtpStatus::ERROR),
             State::IDLE => self.response_idle(),
             State::LINE => self.response_line(data.as_slice()),
@@ -449,7 +537,37 @@ impl ConnectionParser {
             State::BODY_IDENTITY_CL_KNOWN => self.response_body_identity_cl_known(data),
             // These are only used by request_state
             _ => Err(HtpStatus::ERROR),
+        };
+        match state {
+            State::BODY_CHUNKED_DATA
+            | State::BODY_CHUNKED_LENGTH
+            | State::BODY_CHUNKED_DATA_END
+            | State::BODY_IDENTITY_STREAM_CLOSE
+            | State::BODY_IDENTITY_CL_KNOWN => self.response_store_body_raw(start),
+            _ => {}
+        }
+        rc
+    }
+
+    /// Appends the response body bytes consumed since the given position in the current
+    /// data chunk to the raw response body of the transaction, if enabled.
+    fn response_store_body_raw(&mut self, start: usize) {
+        if !self.cfg.response_body_raw_enabled {
+            return;
+        }
+        let limit = self.cfg.response_body_raw_limit;
+        let end = self.response_curr_data.position() as usize;
+        let data = self.response_curr_data.get_ref();
+        if end <= start || end > data.len() {
+            return;
         }
+        let raw = self
+            .transactions
+            .response_mut()
+            .response_body_raw
+            .get_or_insert_with(Bstr::new);
+        let available = limit.saturating_sub(raw.len());
+        raw.add(&data[start..start + min(available, end - start)]);
     }
 
     /// The function used for request line parsing. Depends on the personality.
@@ -504,6 +622,16 @@ impl ConnectionParser {
         // to process the events that depend on stream closure
         self.request_data(Data::default(), timestamp);
         self.response_data(Data::default(), timestamp);
+        // Flag the requests which did not see any response data before the connection closed.
+        for index in self.transactions.response_index()..=self.transactions.request_index() {
+            if let Some(tx) = self.transactions.get_mut(index) {
+                if tx.request_progress > HtpRequestProgress::NOT_STARTED
+                    && tx.response_progress == HtpResponseProgress::NOT_STARTED
+                {
+                    tx.flags.set(HtpFlags::RESPONSE_EMPTY);
+                }
+            }
+        }
     }
 
     /// This function is most likely not used and/or not needed.
@@ -527,6 +655,80 @@ impl ConnectionParser {
         self.response_curr_data.position() as i64
     }
 
+    /// Returns the number of inbound bytes buffered while waiting for more data, such as
+    /// an incomplete request line or a folded request header.
+    pub fn request_buffered_size(&self) -> usize {
+        self.request_buf.len() + self.request_header.as_ref().map_or(0, |h| h.len())
+    }
+
+    /// Returns the number of outbound bytes buffered while waiting for more data, such as
+    /// an incomplete response line or a folded response header.
+    pub fn response_buffered_size(&self) -> usize {
+        self.response_buf.len() + self.response_header.as_ref().map_or(0, |h| h.len())
+    }
+
+    /// Releases the memory held by the outbound buffers. Buffered bytes belong to a
+    /// partially parsed response line or header, so the buffers are only released while
+    /// the response parser is idle, between responses.
+    ///
+    /// Returns true if the buffers were released.
+    pub fn clear_response_buffer(&mut self) -> bool {
+        if self.response_state != State::IDLE {
+            return false;
+        }
+        self.response_buf = Bstr::new();
+        self.response_header = None;
+        // Ignore result: releasing memory is never refused.
+        let _ = self.response_buffers_update();
+        true
+    }
+
+    /// Reports the change in the capacity of the inbound buffers since the last call to
+    /// the buffer_alloc or buffer_free callbacks.
+    pub fn request_buffers_update(&mut self) -> Result<()> {
+        let capacity =
+            self.request_buf.capacity() + self.request_header.as_ref().map_or(0, |h| h.capacity());
+        let reported = std::mem::replace(&mut self.request_buffers_reported, capacity);
+        self.run_buffer_hooks(reported, capacity)
+    }
+
+    /// Reports the change in the capacity of the outbound buffers since the last call to
+    /// the buffer_alloc or buffer_free callbacks.
+    pub fn response_buffers_update(&mut self) -> Result<()> {
+        let capacity = self.response_buf.capacity()
+            + self.response_header.as_ref().map_or(0, |h| h.capacity());
+        let reported = std::mem::replace(&mut self.response_buffers_reported, capacity);
+        self.run_buffer_hooks(reported, capacity)
+    }
+
+    /// Runs the buffer_alloc or buffer_free callbacks for a change in buffer capacity.
+    /// The result of the buffer_free callbacks is ignored.
+    fn run_buffer_hooks(&self, reported: usize, capacity: usize) -> Result<()> {
+        match capacity.cmp(&reported) {
+            Ordering::Greater => self
+                .cfg
+                .hook_buffer_alloc
+                .run_all(self, capacity - reported),
+            Ordering::Less => {
+                let _ = self.cfg.hook_buffer_free.run_all(self, reported - capacity);
+                Ok(())
+            }
+            Ordering::Equal => Ok(()),
+        }
+    }
+
+    /// Switches this connection to the given server personality. The shared config is
+    /// left alone; transactions started from now on use a copy in which the settings
+    /// of the previous personality are replaced by those of the new one.
+    pub fn set_server_personality(&mut self, personality: HtpServerPersonality) -> Result<()> {
+        let mut cfg = (*self.cfg).clone();
+        cfg.reset_server_personality();
+        cfg.set_server_personality(personality)?;
+        self.cfg = Rc::new(cfg);
+        self.transactions.set_config(&self.cfg);
+        Ok(())
+    }
+
     /// Opens connection.
     pub fn open(
         &mut self,
@@ -638,6 +840,17 @@ impl ConnectionParser {
     /// Returns HtpStatus::OK on success or HtpStatus::ERROR if the request transaction
     /// is invalid or response body data hook fails.
     pub fn response_process_body_data_ex(&mut self, data: Option<&[u8]>) -> Result<()> {
+        if self.response().response_body_skipped {
+            if let Some(data) = data {
+                // Keep track of the body size, but do not process the data.
+                let tx = self.response_mut();
+                tx.response_message_len =
+                    (tx.response_message_len as u64).wrapping_add(data.len() as u64) as i64;
+                return Ok(());
+            }
+            // Still signal the end of the body, without flushing a decompressor.
+            self.response_mut().response_decompressor = None;
+        }
         let connp_ptr: *mut Self = self as *mut Self;
         self.response_mut()
             .response_process_body_data(unsafe { &mut *connp_ptr }, data)
@@ -714,4 +927,83 @@ impl ConnectionParser {
             self.transactions.remove(index);
         }
     }
+
+    /// Drives the request transaction currently being parsed to completion, as if all
+    /// of its data had been seen: the request body is finalized (flushing any decompressor)
+    /// and the request and transaction complete callbacks are invoked. Unlike close(),
+    /// the connection stays open and parsing continues with the next request.
+    ///
+    /// Does nothing if there is no request in progress.
+    pub fn request_force_complete(&mut self) -> Result<()> {
+        let progress = if let Some(tx) = self.request_tx() {
+            tx.request_progress
+        } else {
+            return Ok(());
+        };
+        if progress == HtpRequestProgress::NOT_STARTED || progress == HtpRequestProgress::COMPLETE {
+            return Ok(());
+        }
+        self.request_receiver_finalize_clear()?;
+        self.request_buf = Bstr::new();
+        self.request_header = None;
+        self.request_buffers_update()?;
+        self.request_reset();
+        self.request_chunked_length = None;
+        self.state_request_complete()
+    }
+
+    /// Drives the response transaction currently being parsed to completion, as if all
+    /// of its data had been seen: the response body is finalized (flushing any decompressor)
+    /// and the response and transaction complete callbacks are invoked. Unlike close(),
+    /// the connection stays open and parsing continues with the next response.
+    ///
+    /// Does nothing if there is no response in progress.
+    pub fn response_force_complete(&mut self) -> Result<()> {
+        let progress = if let Some(tx) = self.response_tx() {
+            tx.response_progress
+        } else {
+            return Ok(());
+        };
+        if progress == HtpResponseProgress::NOT_STARTED || progress == HtpResponseProgress::COMPLETE
+        {
+            return Ok(());
+        }
+        self.response_receiver_finalize_clear()?;
+        self.response_buf = Bstr::new();
+        self.response_header = None;
+        self.response_buffers_update()?;
+        self.response_content_length = -1;
+        self.response_body_data_left = -1;
+        self.response_chunked_length = None;
+        // Don't yield to the inbound parser, the caller asked for this transaction only.
+        self.state_response_complete_ex(1)
+    }
+
+    /// Skips the rest of the response body of the transaction currently being parsed.
+    /// The body is still consumed, so that the transaction completes normally, but it is
+    /// no longer decompressed or passed to the response body data callbacks.
+    ///
+    /// Does nothing if there is no response in progress.
+    pub fn response_skip_body(&mut self) {
+        let index = self.response_index();
+        if let Some(tx) = self.tx_mut(index) {
+            if tx.response_progress >= HtpResponseProgress::HEADERS
+                && tx.response_progress < HtpResponseProgress::COMPLETE
+            {
+                tx.response_body_skipped = true;
+            }
+        }
+    }
+}
+
+impl Drop for ConnectionParser {
+    fn drop(&mut self) {
+        // Report the release of whatever the buffers still hold.
+        self.request_buf = Bstr::new();
+        self.request_header = None;
+        let _ = self.request_buffers_update();
+        self.response_buf = Bstr::new();
+        self.response_header = None;
+        let _ = self.response_buffers_update();
+    }
 }
@@ -167,6 +167,12 @@ pub trait Decompress: Write {
     /// Indicates that we have reached the end of data. This would be equivalent
     /// to sending a NULL pointer in C and may be used by the hooks.
     fn finish(&mut self) -> std::io::Result<()>;
+
+    /// Returns true if a deflate decompressor in the chain fell back to raw deflate
+    /// data without the zlib header.
+    fn raw_deflate(&self) -> bool {
+        false
+    }
 }
 
 /// Type alias for callback function.
@@ -214,7 +220,7 @@ pub enum HtpContentEncoding {
     NONE,
     /// Gzip compression.
     GZIP,
-    /// Deflate compression (RFC 1951).
+    /// Deflate compression, as zlib (RFC 1950) or raw deflate (RFC 1951) data.
     DEFLATE,
     /// Deflate compression with zlib header (RFC 1950)
     ZLIB,
@@ -222,6 +228,8 @@ pub enum HtpContentEncoding {
     LZMA,
     /// Error retrieving the content encoding.
     ERROR,
+    /// Brotli compression (RFC 7932).
+    BROTLI,
 }
 
 /// The outer decompressor tracks the number of callbacks and time spent
@@ -289,9 +297,10 @@ impl Decompressor {
             HtpContentEncoding::GZIP
             | HtpContentEncoding::DEFLATE
             | HtpContentEncoding::ZLIB
-            | HtpContentEncoding::LZMA => Ok(Decompressor::new(Box::new(InnerDecompressor::new(
-                encoding, self.inner, options,
-            )?))),
+            | HtpContentEncoding::LZMA
+            | HtpContentEncoding::BROTLI => Ok(Decompressor::new(Box::new(
+                InnerDecompressor::new(encoding, self.inner, options)?,
+            ))),
             HtpContentEncoding::ERROR => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 "expected a valid encoding",
@@ -358,6 +367,12 @@ impl Decompressor {
     pub fn finish(&mut self) -> std::io::Result<()> {
         self.inner.finish()
     }
+
+    /// Returns true if deflate data was decompressed as raw deflate because it did not
+    /// start with a zlib header.
+    pub fn raw_deflate(&self) -> bool {
+        self.inner.raw_deflate()
+    }
 }
 
 impl std::fmt::Debug for Decompressor {
@@ -376,6 +391,10 @@ pub trait BufWriter: Write {
     fn get_mut(&mut self) -> Option<&mut Cursor<Box<[u8]>>>;
     /// Notify end of data.
     fn finish(self: Box<Self>) -> std::io::Result<Cursor<Box<[u8]>>>;
+    /// Returns true if the writer decodes raw deflate data in place of zlib data.
+    fn raw_deflate(&self) -> bool {
+        false
+    }
 }
 
 /// A BufWriter that doesn't consume any data.
@@ -562,26 +581,88 @@ impl BufWriter for GzipBufWriter {
     }
 }
 
-/// Simple wrapper around a deflate implementation
-struct DeflateBufWriter(flate2::write::DeflateDecoder<Cursor<Box<[u8]>>>);
+/// Wrapper around a zlib and a raw deflate implementation, for the deflate content
+/// coding. The coding is defined as zlib data, but some servers send raw deflate
+/// data instead, so the first two bytes are checked for a zlib header before choosing
+/// the decoder.
+enum DeflateBufWriter {
+    /// Not enough data seen yet to check for a zlib header.
+    Undecided(Cursor<Box<[u8]>>, Vec<u8>),
+    /// The data started with a zlib header.
+    Zlib(flate2::write::ZlibDecoder<Cursor<Box<[u8]>>>),
+    /// The data did not start with a zlib header.
+    Raw(flate2::write::DeflateDecoder<Cursor<Box<[u8]>>>),
+}
+
+impl DeflateBufWriter {
+    /// Returns true if the bytes are a valid zlib header (RFC 1950): deflate with a window
+    /// of at most 32K and a correct check value.
+    fn is_zlib_header(cmf: u8, flg: u8) -> bool {
+        cmf & 0x0f == 8 && cmf >> 4 <= 7 && (((cmf as u16) << 8) | flg as u16) % 31 == 0
+    }
+
+    /// Chooses the decoder from the buffered header bytes and passes them to it.
+    fn decide(&mut self) -> std::io::Result<()> {
+        let placeholder =
+            DeflateBufWriter::Undecided(Cursor::new(Box::new([]) as Box<[u8]>), Vec::new());
+        if let DeflateBufWriter::Undecided(buf, header) = std::mem::replace(self, placeholder) {
+            *self = if Self::is_zlib_header(header[0], header[1]) {
+                DeflateBufWriter::Zlib(flate2::write::ZlibDecoder::new(buf))
+            } else {
+                DeflateBufWriter::Raw(flate2::write::DeflateDecoder::new(buf))
+            };
+            self.write_all(&header)?;
+        }
+        Ok(())
+    }
+}
 
 impl Write for DeflateBufWriter {
     fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
-        self.0.write(data)
+        match self {
+            DeflateBufWriter::Undecided(_, header) => {
+                let needed = 2 - header.len();
+                if data.len() < needed {
+                    header.extend_from_slice(data);
+                    return Ok(data.len());
+                }
+                header.extend_from_slice(&data[..needed]);
+                self.decide()?;
+                Ok(needed)
+            }
+            DeflateBufWriter::Zlib(decoder) => decoder.write(data),
+            DeflateBufWriter::Raw(decoder) => decoder.write(data),
+        }
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        self.0.flush()
+        match self {
+            DeflateBufWriter::Undecided(_, _) => Ok(()),
+            DeflateBufWriter::Zlib(decoder) => decoder.flush(),
+            DeflateBufWriter::Raw(decoder) => decoder.flush(),
+        }
     }
 }
 
 impl BufWriter for DeflateBufWriter {
     fn get_mut(&mut self) -> Option<&mut Cursor<Box<[u8]>>> {
-        Some(self.0.get_mut())
+        match self {
+            DeflateBufWriter::Undecided(buf, _) => Some(buf),
+            DeflateBufWriter::Zlib(decoder) => Some(decoder.get_mut()),
+            DeflateBufWriter::Raw(decoder) => Some(decoder.get_mut()),
+        }
     }
 
     fn finish(self: Box<Self>) -> std::io::Result<Cursor<Box<[u8]>>> {
-        self.0.finish()
+        match *self {
+            DeflateBufWriter::Undecided(buf, _) => Ok(buf),
+            DeflateBufWriter::Zlib(decoder) => decoder.finish(),
+            DeflateBufWriter::Raw(decoder) => decoder.finish(),
+        }
+    }
+
+    fn raw_deflate(&self) -> bool {
+        matches!(self, DeflateBufWriter::Raw(_))
     }
 }
 
@@ -639,6 +720,85 @@ impl BufWriter for LzmaBufWriter {
     }
 }
 
+/// Wrapper around a brotli implementation. Unlike the flate2 decoders, the brotli
+/// decoder loses data when its output accepts only part of a write, so it decodes
+/// into a growable buffer, which is then drained into the temporary buffer.
+#[cfg(feature = "brotli")]
+struct BrotliBufWriter {
+    /// Decoder, holding the decoded data not yet moved to `buf`.
+    decoder: brotli::DecompressorWriter<Vec<u8>>,
+    /// Temporary buffer of fixed size.
+    buf: Cursor<Box<[u8]>>,
+}
+
+#[cfg(feature = "brotli")]
+impl BrotliBufWriter {
+    fn new(buf: Cursor<Box<[u8]>>) -> Self {
+        Self {
+            decoder: brotli::DecompressorWriter::new(Vec::new(), ENCODING_CHUNK_SIZE),
+            buf,
+        }
+    }
+
+    /// Moves as much decoded data as fits into the temporary buffer. Returns false
+    /// if some of it is left for the next round.
+    fn drain(&mut self) -> bool {
+        let pending = self.decoder.get_mut();
+        let written = self.buf.write(pending).unwrap_or(0);
+        pending.drain(..written);
+        pending.is_empty()
+    }
+}
+
+#[cfg(feature = "brotli")]
+impl Write for BrotliBufWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        // Don't decode more until the previous output has been moved out.
+        if !self.drain() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::WriteZero,
+                "temporary buffer is full",
+            ));
+        }
+        let consumed = self.decoder.write(data)?;
+        self.drain();
+        Ok(consumed)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.decoder.flush()?;
+        if self.drain() {
+            Ok(())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::WriteZero,
+                "temporary buffer is full",
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "brotli")]
+impl BufWriter for BrotliBufWriter {
+    fn get_mut(&mut self) -> Option<&mut Cursor<Box<[u8]>>> {
+        Some(&mut self.buf)
+    }
+
+    fn finish(self: Box<Self>) -> std::io::Result<Cursor<Box<[u8]>>> {
+        let BrotliBufWriter { decoder, buf } = *self;
+        // An incomplete stream still hands back whatever was decoded so far, so
+        // that it reaches the callback.
+        let pending = decoder.into_inner().unwrap_or_else(|pending| pending);
+        let position = buf.position() as usize;
+        let mut output = buf.into_inner()[..position].to_vec();
+        output.extend_from_slice(&pending);
+        let len = output.len();
+        let mut buf = Cursor::new(output.into_boxed_slice());
+        buf.set_position(len as u64);
+        Ok(buf)
+    }
+}
+
 /// Structure that represents each decompressor in the chain.
 struct InnerDecompressor {
     /// Decoder implementation that will write to a temporary buffer.
@@ -655,6 +815,8 @@ struct InnerDecompressor {
     restarts: u8,
     /// Options for decompression
     options: Options,
+    /// Whether the writer fell back to raw deflate data
+    raw_deflate: bool,
 }
 
 impl InnerDecompressor {
@@ -668,7 +830,7 @@ impl InnerDecompressor {
         match encoding {
             HtpContentEncoding::GZIP => Ok((Box::new(GzipBufWriter::new(buf)), false)),
             HtpContentEncoding::DEFLATE => Ok((
-                Box::new(DeflateBufWriter(flate2::write::DeflateDecoder::new(buf))),
+                Box::new(DeflateBufWriter::Undecided(buf, Vec::with_capacity(2))),
                 false,
             )),
             HtpContentEncoding::ZLIB => Ok((
@@ -687,6 +849,11 @@ impl InnerDecompressor {
                     Ok((Box::new(NullBufWriter(buf)), true))
                 }
             }
+            #[cfg(feature = "brotli")]
+            HtpContentEncoding::BROTLI => Ok((Box::new(BrotliBufWriter::new(buf)), false)),
+            // Without brotli support, pass the data through as is.
+            #[cfg(not(feature = "brotli"))]
+            HtpContentEncoding::BROTLI => Ok((Box::new(NullBufWriter(buf)), true)),
             HtpContentEncoding::NONE | HtpContentEncoding::ERROR => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 "expected a valid encoding",
@@ -710,6 +877,7 @@ impl InnerDecompressor {
             passthrough,
             restarts: 0,
             options,
+            raw_deflate: false,
         })
     }
 
@@ -734,6 +902,9 @@ impl InnerDecompressor {
     /// The writer should be taken out of its slot and passed directly instead of
     /// `self.writer` to avoid holding multiple mutable references.
     fn flush_writer(&mut self, writer: &mut Box<dyn BufWriter>) -> std::io::Result<()> {
+        if writer.raw_deflate() {
+            self.raw_deflate = true;
+        }
         if let Some(mut inner) = self.inner.take() {
             while {
                 let result = writer.flush();
@@ -856,6 +1027,7 @@ impl Decompress for InnerDecompressor {
                     HtpContentEncoding::DEFLATE => HtpContentEncoding::ZLIB,
                     HtpContentEncoding::ZLIB => HtpContentEncoding::GZIP,
                     HtpContentEncoding::LZMA => HtpContentEncoding::DEFLATE,
+                    HtpContentEncoding::BROTLI => HtpContentEncoding::DEFLATE,
                     HtpContentEncoding::NONE | HtpContentEncoding::ERROR => {
                         return Err(std::io::Error::new(
                             std::io::ErrorKind::Other,
@@ -906,6 +1078,14 @@ impl Decompress for InnerDecompressor {
             Ok(())
         }
     }
+
+    fn raw_deflate(&self) -> bool {
+        self.raw_deflate
+            || self
+                .inner
+                .as_ref()
+                .map_or(false, |inner| inner.raw_deflate())
+    }
 }
 
 #[test]
@@ -1088,3 +1268,73 @@ fn test_gz_header() {
     let input = b"\x1f\x8b\x08\x01\x00\x00\x00\x00\x00";
     assert!(GzHeader::parse(input).is_err());
 }
+
+#[test]
+fn test_deflate_zlib_and_raw() {
+    use std::{cell::RefCell, rc::Rc};
+    let input = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+
+    let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    zlib.write_all(&input).unwrap();
+    let zlib = zlib.finish().unwrap();
+    let mut raw = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    raw.write_all(&input).unwrap();
+    let raw = raw.finish().unwrap();
+
+    for (compressed, is_raw) in [(zlib, false), (raw, true)].iter() {
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let output_cb = output.clone();
+        let mut decompressor = Decompressor::new_with_callback(
+            HtpContentEncoding::DEFLATE,
+            Box::new(move |data: Option<&[u8]>| -> std::io::Result<usize> {
+                if let Some(data) = data {
+                    output_cb.borrow_mut().extend_from_slice(data);
+                    Ok(data.len())
+                } else {
+                    Ok(0)
+                }
+            }),
+            Default::default(),
+        )
+        .unwrap();
+        // Feed a single byte first so that the header check has to wait for more data.
+        decompressor.decompress(&compressed[..1]).unwrap();
+        decompressor.decompress(&compressed[1..]).unwrap();
+        decompressor.finish().unwrap();
+        assert_eq!(input, *output.borrow());
+        assert_eq!(*is_raw, decompressor.raw_deflate());
+    }
+}
+
+#[cfg(feature = "brotli")]
+#[test]
+fn test_brotli_large_output() {
+    use std::{cell::RefCell, rc::Rc};
+    // Decodes to several times the size of the temporary buffer.
+    let input = b"The quick brown fox jumps over the lazy dog. ".repeat(1000);
+
+    let mut brotli = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+    brotli.write_all(&input).unwrap();
+    let brotli = brotli.into_inner();
+
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let output_cb = output.clone();
+    let mut decompressor = Decompressor::new_with_callback(
+        HtpContentEncoding::BROTLI,
+        Box::new(move |data: Option<&[u8]>| -> std::io::Result<usize> {
+            if let Some(data) = data {
+                output_cb.borrow_mut().extend_from_slice(data);
+                Ok(data.len())
+            } else {
+                Ok(0)
+            }
+        }),
+        Default::default(),
+    )
+    .unwrap();
+    for chunk in brotli.chunks(16) {
+        decompressor.decompress(chunk).unwrap();
+    }
+    decompressor.finish().unwrap();
+    assert_eq!(input, *output.borrow());
+}
@@ -1,4 +1,7 @@
-use crate::util::{is_token, take_until_null, FlagOperations};
+use crate::{
+    transaction::HtpHeaderTerminator,
+    util::{is_ows, is_token, take_until_null, FlagOperations},
+};
 use nom::{
     branch::alt,
     bytes::complete::tag as complete_tag,
@@ -9,28 +12,51 @@ use nom::{
         streaming::{space0, space1},
     },
     combinator::{complete, map, not, opt, peek},
+    error::ErrorKind,
     sequence::tuple,
-    Err::Incomplete,
+    Err::{Failure, Incomplete},
     IResult, Needed,
 };
+use std::cell::Cell;
 
 #[derive(Debug, PartialEq)]
+/// Header parsing flags, kept on each stored header as Header::parser_flags.
 pub struct Flags;
 
 impl Flags {
+    /// The value was folded over several lines.
     pub const FOLDING: u64 = 0x0001;
+    /// The value was folded in an unusual way, such as a first line with no value.
     pub const FOLDING_SPECIAL_CASE: u64 = (0x0002 | Self::FOLDING);
+    /// The header name is empty.
     pub const NAME_EMPTY: u64 = 0x0004;
+    /// The header value is empty.
     pub const VALUE_EMPTY: u64 = 0x0008;
+    /// The header name contains characters that are not token characters.
     pub const NAME_NON_TOKEN_CHARS: u64 = 0x0010;
+    /// The header name is followed by whitespace before the colon.
     pub const NAME_TRAILING_WHITESPACE: u64 = 0x0020;
+    /// The header name is preceded by whitespace.
     pub const NAME_LEADING_WHITESPACE: u64 = 0x0040;
+    /// The header line was terminated by a NUL byte.
     pub const NULL_TERMINATED: u64 = 0x0080;
+    /// The header line has no colon; the whole line is the value.
     pub const MISSING_COLON: u64 = (0x0100 | Self::NAME_EMPTY);
+    /// The header line ended with a deformed mix of CR and LF characters.
     pub const DEFORMED_EOL: u64 = 0x0200;
+    /// The header line ended with an unusual terminator.
     pub const TERMINATOR_SPECIAL_CASE: u64 = 0x0400;
+    /// The name and value were separated by something other than a plain colon.
     pub const DEFORMED_SEPARATOR: u64 = (0x0800 | Self::NAME_NON_TOKEN_CHARS);
+    /// A folded line was empty.
     pub const FOLDING_EMPTY: u64 = (0x1000 | Self::DEFORMED_EOL);
+    /// The header line ended with a bare CR.
+    pub const BARE_CR_EOL: u64 = 0x2000;
+    /// The value was folded over more lines than allowed.
+    pub const FOLDING_LIMIT: u64 = 0x4000;
+    /// The line ending was followed by a CR that looked like folding, but the next
+    /// line is another header, so the CR is not part of the value.
+    pub const CR_NOT_FOLDING: u64 = 0x8000;
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -63,6 +89,11 @@ pub enum Side {
 pub struct Parser {
     side: Side,
     complete: bool,
+    bare_cr_eol: bool,
+    folding_limit: Option<usize>,
+    strict: bool,
+    header_limit: Option<usize>,
+    header_count: Cell<usize>,
 }
 
 impl Parser {
@@ -70,6 +101,11 @@ impl Parser {
         Self {
             side,
             complete: false,
+            bare_cr_eol: false,
+            folding_limit: None,
+            strict: false,
+            header_limit: None,
+            header_count: Cell::new(0),
         }
     }
 
@@ -80,9 +116,52 @@ impl Parser {
         self.complete = complete;
     }
 
+    /// Sets whether a request parser accepts a bare CR as a line ending.
+    ///
+    /// Response parsers always accept a bare CR as a line ending.
+    pub fn set_bare_cr_eol(&mut self, bare_cr_eol: bool) {
+        self.bare_cr_eol = bare_cr_eol;
+    }
+
+    /// Sets the maximum number of continuation lines folded into a header value.
+    ///
+    /// Continuation lines past the limit are consumed but not added to the value,
+    /// which is flagged with FOLDING_LIMIT.
+    pub fn set_folding_limit(&mut self, folding_limit: Option<usize>) {
+        self.folding_limit = folding_limit;
+    }
+
+    /// Sets whether header names with leading or trailing whitespace, or with characters
+    /// that are not token characters, fail parsing instead of being flagged.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Sets the maximum number of header lines the parser accepts over its lifetime.
+    ///
+    /// Parsing fails with ErrorKind::TooLarge once a header line past the limit is seen.
+    pub fn set_header_limit(&mut self, header_limit: Option<usize>) {
+        self.header_limit = header_limit;
+    }
+
+    /// Counts one more parsed header line, failing if that goes over the header limit
+    fn count_header<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], ()> {
+        let count = self.header_count.get() + 1;
+        if self.header_limit.map_or(false, |limit| count > limit) {
+            return Err(Failure((input, ErrorKind::TooLarge)));
+        }
+        self.header_count.set(count);
+        Ok((input, ()))
+    }
+
+    /// Returns true if a bare CR is accepted as a line ending
+    fn accepts_cr_eol(&self) -> bool {
+        self.side == Side::Response || self.bare_cr_eol
+    }
+
     /// Returns true if c is a line feed character
     fn is_eol(&self) -> impl Fn(u8) -> bool + '_ {
-        move |c| c == b'\n' || (self.side == Side::Response && c == b'\r')
+        move |c| c == b'\n' || (self.accepts_cr_eol() && c == b'\r')
     }
 
     /// Parse one complete end of line character or character set
@@ -95,6 +174,8 @@ impl Parser {
                     complete_tag("\n"),
                     complete_tag("\r"),
                 ))(input)
+            } else if self.bare_cr_eol {
+                alt((complete_tag("\r\n"), complete_tag("\n"), complete_tag("\r")))(input)
             } else {
                 alt((complete_tag("\r\n"), complete_tag("\n")))(input)
             }
@@ -150,7 +231,13 @@ impl Parser {
         move |input| {
             alt((
                 self.complete_eol_deformed(),
-                map(self.complete_eol_regular(), |eol| (eol, 0)),
+                map(self.complete_eol_regular(), |eol| {
+                    if self.side == Side::Request && eol == b"\r" {
+                        (eol, Flags::BARE_CR_EOL)
+                    } else {
+                        (eol, 0)
+                    }
+                }),
             ))(input)
         }
     }
@@ -319,8 +406,9 @@ impl Parser {
                 *input = data.to_vec();
             }
         }
+        // Only optional whitespace is removed, a trailing CR is significant.
         while let Some(end) = input.last() {
-            if is_space(*end) {
+            if is_ows(*end) {
                 input.pop();
             } else {
                 break;
@@ -352,11 +440,17 @@ impl Parser {
             let mut value = val_bytes.to_vec();
             if fold.is_some() {
                 let mut i = rest;
+                let mut folds = 0;
                 loop {
                     if self.side == Side::Response {
                         // Peek ahead for ambiguous name with lws vs. value with folding
                         match tuple((token_chars, separator_regular))(i) {
                             Ok(_) => {
+                                if flags & Flags::FOLDING_SPECIAL_CASE
+                                    == Flags::FOLDING_SPECIAL_CASE
+                                {
+                                    flags.set(Flags::CR_NOT_FOLDING);
+                                }
                                 flags.unset(Flags::FOLDING_SPECIAL_CASE);
                                 if value.is_empty() {
                                     flags.set(Flags::VALUE_EMPTY);
@@ -375,11 +469,17 @@ impl Parser {
                         Ok((rest, (val_bytes, ((_eol, other_flags), fold)))) => {
                             i = rest;
                             flags.set(other_flags);
-                            //If the value is empty, the value started with a fold and we don't want to push back a space
-                            if !value.is_empty() {
-                                value.push(b' ');
+                            folds += 1;
+                            if self.folding_limit.map_or(false, |limit| folds > limit) {
+                                // Past the limit, the value is considered terminated.
+                                flags.set(Flags::FOLDING_LIMIT);
+                            } else {
+                                //If the value is empty, the value started with a fold and we don't want to push back a space
+                                if !value.is_empty() {
+                                    value.push(b' ');
+                                }
+                                value.extend(val_bytes);
                             }
-                            value.extend(val_bytes);
                             if fold.is_none() {
                                 self.remove_trailing(&mut value, &mut flags);
                                 return Ok((rest, Value { value, flags }));
@@ -561,16 +661,24 @@ impl Parser {
     }
 
     /// Parse a header name separator value
+    ///
+    /// In strict mode, a malformed name is a parsing failure.
     fn header_with_colon(&self) -> impl Fn(&[u8]) -> IResult<&[u8], Header> + '_ {
         move |input| {
-            map(
-                tuple((self.name(), self.separator(), self.value())),
-                |(mut name, flag, mut value)| {
-                    name.flags |= flag;
-                    value.flags |= flag;
-                    Header { name, value }
-                },
-            )(input)
+            let (rest, (mut name, flag, mut value)) =
+                tuple((self.name(), self.separator(), self.value()))(input)?;
+            name.flags |= flag;
+            value.flags |= flag;
+            if self.strict
+                && name.flags.is_set(
+                    Flags::NAME_LEADING_WHITESPACE
+                        | Flags::NAME_TRAILING_WHITESPACE
+                        | Flags::NAME_NON_TOKEN_CHARS,
+                )
+            {
+                return Err(Failure((input, ErrorKind::Verify)));
+            }
+            Ok((rest, Header { name, value }))
         }
     }
 
@@ -581,11 +689,27 @@ impl Parser {
 
     /// Parse multiple headers and indicate if end of headers or null was found
     pub fn headers(&self) -> impl Fn(&[u8]) -> IResult<&[u8], (Vec<Header>, bool)> + '_ {
+        move |input| {
+            let (rest, (headers, eoh)) = self.headers_with_terminators()(input)?;
+            Ok((
+                rest,
+                (headers.into_iter().map(|(header, _)| header).collect(), eoh),
+            ))
+        }
+    }
+
+    /// Parse multiple headers, along with how each of them is terminated, and indicate if
+    /// end of headers or null was found
+    pub fn headers_with_terminators(
+        &self,
+    ) -> impl Fn(&[u8]) -> IResult<&[u8], (Vec<(Header, HtpHeaderTerminator)>, bool)> + '_ {
         move |input| {
             let (rest, head) = self.header()(input)?;
+            self.count_header(input)?;
             let is_null_terminated = head.value.flags.is_set(Flags::NULL_TERMINATED);
+            let terminator = terminator(&input[..input.len() - rest.len()], head.value.flags);
             let mut out = Vec::with_capacity(16);
-            out.push(head);
+            out.push((head, terminator));
             if is_null_terminated {
                 return Ok((rest, (out, true)));
             }
@@ -596,9 +720,11 @@ impl Parser {
             loop {
                 match self.header()(i) {
                     Ok((rest, head)) => {
-                        i = rest;
+                        self.count_header(i)?;
                         let is_null_terminated = head.value.flags.is_set(Flags::NULL_TERMINATED);
-                        out.push(head);
+                        let terminator = terminator(&i[..i.len() - rest.len()], head.value.flags);
+                        i = rest;
+                        out.push((head, terminator));
                         if is_null_terminated {
                             return Ok((rest, (out, true)));
                         }
@@ -616,6 +742,24 @@ impl Parser {
     }
 }
 
+/// Classifies the terminator of a header, given the bytes consumed when parsing it and its
+/// value flags
+fn terminator(raw: &[u8], flags: u64) -> HtpHeaderTerminator {
+    if flags.is_set(Flags::NULL_TERMINATED) {
+        HtpHeaderTerminator::NULL
+    } else if flags.is_set(Flags::DEFORMED_EOL) || raw.ends_with(b"\n\r") {
+        HtpHeaderTerminator::DEFORMED
+    } else if raw.ends_with(b"\r\n") {
+        HtpHeaderTerminator::CRLF
+    } else if raw.ends_with(b"\n") {
+        HtpHeaderTerminator::LF
+    } else if raw.ends_with(b"\r") {
+        HtpHeaderTerminator::CR
+    } else {
+        HtpHeaderTerminator::NONE
+    }
+}
+
 /// Parse one null character and return it and the NULL_TERMINATED flag
 fn null(input: &[u8]) -> IResult<&[u8], (&[u8], u64)> {
     map(complete_tag("\0"), |null| (null, Flags::NULL_TERMINATED))(input)
@@ -2910,4 +3054,172 @@ mod test {
             res_parser
         );
     }
+
+    #[test]
+    fn BareCrEol() {
+        let req_parser = Parser::new(Side::Request);
+        let mut cr_parser = Parser::new(Side::Request);
+        cr_parser.set_bare_cr_eol(true);
+
+        let input = b"k1:v1\rk2:v2\r\n\r\n";
+        let req_result = Ok((b!(""), (vec![header!(b"k1", 0, b"v1\rk2:v2", 0)], true)));
+        let cr_result = Ok((
+            b!(""),
+            (
+                vec![
+                    header!(b"k1", 0, b"v1", Flags::BARE_CR_EOL),
+                    header!(b"k2", 0, b"v2", 0),
+                ],
+                true,
+            ),
+        ));
+        assert_headers_result_eq!(req_result, input, req_parser);
+        assert_headers_result_eq!(cr_result, input, cr_parser);
+    }
+
+    #[test]
+    fn FoldingLimit() {
+        let mut req_parser = Parser::new(Side::Request);
+        req_parser.set_folding_limit(Some(2));
+        let mut res_parser = Parser::new(Side::Response);
+        res_parser.set_folding_limit(Some(2));
+
+        assert_value_result_eq!(
+            Ok((
+                b!("next:"),
+                Value {
+                    value: b"one two".to_vec(),
+                    flags: Flags::FOLDING
+                }
+            )),
+            b"one\r\n two\r\nnext:",
+            req_parser,
+            res_parser
+        );
+        assert_value_result_eq!(
+            Ok((
+                b!("next:"),
+                Value {
+                    value: b"one two three".to_vec(),
+                    flags: Flags::FOLDING | Flags::FOLDING_LIMIT
+                }
+            )),
+            b"one\r\n two\r\n three\r\n four\r\n five\r\nnext:",
+            req_parser,
+            res_parser
+        );
+    }
+
+    #[test]
+    fn StrictNames() {
+        let mut strict_req_parser = Parser::new(Side::Request);
+        strict_req_parser.set_strict(true);
+        let mut strict_res_parser = Parser::new(Side::Response);
+        strict_res_parser.set_strict(true);
+        let req_parser = Parser::new(Side::Request);
+        let res_parser = Parser::new(Side::Response);
+
+        for &input in [
+            b!(" Host : x\r\n\r\n"),
+            b!(" Host: x\r\n\r\n"),
+            b!("Host : x\r\n\r\n"),
+            b!("Ho(st: x\r\n\r\n"),
+        ]
+        .iter()
+        {
+            assert_header_result_eq!(
+                Err(Failure((input, ErrorKind::Verify))),
+                input,
+                strict_req_parser,
+                strict_res_parser
+            );
+            assert!(req_parser.header()(input).is_ok());
+            assert!(res_parser.header()(input).is_ok());
+        }
+
+        assert_header_result_eq!(
+            Ok((
+                b!("\r\n"),
+                header!(
+                    b"Host",
+                    Flags::NAME_LEADING_WHITESPACE | Flags::NAME_TRAILING_WHITESPACE,
+                    b"x",
+                    0
+                ),
+            )),
+            b" Host : x\r\n\r\n",
+            req_parser
+        );
+        assert_header_result_eq!(
+            Ok((b!("\r\n"), header!(b"Host", 0, b"x", 0),)),
+            b"Host: x\r\n\r\n",
+            strict_req_parser,
+            strict_res_parser
+        );
+    }
+
+    #[test]
+    fn CrNotFolding() {
+        let res_parser = Parser::new(Side::Response);
+        let input = b"a:1\r\n\rb:2\r\n\r\n";
+        let res_result = Ok((
+            b!(""),
+            (
+                vec![
+                    header!(b"a", 0, b"1", Flags::CR_NOT_FOLDING),
+                    header!(b"b", 0, b"2", 0),
+                ],
+                true,
+            ),
+        ));
+        assert_headers_result_eq!(res_result, input, res_parser);
+    }
+
+    #[test]
+    fn HeadersWithTerminators() {
+        let res_parser = Parser::new(Side::Response);
+        let (_, (headers, eoh)) =
+            res_parser.headers_with_terminators()(b"a:1\r\nb:2\nc:3\rd:4\n\re:5\r\n f\r\n\r\n")
+                .unwrap();
+        assert!(eoh);
+        let terminators: Vec<HtpHeaderTerminator> = headers.iter().map(|(_, t)| *t).collect();
+        assert_eq!(
+            vec![
+                HtpHeaderTerminator::CRLF,
+                HtpHeaderTerminator::LF,
+                HtpHeaderTerminator::CR,
+                HtpHeaderTerminator::DEFORMED,
+                HtpHeaderTerminator::CRLF,
+            ],
+            terminators
+        );
+
+        let req_parser = Parser::new(Side::Request);
+        let (_, (headers, eoh)) = req_parser.headers_with_terminators()(b"a:1\nb:2\0\r\n").unwrap();
+        assert!(eoh);
+        assert_eq!(HtpHeaderTerminator::LF, headers[0].1);
+        assert_eq!(HtpHeaderTerminator::NULL, headers[1].1);
+    }
+
+    #[test]
+    fn HeaderLimit() {
+        let mut req_parser = Parser::new(Side::Request);
+        req_parser.set_header_limit(Some(2));
+        // The count carries over between calls on the same parser.
+        let (rest, (headers, eoh)) = req_parser.headers()(b"a:1\r\nb").unwrap();
+        assert!(!eoh);
+        assert_eq!(1, headers.len());
+        assert_eq!(b"b", rest);
+        let input = b"b:2\r\nContent-Length: 5\r\n\r\n";
+        assert_eq!(
+            Err(Failure((&input[5..], ErrorKind::TooLarge))),
+            req_parser.headers()(input)
+        );
+
+        let mut res_parser = Parser::new(Side::Response);
+        res_parser.set_header_limit(Some(2));
+        let (_, (headers, eoh)) = res_parser.headers()(b"a:1\r\nb:2\r\n\r\n").unwrap();
+        assert!(eoh);
+        assert_eq!(2, headers.len());
+    }
 }
@@ -2,6 +2,7 @@ use crate::{
     connection_parser::ConnectionParser,
     error::Result,
     log::Log,
+    multipart::Part,
     transaction::{Data, Transaction},
     util::FileData,
     HtpStatus,
@@ -45,6 +46,47 @@ pub type LogNativeCallbackFn = fn(log: &mut Log) -> Result<()>;
 /// Hook for Log
 pub type LogHook = Hook<LogExternalCallbackFn, LogNativeCallbackFn>;
 
+/// External (C) callback function prototype. There is no C registration for it, as
+/// a Part is opaque to C callers.
+pub type MultipartPartExternalCallbackFn = unsafe extern "C" fn(part: *const Part) -> HtpStatus;
+
+/// Native (rust) callback function prototype
+pub type MultipartPartNativeCallbackFn = fn(part: &Part) -> Result<()>;
+
+/// Hook for MultipartPart
+pub type MultipartPartHook = Hook<MultipartPartExternalCallbackFn, MultipartPartNativeCallbackFn>;
+
+/// External (C) callback function prototype
+pub type FieldSoftLimitExternalCallbackFn =
+    unsafe extern "C" fn(tx: *mut Transaction, size: usize) -> HtpStatus;
+
+/// Native (rust) callback function prototype
+pub type FieldSoftLimitNativeCallbackFn = fn(tx: &mut Transaction, size: usize) -> Result<()>;
+
+/// External (C) callback function prototype
+pub type ErrorExternalCallbackFn =
+    unsafe extern "C" fn(connp: *const ConnectionParser, log: *const Log) -> HtpStatus;
+
+/// Native (rust) callback function prototype
+pub type ErrorNativeCallbackFn = fn(connp: &ConnectionParser, log: Option<&Log>) -> Result<()>;
+
+/// Hook for Error
+pub type ErrorHook = Hook<ErrorExternalCallbackFn, ErrorNativeCallbackFn>;
+
+/// Hook for FieldSoftLimit
+pub type FieldSoftLimitHook =
+    Hook<FieldSoftLimitExternalCallbackFn, FieldSoftLimitNativeCallbackFn>;
+
+/// External (C) callback function prototype
+pub type BufferExternalCallbackFn =
+    unsafe extern "C" fn(connp: *const ConnectionParser, size: usize) -> HtpStatus;
+
+/// Native (rust) callback function prototype
+pub type BufferNativeCallbackFn = fn(connp: &ConnectionParser, size: usize) -> Result<()>;
+
+/// Hook for buffer allocation and release
+pub type BufferHook = Hook<BufferExternalCallbackFn, BufferNativeCallbackFn>;
+
 /// Callback list
 #[derive(Clone)]
 pub struct Hook<E, N> {
@@ -180,6 +222,115 @@ impl LogHook {
     }
 }
 
+impl MultipartPartHook {
+    /// Run all callbacks on the list
+    ///
+    /// This function will exit early if a callback fails to return HtpStatus::OK
+    /// or HtpStatus::DECLINED.
+    pub fn run_all(&self, part: &Part) -> Result<()> {
+        for cbk_fn in &self.callbacks {
+            match cbk_fn {
+                Callback::External(cbk_fn) => {
+                    let result = unsafe { cbk_fn(part) };
+                    if result != HtpStatus::OK && result != HtpStatus::DECLINED {
+                        return Err(result);
+                    }
+                }
+                Callback::Native(cbk_fn) => {
+                    if let Err(e) = cbk_fn(part) {
+                        if e != HtpStatus::DECLINED {
+                            return Err(e);
+                        }
+                    }
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+impl FieldSoftLimitHook {
+    /// Run all callbacks on the list
+    ///
+    /// This function will exit early if a callback fails to return HtpStatus::OK
+    /// or HtpStatus::DECLINED.
+    pub fn run_all(&self, tx: &mut Transaction, size: usize) -> Result<()> {
+        for cbk_fn in &self.callbacks {
+            match cbk_fn {
+                Callback::External(cbk_fn) => {
+                    let result = unsafe { cbk_fn(tx, size) };
+                    if result != HtpStatus::OK && result != HtpStatus::DECLINED {
+                        return Err(result);
+                    }
+                }
+                Callback::Native(cbk_fn) => {
+                    if let Err(e) = cbk_fn(tx, size) {
+                        if e != HtpStatus::DECLINED {
+                            return Err(e);
+                        }
+                    }
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+impl ErrorHook {
+    /// Run all callbacks on the list
+    ///
+    /// This function will exit early if a callback fails to return HtpStatus::OK
+    /// or HtpStatus::DECLINED.
+    pub fn run_all(&self, connp: &ConnectionParser, log: Option<&Log>) -> Result<()> {
+        for cbk_fn in &self.callbacks {
+            match cbk_fn {
+                Callback::External(cbk_fn) => {
+                    let log_ptr = log.map_or(std::ptr::null(), |log| log as *const Log);
+                    let result = unsafe { cbk_fn(connp, log_ptr) };
+                    if result != HtpStatus::OK && result != HtpStatus::DECLINED {
+                        return Err(result);
+                    }
+                }
+                Callback::Native(cbk_fn) => {
+                    if let Err(e) = cbk_fn(connp, log) {
+                        if e != HtpStatus::DECLINED {
+                            return Err(e);
+                        }
+                    }
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+impl BufferHook {
+    /// Run all callbacks on the list
+    ///
+    /// This function will exit early if a callback fails to return HtpStatus::OK
+    /// or HtpStatus::DECLINED.
+    pub fn run_all(&self, connp: &ConnectionParser, size: usize) -> Result<()> {
+        for cbk_fn in &self.callbacks {
+            match cbk_fn {
+                Callback::External(cbk_fn) => {
+                    let result = unsafe { cbk_fn(connp, size) };
+                    if result != HtpStatus::OK && result != HtpStatus::DECLINED {
+                        return Err(result);
+                    }
+                }
+                Callback::Native(cbk_fn) => {
+                    if let Err(e) = cbk_fn(connp, size) {
+                        if e != HtpStatus::DECLINED {
+                            return Err(e);
+                        }
+                    }
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
 /// Type of callbacks
 #[derive(Copy, Clone)]
 pub enum Callback<E, N> {
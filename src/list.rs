@@ -190,6 +190,13 @@ impl<T> List<T> {
     pub fn is_empty(&self) -> bool {
         self.elements.is_empty()
     }
+
+    /// Returns an iterator over the elements in the order they were pushed.
+    ///
+    /// Removed elements are skipped.
+    pub fn iter(&self) -> IntoIter<'_, T> {
+        self.into_iter()
+    }
 }
 
 #[cfg(test)]
@@ -328,6 +335,16 @@ mod tests {
         assert_eq!(list_copy, [&'a', &'c']);
     }
 
+    #[test]
+    fn iter() {
+        let mut list = List::with_capacity(8);
+        list.push('a');
+        list.push('b');
+        list.push('c');
+        list.push('d');
+        assert_eq!(list.iter().collect::<Vec<_>>(), [&'a', &'b', &'c', &'d']);
+    }
+
     #[test]
     fn iterator_empty() {
         let list: List<char> = List::with_capacity(4);
@@ -1,5 +1,5 @@
 use crate::connection::Connection;
-use std::{net::IpAddr, sync::mpsc::Sender};
+use std::{cell::RefCell, net::IpAddr, rc::Rc, sync::mpsc::Sender};
 
 /// Different codes used for logging.
 /// cbindgen:rename-all=QualifiedScreamingSnakeCase
@@ -160,6 +160,66 @@ pub enum HtpLogCode {
     COMPRESSION_BOMB_DOUBLE_LZMA,
     /// Invalid content-encoding detected.
     INVALID_CONTENT_ENCODING,
+    /// Transaction accumulated more bytes than the configured limit.
+    TX_BYTES_LIMIT_REACHED,
+    /// Transfer-Encoding contains a token that is not in the configured allowlist.
+    TRANSFER_ENCODING_DISALLOWED,
+    /// A bare CR was accepted as a request header line terminator.
+    REQUEST_BARE_CR_EOL,
+    /// The request path contains control characters.
+    PATH_CONTROL_CHARS,
+    /// A header value is longer than the configured soft limit.
+    HEADER_VALUE_SOFT_LIMIT,
+    /// More headers than the configured limit were seen.
+    HEADERS_TOO_MANY,
+    /// A header value was folded over more lines than the configured limit.
+    HEADER_FOLDING_LIMIT,
+    /// The request target form does not fit the request method.
+    REQUEST_TARGET_INVALID,
+    /// A second response line was seen for a transaction which already had a final status.
+    RESPONSE_MULTIPLE_STATUS_LINES,
+    /// The request line contains raw bytes with the high bit set.
+    REQUEST_LINE_HIGH_BIT,
+    /// The sniffed request body content type differs from the declared Content-Type.
+    REQUEST_CONTENT_TYPE_MISMATCH,
+    /// More cookies, or more cookie bytes, than the configured limits were seen.
+    COOKIES_LIMIT,
+    /// The response completed before the declared request body was fully received.
+    REQUEST_BODY_UNREAD,
+    /// A 100 Continue response was sent to a request without a body.
+    RESPONSE_CONTINUE_WITHOUT_BODY,
+    /// HTTP/0.9 was seen while disabled by the configuration.
+    HTTP_0_9_DISALLOWED,
+    /// Data which does not look like a response line was not treated as body.
+    RESPONSE_BODY_FALLBACK_DISALLOWED,
+    /// Whitespace seen between a chunk size and the line ending.
+    CHUNKED_LENGTH_WHITESPACE,
+    /// The response body has more chunks than the configured limit.
+    RESPONSE_TOO_MANY_CHUNKS,
+    /// A protocol version has a major or minor number above 99.
+    PROTOCOL_VERSION_INVALID,
+    /// The response body length does not match the declared Content-Length.
+    RESPONSE_CONTENT_LENGTH_MISMATCH,
+    /// A response header appears to have been injected through a request parameter or header.
+    RESPONSE_HEADER_INJECTION,
+    /// The request body is longer than the declared Content-Length.
+    REQUEST_BODY_OVERFLOW,
+    /// The request expected a 100 Continue response that the server did not send.
+    EXPECT_MISMATCH,
+    /// The request method is not in the configured allow-list.
+    REQUEST_METHOD_DISALLOWED,
+    /// The request target contains more than one question mark.
+    URI_MULTIPLE_QUERY_MARKS,
+    /// The response status code is not exactly three digits, as in "0200" or "20".
+    RESPONSE_STATUS_FORMAT_INVALID,
+    /// A response header that must appear only once, such as Content-Length, is repeated.
+    RESPONSE_SINGULAR_HEADER_REPEATED,
+    /// The request URI path is longer than the configured limit.
+    PATH_TOO_LONG,
+    /// A response header value contains a raw CR or LF, which indicates response splitting.
+    RESPONSE_SPLITTING,
+    /// The server personality was inferred from the Server response header.
+    SERVER_PERSONALITY_INFERRED,
     /// Error retrieving a log message's code
     ERROR,
 }
@@ -192,6 +252,8 @@ pub struct Logger {
     /// Log level used when deciding whether to store or
     /// ignore the messages issued by the parser.
     pub level: HtpLogLevel,
+    /// The most recent message sent, shared by all clones of this logger.
+    last_message: Rc<RefCell<Option<Message>>>,
 }
 
 impl Logger {
@@ -200,8 +262,14 @@ impl Logger {
         Self {
             sender: sender.clone(),
             level,
+            last_message: Rc::new(RefCell::new(None)),
         }
     }
+
+    /// Returns the most recent message sent by this logger or any of its clones.
+    pub fn last_message(&self) -> Option<Message> {
+        self.last_message.borrow().clone()
+    }
     /// Logs a message to the logger channel.
     pub fn log(
         &mut self,
@@ -213,7 +281,9 @@ impl Logger {
     ) {
         // Ignore messages below our log level.
         if level <= self.level {
-            let _ = self.sender.send(Message::new(file, line, level, code, msg));
+            let message = Message::new(file, line, level, code, msg);
+            self.last_message.replace(Some(message.clone()));
+            let _ = self.sender.send(message);
         }
     }
 }
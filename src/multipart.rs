@@ -3,7 +3,7 @@ use crate::{
     config::{Config, MultipartConfig},
     error::Result,
     headers::{Flags as HeaderFlags, Parser as HeadersParser, Side},
-    hook::FileDataHook,
+    hook::{FileDataHook, MultipartPartHook},
     list::List,
     parsers::parse_content_type,
     table::Table,
@@ -16,7 +16,7 @@ use crate::{
 };
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take, take_till, take_until, take_while},
+    bytes::complete::{tag, tag_no_case, take, take_till, take_until, take_while, take_while1},
     character::complete::char,
     character::is_space as nom_is_space,
     combinator::{map, not, opt, peek},
@@ -102,6 +102,9 @@ impl Flags {
     pub const PART_INCOMPLETE: u64 = 0x20_0000;
     /// A NUL byte was seen in a part header area.
     pub const NUL_BYTE: u64 = 0x40_0000;
+    /// The filename and filename* Content-Disposition parameters name different files.
+    /// Servers differ in which one they use, so this may be indicative of evasion.
+    pub const CD_FILENAME_MISMATCH: u64 = 0x80_0000;
     /// A collection of flags that all indicate an invalid C-D header.
     pub const CD_INVALID: u64 = (Self::CD_TYPE_INVALID
         | Self::CD_PARAM_REPEATED
@@ -126,7 +129,8 @@ impl Flags {
         | Self::BBOUNDARY_NLWS_AFTER
         | Self::HAS_EPILOGUE
         | Self::HBOUNDARY_UNUSUAL
-        | Self::HBOUNDARY_QUOTED);
+        | Self::HBOUNDARY_QUOTED
+        | Self::CD_FILENAME_MISMATCH);
     /// A collection of flags that all indicate an unusual Multipart payload, with a low sensitivity to irregularities.
     pub const UNUSUAL_PARANOID: u64 =
         (Self::UNUSUAL | Self::LF_LINE | Self::BBOUNDARY_LWS_AFTER | Self::HAS_PREAMBLE);
@@ -141,6 +145,8 @@ pub struct Parser {
     pub cfg: MultipartConfig,
     /// Request file data hook invoked whenever file data is available.
     pub hook: FileDataHook,
+    /// Multipart part complete hook invoked whenever a part is finished.
+    pub part_hook: MultipartPartHook,
     /// Number of extracted files.
     pub file_count: u32,
     // Internal parsing fields; move into a private structure
@@ -215,6 +221,7 @@ impl Parser {
             },
             cfg: cfg.multipart_cfg.clone(),
             hook: cfg.hook_request_file_data.clone(),
+            part_hook: cfg.hook_multipart_part_complete.clone(),
             file_count: 0,
             // We're starting in boundary-matching mode. The first boundary can appear without the
             // CRLF, and our starting state expects that. If we encounter non-boundary data, the
@@ -513,7 +520,12 @@ impl Parser {
             self.get_current_part()?.value.add(data.as_slice());
             self.part_data_pieces.clear();
         }
-        Ok(())
+        // Notify callbacks about the completed part.
+        let part = self
+            .current_part_idx
+            .and_then(|idx| self.multipart.parts.get(idx))
+            .ok_or(HtpStatus::ERROR)?;
+        self.part_hook.run_all(part)
     }
 
     /// Returns the multipart structure created by the parser.
@@ -804,6 +816,8 @@ impl Parser {
 
         // Require "form-data" at the beginning of the header.
         if let Ok((_, params)) = content_disposition((*header.value).as_slice()) {
+            let mut filename: Option<Vec<u8>> = None;
+            let mut ext_filename_seen = false;
             for (param_name, param_value) in params {
                 match param_name {
                     b"name" => {
@@ -822,18 +836,38 @@ impl Parser {
                     }
                     b"filename" => {
                         // Check that we have not seen the filename parameter already.
-                        match part.file {
-                            Some(_) => {
-                                self.multipart.flags.set(Flags::CD_PARAM_REPEATED);
-                                return Err(HtpStatus::DECLINED);
-                            }
+                        if filename.is_some() {
+                            self.multipart.flags.set(Flags::CD_PARAM_REPEATED);
+                            return Err(HtpStatus::DECLINED);
+                        }
+                        // The extended filename takes precedence.
+                        if part.file.is_none() {
+                            part.file = Some(File::new(
+                                HtpFileSource::MULTIPART,
+                                Some(Bstr::from(param_value.as_slice())),
+                            ));
+                        }
+                        filename = Some(param_value);
+                    }
+                    b"filename*" => {
+                        if ext_filename_seen {
+                            self.multipart.flags.set(Flags::CD_PARAM_REPEATED);
+                            return Err(HtpStatus::DECLINED);
+                        }
+                        ext_filename_seen = true;
+                        let filename = if let Some(filename) = decode_ext_value(&param_value) {
+                            Bstr::from(filename)
+                        } else {
+                            self.multipart.flags.set(Flags::CD_SYNTAX_INVALID);
+                            return Err(HtpStatus::DECLINED);
+                        };
+                        match &mut part.file {
+                            Some(file) => file.filename = Some(filename),
                             None => {
-                                part.file = Some(File::new(
-                                    HtpFileSource::MULTIPART,
-                                    Some(Bstr::from(param_value)),
-                                ));
+                                part.file =
+                                    Some(File::new(HtpFileSource::MULTIPART, Some(filename)))
                             }
-                        };
+                        }
                     }
                     _ => {
                         // Unknown parameter.
@@ -842,6 +876,18 @@ impl Parser {
                     }
                 }
             }
+            // When both are present, the file is named by filename*; check that
+            // filename agrees with it.
+            let mismatch = match (&filename, &part.file) {
+                (Some(filename), Some(file)) if ext_filename_seen => file
+                    .filename
+                    .as_ref()
+                    .map_or(true, |ext_filename| !ext_filename.eq(filename)),
+                _ => false,
+            };
+            if mismatch {
+                self.multipart.flags.set(Flags::CD_FILENAME_MISMATCH);
+            }
         } else {
             self.multipart.flags.set(Flags::CD_SYNTAX_INVALID);
             return Err(HtpStatus::DECLINED);
@@ -890,6 +936,41 @@ pub struct Part {
     pub file: Option<File>,
 }
 
+/// The name, file name, content type and data of a form-data part, as returned by
+/// Transaction::multipart_parts().
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultipartPart {
+    /// Part name, from the Content-Disposition header.
+    pub name: Option<Bstr>,
+    /// File name, from the Content-Disposition header. An extended filename* parameter
+    /// takes precedence over filename, and is returned decoded.
+    pub filename: Option<Bstr>,
+    /// Part content type, from the Content-Type header.
+    pub content_type: Option<Bstr>,
+    /// Part data, or None for file parts. File data is not kept in memory; it is passed
+    /// to the file data callbacks and, if enabled, extracted to a file.
+    pub body: Option<Bstr>,
+}
+
+impl From<&Part> for MultipartPart {
+    fn from(part: &Part) -> Self {
+        Self {
+            name: if part.name.is_empty() {
+                None
+            } else {
+                Some(part.name.clone())
+            },
+            filename: part.file.as_ref().and_then(|file| file.filename.clone()),
+            content_type: part.content_type.clone(),
+            body: if part.type_0 == HtpMultipartType::FILE {
+                None
+            } else {
+                Some(part.value.clone())
+            },
+        }
+    }
+}
+
 impl Default for Part {
     fn default() -> Self {
         Self {
@@ -979,7 +1060,7 @@ pub struct Multipart {
 ///  - Opera encodes " as \" and \ as \\.
 fn content_disposition_param() -> impl Fn(&[u8]) -> IResult<&[u8], (&[u8], Vec<u8>)> {
     move |input| {
-        let (mut remaining_input, param_name) = map(
+        let (remaining_input, param_name) = map(
             tuple((
                 take_ascii_whitespace(),
                 char(';'),
@@ -988,10 +1069,19 @@ fn content_disposition_param() -> impl Fn(&[u8]) -> IResult<&[u8], (&[u8], Vec<u
                 take_ascii_whitespace(),
                 char('='),
                 take_ascii_whitespace(),
-                char('\"'), //must start with opening quote
             )),
-            |(_, _, _, param_name, _, _, _, _)| param_name,
+            |(_, _, _, param_name, _, _, _)| param_name,
         )(input)?;
+        // Extended parameters (RFC 5987), as in filename*=UTF-8''a%20b.txt, are not quoted.
+        if param_name.ends_with(b"*") {
+            let (remaining_input, param_value) =
+                take_while1(|c: u8| c != b';' && c != b',' && !c.is_ascii_whitespace())(
+                    remaining_input,
+                )?;
+            return Ok((remaining_input, (param_name, param_value.to_vec())));
+        }
+        //must start with opening quote
+        let (mut remaining_input, _) = char('\"')(remaining_input)?;
         // Unescape any escaped " and \ and find the closing "
         let mut param_value = Vec::new();
         loop {
@@ -1015,6 +1105,47 @@ fn content_disposition_param() -> impl Fn(&[u8]) -> IResult<&[u8], (&[u8], Vec<u
     }
 }
 
+/// Decodes an RFC 5987 extended parameter value, such as "UTF-8''%e2%82%ac%20rates.txt".
+/// The UTF-8 and ISO-8859-1 charsets are supported; ISO-8859-1 values are converted to UTF-8.
+///
+/// Returns None if the value is malformed or uses another charset.
+fn decode_ext_value(value: &[u8]) -> Option<Vec<u8>> {
+    let mut fields = value.splitn(3, |c| *c == b'\'');
+    let charset = fields.next()?;
+    let _language = fields.next()?;
+    let encoded = fields.next()?;
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        if encoded[i] == b'%' {
+            let hex = encoded.get(i + 1..i + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            let digit = |c: u8| (c as char).to_digit(16).unwrap_or(0) as u8;
+            decoded.push((digit(hex[0]) << 4) | digit(hex[1]));
+            i += 3;
+        } else {
+            decoded.push(encoded[i]);
+            i += 1;
+        }
+    }
+    if charset.eq_ignore_ascii_case(b"utf-8") {
+        std::str::from_utf8(&decoded).ok()?;
+        Some(decoded)
+    } else if charset.eq_ignore_ascii_case(b"iso-8859-1") {
+        Some(
+            decoded
+                .iter()
+                .map(|c| *c as char)
+                .collect::<String>()
+                .into_bytes(),
+        )
+    } else {
+        None
+    }
+}
+
 /// Extracts and decodes a C-D header param names and values. This is impossible to do correctly without a
 /// parsing personality because most browsers are broken:
 ///  - Firefox encodes " as \", and \ is not encoded.
@@ -1247,6 +1378,9 @@ pub fn find_boundary<'a>(content_type: &'a [u8], flags: &mut u64) -> Option<&'a
             // Unusual but allowed to have only whitespace after the boundary
             flags.set(Flags::HBOUNDARY_UNUSUAL)
         }
+        if opening_quote.is_some() {
+            flags.set(Flags::HBOUNDARY_QUOTED)
+        }
         if !chars_before_equal.is_empty()
             || (opening_quote.is_some() && closing_quote.is_none())
             || (opening_quote.is_none() && closing_quote.is_some())
@@ -4,12 +4,12 @@ use crate::{
     connection_parser::{ConnectionParser, Data as ParserData, HtpStreamState, State},
     error::Result,
     hook::DataHook,
-    parsers::parse_chunked_length,
+    parsers::{chunked_length_has_trailing_whitespace, parse_chunked_length},
     transaction::{Data, HtpRequestProgress, HtpResponseProgress, HtpTransferCoding, Transaction},
     util::{
         chomp, is_line_ignorable, is_space, is_valid_chunked_length_data, nom_take_is_space,
         take_is_space, take_not_is_space, take_till_eol, take_till_lf, take_till_lf_null,
-        FlagOperations, HtpFlags,
+        FlagOperations, HtpFlags, HtpFlags2,
     },
     HtpStatus,
 };
@@ -200,7 +200,7 @@ impl ConnectionParser {
 
     /// If there is any data left in the inbound data chunk, this function will preserve
     /// it for later consumption. The maximum amount accepted for buffering is controlled
-    /// by Config::field_limit.
+    /// by Config::request_field_limit, or Config::field_limit if not set.
     fn check_request_buffer_limit(&mut self, len: usize) -> Result<()> {
         if len == 0 {
             return Ok(());
@@ -212,7 +212,8 @@ impl ConnectionParser {
         if let Some(header) = &self.request_header {
             newlen = newlen.wrapping_add(header.len())
         }
-        let field_limit = self.request().cfg.field_limit;
+        let cfg = self.request().cfg.clone();
+        let field_limit = cfg.request_field_limit.unwrap_or(cfg.field_limit);
         if newlen > field_limit {
             htp_error!(
                 self.logger,
@@ -224,6 +225,13 @@ impl ConnectionParser {
             );
             return Err(HtpStatus::ERROR);
         }
+        // Check the soft limit, notifying only when the buffer grows over it.
+        if let Some(soft_limit) = cfg.field_soft_limit {
+            if newlen > soft_limit && newlen.wrapping_sub(len) <= soft_limit {
+                cfg.hook_field_soft_limit
+                    .run_all(self.request_mut(), newlen)?;
+            }
+        }
         Ok(())
     }
 
@@ -387,6 +395,19 @@ impl ConnectionParser {
                 Ok(len) => {
                     self.request_chunked_length = len;
                     if let Some(len) = len {
+                        if chunked_length_has_trailing_whitespace(&data) {
+                            htp_warn!(
+                                self.logger,
+                                HtpLogCode::CHUNKED_LENGTH_WHITESPACE,
+                                "Request chunk encoding: Whitespace after chunk length"
+                            );
+                            self.request_mut()
+                                .flags
+                                .set(HtpFlags::CHUNKED_LENGTH_WHITESPACE);
+                            if self.cfg.chunked_length_whitespace_reject {
+                                return Err(HtpStatus::ERROR);
+                            }
+                        }
                         match len.cmp(&0) {
                             Ordering::Equal => {
                                 // End of data
@@ -594,6 +615,14 @@ impl ConnectionParser {
                     return Ok(());
                 }
                 Err(_) => {
+                    if !self.cfg.allow_http_0_9 {
+                        htp_error!(
+                            self.logger,
+                            HtpLogCode::HTTP_0_9_DISALLOWED,
+                            "Request line: HTTP/0.9 is not allowed"
+                        );
+                        return Err(HtpStatus::ERROR);
+                    }
                     // We're done with this request.
                     self.request_state = State::FINALIZE;
                 }
@@ -710,6 +739,23 @@ impl ConnectionParser {
                         HtpLogCode::REQUEST_BODY_UNEXPECTED,
                         "Unexpected request body"
                     );
+                    // More bytes than the declared Content-Length.
+                    if self.request().request_transfer_coding == HtpTransferCoding::IDENTITY
+                        && self.request().request_content_length >= 0
+                        && !self
+                            .request()
+                            .flags2
+                            .is_set(HtpFlags2::REQUEST_BODY_OVERFLOW)
+                    {
+                        htp_warn!(
+                            self.logger,
+                            HtpLogCode::REQUEST_BODY_OVERFLOW,
+                            "Request body exceeds Content-Length"
+                        );
+                        self.request_mut()
+                            .flags2
+                            .set(HtpFlags2::REQUEST_BODY_OVERFLOW);
+                    }
                 } else {
                     self.request_body_data_left = 1;
                 }
@@ -879,7 +925,18 @@ impl ConnectionParser {
         // Return if there's been an error or if we've run out of data. We are relying
         // on processors to supply error messages, so we'll keep quiet here.
         {
+            let index = self.request_index();
+            let position = self.request_curr_data.position() as i64;
             let mut rc = self.handle_request_state(&mut chunk);
+            if let Err(e) = self.request_buffers_update() {
+                rc = Err(e);
+            }
+            // Attribute the consumed bytes to the transaction that consumed them. Bytes
+            // given back for the next transaction are subtracted again.
+            let consumed = self.request_curr_data.position() as i64 - position;
+            if let Some(tx) = self.tx_mut(index) {
+                tx.request_total_size += consumed;
+            }
 
             if rc.is_ok() {
                 if self.request_status == HtpStreamState::TUNNEL {
@@ -918,6 +975,7 @@ impl ConnectionParser {
                 // Permanent stream error.
                 Err(_) => {
                     self.request_status = HtpStreamState::ERROR;
+                    self.run_hook_error();
                     return HtpStreamState::ERROR;
                 }
             }
@@ -4,13 +4,14 @@ use crate::{
     connection_parser::ConnectionParser,
     error::Result,
     headers::Flags as HeaderFlags,
-    parsers::{parse_content_length, parse_protocol},
+    parsers::{parse_content_length, parse_protocol, protocol_version_numbers},
     request::HtpMethod,
     transaction::{Header, HtpProtocol},
     util::{
         is_space, take_ascii_whitespace, take_is_space, take_not_is_space, take_until_null,
         FlagOperations, HtpFlags,
     },
+    HtpStatus,
 };
 use nom::{bytes::complete::take_while, error::ErrorKind, sequence::tuple};
 use std::cmp::Ordering;
@@ -42,6 +43,7 @@ impl ConnectionParser {
             // For simplicity reasons, we count the repetitions of all headers
             // Keep track of repeated same-name headers.
             h_existing.flags.set(HtpFlags::FIELD_REPEATED);
+            h_existing.parser_flags.set(header.parser_flags);
             // Having multiple C-L headers is against the RFC but
             // servers may ignore the subsequent headers if the values are the same.
             if header.name.cmp_nocase("Content-Length") == Ordering::Equal {
@@ -57,6 +59,11 @@ impl ConnectionParser {
                         "Ambiguous request C-L value"
                     );
                 }
+            } else if header.name.cmp_nocase("Cookie") == Ordering::Equal {
+                // Cookie headers are combined with the cookie separator so that
+                // cookie parsing sees every cookie.
+                h_existing.value.extend_from_slice(b"; ");
+                h_existing.value.extend_from_slice(header.value.as_slice());
             } else {
                 // Add to the existing header.
                 h_existing.value.extend_from_slice(b", ");
@@ -86,9 +93,12 @@ impl ConnectionParser {
         &mut self,
         data: &'a [u8],
     ) -> Result<(&'a [u8], bool)> {
-        let rc = self.request_mut().request_header_parser.headers()(data);
+        let rc = self
+            .request_mut()
+            .request_header_parser
+            .headers_with_terminators()(data);
         if let Ok((remaining, (headers, eoh))) = rc {
-            for h in headers {
+            for (h, terminator) in headers {
                 let mut flags = 0;
                 let name_flags = h.name.flags;
                 // Ignore LWS after field-name.
@@ -156,13 +166,63 @@ impl ConnectionParser {
                         HtpFlags::FIELD_INVALID
                     );
                 }
-                self.process_request_header_generic(Header::new_with_flags(
-                    h.name.name.into(),
-                    h.value.value.into(),
-                    flags,
-                ))?;
+                // Line terminated by a bare CR?
+                if h.value.flags.is_set(HeaderFlags::BARE_CR_EOL) {
+                    htp_warn_once!(
+                        self.logger,
+                        HtpLogCode::REQUEST_BARE_CR_EOL,
+                        "Request field terminated by a bare CR",
+                        self.request_mut().flags,
+                        flags,
+                        HtpFlags::REQUEST_BARE_CR_EOL
+                    );
+                }
+                // Folded over too many lines?
+                if h.value.flags.is_set(HeaderFlags::FOLDING_LIMIT) {
+                    htp_warn_once!(
+                        self.logger,
+                        HtpLogCode::HEADER_FOLDING_LIMIT,
+                        "Request field folded over too many lines",
+                        self.request_mut().flags,
+                        flags,
+                        HtpFlags::FIELD_FOLDING_LIMIT
+                    );
+                }
+                // Value longer than the soft limit?
+                if let Some(limit) = self.cfg.header_value_soft_limit {
+                    if h.value.value.len() > limit {
+                        htp_warn_once!(
+                            self.logger,
+                            HtpLogCode::HEADER_VALUE_SOFT_LIMIT,
+                            "Request field value exceeds the soft limit",
+                            self.request_mut().flags,
+                            flags,
+                            HtpFlags::FIELD_VALUE_SOFT_LIMIT
+                        );
+                    }
+                }
+                let mut header =
+                    Header::new_with_flags(h.name.name.into(), h.value.value.into(), flags);
+                header.terminator = terminator;
+                header.parser_flags = h.name.flags | h.value.flags;
+                self.process_request_header_generic(header)?;
             }
             Ok((remaining, eoh))
+        } else if let Err(nom::Err::Failure((_, ErrorKind::TooLarge))) = rc {
+            htp_error!(
+                self.logger,
+                HtpLogCode::HEADERS_TOO_MANY,
+                "Too many request headers"
+            );
+            self.request_mut().flags.set(HtpFlags::HEADERS_TOO_MANY);
+            Err(HtpStatus::ERROR)
+        } else if let Err(nom::Err::Failure(_)) = rc {
+            htp_error!(
+                self.logger,
+                HtpLogCode::REQUEST_HEADER_INVALID,
+                "Request header name rejected by strict parsing"
+            );
+            Err(HtpStatus::ERROR)
         } else {
             Ok((data, false))
         }
@@ -272,6 +332,7 @@ impl ConnectionParser {
                     }
                 }
                 self.request_mut().request_uri = Some(Bstr::from(uri));
+                self.request_mut().request_uri_raw = Some(Bstr::from(uri));
                 // Is there protocol information available?
                 if protocol.is_empty() {
                     // No, this looks like a HTTP/0.9 request.
@@ -290,6 +351,16 @@ impl ConnectionParser {
                 self.request_mut().request_protocol = Some(Bstr::from(protocol));
                 self.request_mut().request_protocol_number =
                     parse_protocol(protocol, &mut self.logger);
+                if protocol_version_numbers(protocol)
+                    .map_or(false, |(major, minor)| major > 99 || minor > 99)
+                {
+                    htp_warn!(
+                        self.logger,
+                        HtpLogCode::PROTOCOL_VERSION_INVALID,
+                        "Request line: protocol version number above 99"
+                    );
+                    self.request_mut().flags.set(HtpFlags::PROTOCOL_INVALID);
+                }
                 if self.request().request_method_number == HtpMethod::UNKNOWN
                     && self.request().request_protocol_number == HtpProtocol::INVALID
                 {
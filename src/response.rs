@@ -1,18 +1,22 @@
 use crate::{
     bstr::Bstr,
+    config::HtpResponseBodyFallback,
     connection_parser::{ConnectionParser, Data as ParserData, HtpStreamState, State},
     decompressors::HtpContentEncoding,
     error::Result,
     hook::DataHook,
-    parsers::{parse_chunked_length, parse_content_length},
+    parsers::{
+        chunked_length_has_trailing_whitespace, parse_chunked_length, parse_content_length,
+        parse_content_type_header,
+    },
     request::HtpMethod,
     transaction::{
         Data, HtpProtocol, HtpRequestProgress, HtpResponseProgress, HtpTransferCoding, Transaction,
     },
     uri::Uri,
     util::{
-        chomp, is_line_ignorable, is_space, is_valid_chunked_length_data, take_till_eol,
-        take_till_lf, treat_response_line_as_body, FlagOperations, HtpFlags,
+        chomp, is_line_ignorable, is_space, is_valid_chunked_length_data, parse_content_range,
+        take_till_eol, take_till_lf, treat_response_line_as_body, FlagOperations, HtpFlags,
     },
     HtpStatus,
 };
@@ -95,7 +99,7 @@ impl ConnectionParser {
     }
 
     /// The maximum amount accepted for buffering is controlled
-    /// by htp_config_t::field_limit.
+    /// by Config::response_field_limit, or Config::field_limit if not set.
     fn check_response_buffer_limit(&mut self, len: usize) -> Result<()> {
         if self.response_curr_len() == 0 || len == 0 {
             return Ok(());
@@ -108,7 +112,8 @@ impl ConnectionParser {
             newlen = newlen.wrapping_add(response_header.len())
         }
 
-        let field_limit = self.response().cfg.field_limit;
+        let cfg = self.response().cfg.clone();
+        let field_limit = cfg.response_field_limit.unwrap_or(cfg.field_limit);
         if newlen > field_limit {
             htp_error!(
                 self.logger,
@@ -120,6 +125,13 @@ impl ConnectionParser {
             );
             return Err(HtpStatus::ERROR);
         }
+        // Check the soft limit, notifying only when the buffer grows over it.
+        if let Some(soft_limit) = cfg.field_soft_limit {
+            if newlen > soft_limit && newlen.wrapping_sub(len) <= soft_limit {
+                cfg.hook_field_soft_limit
+                    .run_all(self.response_mut(), newlen)?;
+            }
+        }
         Ok(())
     }
 
@@ -205,6 +217,19 @@ impl ConnectionParser {
                         self.response_chunked_length = len;
                         // Handle chunk length
                         if let Some(len) = len {
+                            if chunked_length_has_trailing_whitespace(&data) {
+                                htp_warn!(
+                                    self.logger,
+                                    HtpLogCode::CHUNKED_LENGTH_WHITESPACE,
+                                    "Response chunk encoding: Whitespace after chunk length"
+                                );
+                                self.response_mut()
+                                    .flags
+                                    .set(HtpFlags::CHUNKED_LENGTH_WHITESPACE);
+                                if self.cfg.chunked_length_whitespace_reject {
+                                    return Err(HtpStatus::ERROR);
+                                }
+                            }
                             match len.cmp(&0) {
                                 Ordering::Equal => {
                                     // End of data
@@ -213,6 +238,22 @@ impl ConnectionParser {
                                         HtpResponseProgress::TRAILER
                                 }
                                 Ordering::Greater => {
+                                    let count =
+                                        self.response().response_chunk_count.wrapping_add(1);
+                                    self.response_mut().response_chunk_count = count;
+                                    if self.cfg.max_chunks.map_or(false, |max| count > max) {
+                                        // Finalizing here would hand the remaining chunks to
+                                        // the next response, so stop parsing the stream.
+                                        htp_error!(
+                                            self.logger,
+                                            HtpLogCode::RESPONSE_TOO_MANY_CHUNKS,
+                                            "Response chunk encoding: Too many chunks"
+                                        );
+                                        self.response_mut()
+                                            .flags
+                                            .set(HtpFlags::RESPONSE_TOO_MANY_CHUNKS);
+                                        return Err(HtpStatus::ERROR);
+                                    }
                                     // More data available.
                                     self.response_state = State::BODY_CHUNKED_DATA
                                 }
@@ -277,9 +318,7 @@ impl ConnectionParser {
                 .response()
                 .response_message_len
                 .wrapping_add(data.len() as i64);
-            // Send the gap to the data hooks
-            let mut tx_data = Data::new(self.response_mut(), data, false);
-            self.response_run_hook_body_data(&mut tx_data)?;
+            self.response_process_body_gap(data)?;
         } else {
             // Consume the data.
             self.response_process_body_data_ex(Some(&data.as_slice()[0..bytes_to_consume]))?;
@@ -306,9 +345,7 @@ impl ConnectionParser {
     /// when more data is needed.
     pub fn response_body_identity_stream_close(&mut self, data: &ParserData) -> Result<()> {
         if data.is_gap() {
-            // Send the gap to the data hooks
-            let mut tx_data = Data::new(self.response_mut(), data, false);
-            self.response_run_hook_body_data(&mut tx_data)?;
+            self.response_process_body_gap(data)?;
         } else if !data.is_empty() {
             // Consume all data from the input buffer.
             self.response_process_body_data_ex(data.data())?;
@@ -366,6 +403,9 @@ impl ConnectionParser {
             .response_headers
             .get_nocase_nozero("transfer-encoding")
             .map(|(_, val)| val.clone());
+        if let Some(te) = &te_opt {
+            self.response_mut().check_transfer_encoding(&te.value)?;
+        }
         // Check for "101 Switching Protocol" response.
         // If it's seen, it means that traffic after empty line following headers
         // is no longer HTTP. We can treat it similarly to CONNECT.
@@ -411,6 +451,16 @@ impl ConnectionParser {
                 );
                 return Err(HtpStatus::ERROR);
             }
+            if !self.response().request_has_body() {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::RESPONSE_CONTINUE_WITHOUT_BODY,
+                    "100-Continue sent to a request without a body"
+                );
+                self.response_mut()
+                    .flags
+                    .set(HtpFlags::RESPONSE_CONTINUE_WITHOUT_BODY);
+            }
             // Ignore any response headers seen so far.
             self.response_mut().response_headers.elements.clear();
             // Expecting to see another response line next.
@@ -481,10 +531,7 @@ impl ConnectionParser {
 
                 let mut response_content_type = Bstr::from(response_content_type);
                 response_content_type.make_ascii_lowercase();
-                if response_content_type
-                    .index_of_nocase("multipart/byteranges")
-                    .is_some()
-                {
+                if response_content_type.starts_with_nocase("multipart/byteranges") {
                     multipart_byteranges = true;
                 }
                 Some(response_content_type)
@@ -494,7 +541,19 @@ impl ConnectionParser {
 
             if response_content_type.is_some() {
                 self.response_mut().response_content_type = response_content_type;
+                let parsed_response_content_type = self
+                    .response()
+                    .response_headers
+                    .get_nocase_nozero("content-type")
+                    .and_then(|(_, ct)| parse_content_type_header(ct.value.as_slice()).ok());
+                self.response_mut().parsed_response_content_type = parsed_response_content_type;
             }
+            let response_content_range = self
+                .response()
+                .response_headers
+                .get_nocase_nozero("content-range")
+                .and_then(|(_, cr)| parse_content_range(cr.value.as_slice()));
+            self.response_mut().response_content_range = response_content_range;
             // 2. If a Transfer-Encoding header field (section 14.40) is present and
             //   indicates that the "chunked" transfer coding has been applied, then
             //   the length is defined by the chunked encoding (section 3.6).
@@ -523,7 +582,10 @@ impl ConnectionParser {
                 // We are still going to check for the presence of C-L
                 if cl_opt.is_some() {
                     // This is a violation of the RFC
-                    self.response_mut().flags.set(HtpFlags::REQUEST_SMUGGLING)
+                    self.response_mut().flags.set(HtpFlags::REQUEST_SMUGGLING);
+                    self.response_mut()
+                        .flags
+                        .set(HtpFlags::RESPONSE_SMUGGLING_TE_CL)
                 }
                 self.response_state = State::BODY_CHUNKED_LENGTH;
                 self.response_mut().response_progress = HtpResponseProgress::BODY
@@ -578,10 +640,26 @@ impl ConnectionParser {
                 //   would leave no possibility for the server to send back a response.)
                 self.response_state = State::BODY_IDENTITY_STREAM_CLOSE;
                 self.response_mut().response_transfer_coding = HtpTransferCoding::IDENTITY;
+                // Chunked request framing answered with close-delimited response framing.
+                if self.response().request_transfer_coding == HtpTransferCoding::CHUNKED {
+                    self.response_mut()
+                        .flags
+                        .set(HtpFlags::TRANSFER_CODING_MISMATCH);
+                }
                 self.response_mut().response_progress = HtpResponseProgress::BODY;
                 self.response_body_data_left = -1
             }
         }
+        // Origin servers should send a Date header on HTTP/1.1 responses.
+        if self.cfg.response_date_required
+            && self.response().response_protocol_number == HtpProtocol::V1_1
+            && self.response().response_progress == HtpResponseProgress::BODY
+            && !self.response().response_has_date()
+        {
+            self.response_mut()
+                .flags
+                .set(HtpFlags::RESPONSE_DATE_MISSING);
+        }
         // NOTE We do not need to check for short-style HTTP/0.9 requests here because
         //      that is done earlier, before response line parsing begins
         self.state_response_headers()
@@ -717,6 +795,11 @@ impl ConnectionParser {
         // a response line. If it does not look like a line, process the
         // data as a response body because that is what browsers do.
         if treat_response_line_as_body(data) {
+            // An HTTP/0.9 response: Config::set_allow_http_0_9 controls this case through
+            // response_body_fallback.
+            if self.cfg.response_body_fallback == HtpResponseBodyFallback::NEVER {
+                return self.response_body_fallback_disallowed();
+            }
             self.response_mut().response_content_encoding_processing = HtpContentEncoding::NONE;
             self.response_process_body_data_ex(Some(data))?;
             // Continue to process response body. Because we don't have
@@ -739,6 +822,48 @@ impl ConnectionParser {
         Ok(())
     }
 
+    /// Flags a second response line for the current transaction, if it already saw
+    /// a final (non-1xx) status.
+    ///
+    /// Returns HtpStatus::ERROR if configured to reject such transactions.
+    pub fn response_multiple_status_lines(&mut self) -> Result<()> {
+        if self.response().response_status_number.in_range(100, 199) {
+            return Ok(());
+        }
+        if !self
+            .response()
+            .flags
+            .is_set(HtpFlags::RESPONSE_MULTIPLE_STATUS_LINES)
+        {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::RESPONSE_MULTIPLE_STATUS_LINES,
+                "Response line seen after a final status"
+            );
+            self.response_mut()
+                .flags
+                .set(HtpFlags::RESPONSE_MULTIPLE_STATUS_LINES);
+        }
+        if self.cfg.response_multiple_status_lines_reject {
+            return Err(HtpStatus::ERROR);
+        }
+        Ok(())
+    }
+
+    /// Flags and rejects data which does not look like a response line, when the
+    /// configuration does not allow treating it as response body.
+    ///
+    /// Returns HtpStatus::ERROR.
+    pub fn response_body_fallback_disallowed(&mut self) -> Result<()> {
+        htp_error!(
+            self.logger,
+            HtpLogCode::RESPONSE_BODY_FALLBACK_DISALLOWED,
+            "Invalid response line not treated as body"
+        );
+        self.response_mut().flags.set(HtpFlags::STATUS_LINE_INVALID);
+        Err(HtpStatus::ERROR)
+    }
+
     /// Finalizes response parsing.
     pub fn response_finalize(&mut self, data: &ParserData) -> Result<()> {
         if data.is_gap() {
@@ -780,7 +905,12 @@ impl ConnectionParser {
             //closing
             return self.state_response_complete_ex(0);
         }
-        if treat_response_line_as_body(&data) {
+        if treat_response_line_as_body(&data)
+            && self.cfg.response_body_fallback != HtpResponseBodyFallback::ONLY_FIRST_LINE
+        {
+            if self.cfg.response_body_fallback == HtpResponseBodyFallback::NEVER {
+                return self.response_body_fallback_disallowed();
+            }
             // Interpret remaining bytes as body data
             htp_warn!(
                 self.logger,
@@ -824,12 +954,16 @@ impl ConnectionParser {
                 HtpLogCode::UNABLE_TO_MATCH_RESPONSE_TO_REQUEST,
                 "Unable to match response to request"
             );
+            if self.cfg.tx_match_strict {
+                return Err(HtpStatus::ERROR);
+            }
             let tx = self.response_mut();
             let mut uri = Uri::default();
             uri.path = Some(Bstr::from("/libhtp::request_uri_not_seen"));
             tx.request_uri = uri.path.clone();
             tx.parsed_uri = Some(uri);
             tx.request_progress = HtpRequestProgress::COMPLETE;
+            tx.flags.set(HtpFlags::REQUEST_EMPTY);
             self.request_next();
         }
         self.response_content_length = -1;
@@ -837,6 +971,15 @@ impl ConnectionParser {
         self.state_response_start()
     }
 
+    /// Sends a gap in the response body to the data hooks, unless the body is skipped.
+    fn response_process_body_gap(&mut self, data: &ParserData) -> Result<()> {
+        if self.response().response_body_skipped {
+            return Ok(());
+        }
+        let mut tx_data = Data::new(self.response_mut(), data, false);
+        self.response_run_hook_body_data(&mut tx_data)
+    }
+
     /// Run the RESPONSE_BODY_DATA hook.
     pub fn response_run_hook_body_data(&mut self, d: &mut Data) -> Result<()> {
         // Do not invoke callbacks with an empty data chunk.
@@ -936,7 +1079,18 @@ impl ConnectionParser {
         // on processors to add error messages, so we'll
         // keep quiet here.
         {
+            let index = self.response_index();
+            let position = self.response_curr_data.position() as i64;
             let mut rc = self.handle_response_state(&mut chunk);
+            if let Err(e) = self.response_buffers_update() {
+                rc = Err(e);
+            }
+            // Attribute the consumed bytes to the transaction that consumed them. Bytes
+            // given back for the next transaction are subtracted again.
+            let consumed = self.response_curr_data.position() as i64 - position;
+            if let Some(tx) = self.tx_mut(index) {
+                tx.response_total_size += consumed;
+            }
 
             if rc.is_ok() {
                 if self.response_status == HtpStreamState::TUNNEL {
@@ -976,6 +1130,7 @@ impl ConnectionParser {
                 // Permanent stream error.
                 Err(_) => {
                     self.response_status = HtpStreamState::ERROR;
+                    self.run_hook_error();
                     return HtpStreamState::ERROR;
                 }
             }
@@ -1,13 +1,14 @@
 use crate::{
     bstr::Bstr,
+    config::HtpDupHeaderPolicy,
     connection_parser::ConnectionParser,
     error::Result,
     headers::Flags as HeaderFlags,
-    parsers::{parse_content_length, parse_protocol, parse_status},
-    transaction::{Header, HtpProtocol, HtpResponseNumber},
+    parsers::{parse_content_length, parse_protocol, parse_status, protocol_version_numbers},
+    transaction::{Header, HtpProtocol, HtpResponseNumber, HtpResponseProgress},
     util::{
-        take_ascii_whitespace, take_is_space, take_is_space_or_null, take_not_is_space,
-        FlagOperations, HtpFlags,
+        canonicalize_header_name, is_status_line, take_ascii_whitespace, take_is_space,
+        take_is_space_or_null, take_not_is_space, FlagOperations, HtpFlags, HtpFlags2,
     },
     HtpStatus,
 };
@@ -41,6 +42,16 @@ impl ConnectionParser {
             response_tx.response_protocol = Some(Bstr::from(response_protocol));
             self.response_mut().response_protocol_number =
                 parse_protocol(response_protocol, &mut self.logger);
+            if protocol_version_numbers(response_protocol)
+                .map_or(false, |(major, minor)| major > 99 || minor > 99)
+            {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::PROTOCOL_VERSION_INVALID,
+                    "Response line: protocol version number above 99"
+                );
+                self.response_mut().flags.set(HtpFlags::PROTOCOL_INVALID);
+            }
 
             if ws1.is_empty() || status_code.is_empty() {
                 return Ok(());
@@ -68,9 +79,12 @@ impl ConnectionParser {
         &mut self,
         data: &'a [u8],
     ) -> Result<(&'a [u8], bool)> {
-        let rc = self.response_mut().response_header_parser.headers()(data);
+        let rc = self
+            .response_mut()
+            .response_header_parser
+            .headers_with_terminators()(data);
         if let Ok((remaining, (headers, eoh))) = rc {
-            for h in headers {
+            for (h, terminator) in headers {
                 let mut flags = 0;
                 let name_flags = &h.name.flags;
                 let value_flags = &h.value.flags;
@@ -133,6 +147,10 @@ impl ConnectionParser {
                         HtpFlags::FIELD_UNPARSEABLE
                     );
                     flags.set(HtpFlags::FIELD_INVALID);
+                    // A response line among the headers.
+                    if is_status_line(&h.value.value) {
+                        self.response_multiple_status_lines()?;
+                    }
                 } else if name_flags.is_set(HeaderFlags::NAME_EMPTY) {
                     // Empty header name.
                     htp_warn_once!(
@@ -144,13 +162,56 @@ impl ConnectionParser {
                         HtpFlags::FIELD_INVALID
                     );
                 }
-                self.process_response_header_generic(Header::new_with_flags(
-                    h.name.name.into(),
-                    h.value.value.into(),
-                    flags,
-                ))?;
+                // Folded over too many lines?
+                if value_flags.is_set(HeaderFlags::FOLDING_LIMIT) {
+                    htp_warn_once!(
+                        self.logger,
+                        HtpLogCode::HEADER_FOLDING_LIMIT,
+                        "Response field folded over too many lines",
+                        self.response_mut().flags,
+                        flags,
+                        HtpFlags::FIELD_FOLDING_LIMIT
+                    );
+                }
+                // Value longer than the soft limit?
+                if let Some(limit) = self.cfg.header_value_soft_limit {
+                    if h.value.value.len() > limit {
+                        htp_warn_once!(
+                            self.logger,
+                            HtpLogCode::HEADER_VALUE_SOFT_LIMIT,
+                            "Response field value exceeds the soft limit",
+                            self.response_mut().flags,
+                            flags,
+                            HtpFlags::FIELD_VALUE_SOFT_LIMIT
+                        );
+                    }
+                }
+                let mut header =
+                    Header::new_with_flags(h.name.name.into(), h.value.value.into(), flags);
+                header.terminator = terminator;
+                header.parser_flags = h.name.flags | h.value.flags;
+                if self.cfg.response_header_name_canonicalize {
+                    let canonical = canonicalize_header_name(header.name.as_slice());
+                    header.raw_name = Some(std::mem::replace(&mut header.name, canonical));
+                }
+                self.process_response_header_generic(header)?;
             }
             Ok((remaining, eoh))
+        } else if let Err(nom::Err::Failure((_, ErrorKind::TooLarge))) = rc {
+            htp_error!(
+                self.logger,
+                HtpLogCode::HEADERS_TOO_MANY,
+                "Too many response headers"
+            );
+            self.response_mut().flags.set(HtpFlags::HEADERS_TOO_MANY);
+            Err(HtpStatus::ERROR)
+        } else if let Err(nom::Err::Failure(_)) = rc {
+            htp_error!(
+                self.logger,
+                HtpLogCode::RESPONSE_HEADER_NAME_NOT_TOKEN,
+                "Response header name rejected by strict parsing"
+            );
+            Err(HtpStatus::ERROR)
         } else {
             Ok((data, false))
         }
@@ -158,10 +219,31 @@ impl ConnectionParser {
 
     /// Generic response header line(s) processor, which assembles folded lines
     /// into a single buffer before invoking the parsing function.
-    fn process_response_header_generic(&mut self, header: Header) -> Result<()> {
+    fn process_response_header_generic(&mut self, mut header: Header) -> Result<()> {
+        // Folded lines are joined with a space, so any CR or LF left inside the value
+        // was not part of a fold. A trailing CR cannot start another line.
+        let value = header.value.as_slice();
+        let len = value.len() - value.iter().rev().take_while(|c| **c == b'\r').count();
+        if value[..len].iter().any(|c| *c == b'\r' || *c == b'\n')
+            || header.parser_flags.is_set(HeaderFlags::CR_NOT_FOLDING)
+        {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::RESPONSE_SPLITTING,
+                "Response header value contains a raw CR or LF"
+            );
+            header.flags.set(HtpFlags::FIELD_INVALID);
+            self.response_mut()
+                .flags2
+                .set(HtpFlags2::RESPONSE_SPLITTING);
+        }
+        if self.response().response_progress == HtpResponseProgress::TRAILER {
+            return self.process_response_trailer_generic(header);
+        }
         let mut repeated = false;
         let reps = self.response().response_header_repetitions;
         let mut update_reps = false;
+        let mut singular_repeated = false;
         // Do we already have a header with the same name?
         if let Some((_, h_existing)) = self
             .response_mut()
@@ -178,6 +260,10 @@ impl ConnectionParser {
                 return Ok(());
             }
             h_existing.flags.set(HtpFlags::FIELD_REPEATED);
+            h_existing.parser_flags.set(header.parser_flags);
+            singular_repeated = header.name.cmp_nocase("Content-Length") == Ordering::Equal
+                || header.name.cmp_nocase("Transfer-Encoding") == Ordering::Equal
+                || header.name.cmp_nocase("Content-Type") == Ordering::Equal;
             // For simplicity reasons, we count the repetitions of all headers
             // Having multiple C-L headers is against the RFC but many
             // browsers ignore the subsequent headers if the values are the same.
@@ -204,6 +290,17 @@ impl ConnectionParser {
                 .response_headers
                 .add(header.name.clone(), header);
         }
+        if singular_repeated && self.cfg.response_dup_header_policy != HtpDupHeaderPolicy::ALLOW {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::RESPONSE_SINGULAR_HEADER_REPEATED,
+                "Repetition for singular response header"
+            );
+            self.response_mut().flags.set(HtpFlags::FIELD_REPEATED);
+            if self.cfg.response_dup_header_policy == HtpDupHeaderPolicy::ERROR {
+                return Err(HtpStatus::ERROR);
+            }
+        }
         if update_reps {
             self.response_mut().response_header_repetitions =
                 self.response().response_header_repetitions.wrapping_add(1)
@@ -217,4 +314,18 @@ impl ConnectionParser {
         }
         Ok(())
     }
+
+    /// Adds a chunked trailer header to the response trailer table. Repeated trailers
+    /// are combined.
+    fn process_response_trailer_generic(&mut self, header: Header) -> Result<()> {
+        let trailers = &mut self.response_mut().response_trailer_headers;
+        if let Some((_, h_existing)) = trailers.get_nocase_mut(header.name.as_slice()) {
+            h_existing.flags.set(HtpFlags::FIELD_REPEATED);
+            h_existing.value.extend_from_slice(b", ");
+            h_existing.value.extend_from_slice(header.value.as_slice());
+        } else {
+            trailers.add(header.name.clone(), header);
+        }
+        Ok(())
+    }
 }
@@ -107,6 +107,18 @@ impl<T> Table<T> {
             .find(|x| x.0.cmp_nocase_nozero(key.as_ref()) == Ordering::Equal)
     }
 
+    /// Remove the first tuple with a key matching the given slice, ignoring ascii case in self.
+    /// The order of the remaining elements is preserved.
+    ///
+    /// Returns the removed value, or None if no match is found.
+    pub fn remove_nocase<K: AsRef<[u8]>>(&mut self, key: K) -> Option<T> {
+        let index = self
+            .elements
+            .iter()
+            .position(|x| x.0.cmp_nocase(key.as_ref()) == Ordering::Equal)?;
+        Some(self.elements.remove(index).1)
+    }
+
     /// Returns the number of elements in the table
     pub fn size(&self) -> usize {
         self.elements.len()
@@ -238,3 +250,22 @@ fn Table_Misc() {
     assert!(t.get_nocase("z").is_none());
     assert_eq!("1", t.get_nocase("p").unwrap().1);
 }
+
+#[test]
+fn RemoveNocase() {
+    let mut t: Table<&str> = Table::with_capacity(3);
+    t.add(Bstr::from("p"), "1");
+    t.add(Bstr::from("q"), "2");
+    t.add(Bstr::from("r"), "3");
+
+    assert!(t.remove_nocase("z").is_none());
+    assert_eq!(3, t.size());
+    assert_eq!(Some("2"), t.remove_nocase("Q"));
+    assert_eq!(2, t.size());
+    assert!(t.get_nocase("q").is_none());
+    assert_eq!(Ordering::Equal, t[0].0.cmp("p"));
+    assert_eq!(Ordering::Equal, t[1].0.cmp("r"));
+
+    t.get_nocase_mut("R").unwrap().1 = "4";
+    assert_eq!("4", t.get_nocase("r").unwrap().1);
+}
@@ -1,6 +1,6 @@
 use crate::{
     bstr::Bstr,
-    config::{Config, HtpUnwanted},
+    config::{Config, HtpServerPersonality, HtpUnwanted},
     connection_parser::{ConnectionParser, Data as ParserData, HtpStreamState, State},
     decompressors::{Decompressor, HtpContentEncoding},
     error::Result,
@@ -8,19 +8,27 @@ use crate::{
     hook::{DataHook, DataNativeCallbackFn},
     list::List,
     log::Logger,
-    multipart::{find_boundary, HtpMultipartType, Parser as MultipartParser},
+    multipart::{find_boundary, HtpMultipartType, MultipartPart, Parser as MultipartParser},
     parsers::{
-        parse_authorization, parse_content_length, parse_content_type, parse_cookies_v0,
-        parse_hostport,
+        parse_authorization, parse_content_length, parse_content_type, parse_content_type_header,
+        parse_cookies_v0, parse_hostport, parse_matrix_params, protocol_version_numbers,
+        ContentType,
     },
     request::HtpMethod,
     table::Table,
-    uri::Uri,
+    uri::{RawUri, Uri},
     urlencoded::Parser as UrlEncodedParser,
-    util::{validate_hostname, File, FlagOperations, HtpFileSource, HtpFlags},
+    util::{
+        content_type_matches, sniff_content_type, trim_ows, utf8_bestfit_query_inplace,
+        validate_hostname, ContentRange, File, FlagOperations, HtpFileSource, HtpFlags, HtpFlags2,
+    },
     HtpStatus,
 };
 
+/// Export the header parsing flags stored in Header::parser_flags.
+pub use crate::headers::Flags as HeaderParserFlags;
+use bstr::ByteSlice;
+use chrono::{DateTime, Utc};
 use std::{any::Any, cmp::Ordering, mem::take, rc::Rc};
 
 /// A collection of possible data sources.
@@ -60,6 +68,17 @@ impl Param {
     }
 }
 
+/// Represents a single matrix parameter of a path segment, as in "/a;sid=123/b".
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatrixParam {
+    /// The path segment the parameter belongs to, without its parameters.
+    pub segment: Bstr,
+    /// Parameter name.
+    pub name: Bstr,
+    /// Parameter value. Empty if the parameter has no "=".
+    pub value: Bstr,
+}
+
 #[derive(Debug, Clone)]
 /// This structure is used to pass transaction data (for example
 /// request and response body buffers) to callbacks.
@@ -100,6 +119,12 @@ impl<'a> Data<'a> {
         self.data.data()
     }
 
+    /// Return an immutable slice view of the data, without copying it. When there is
+    /// no data, as for the call signalling the end of a body, the slice is empty.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
     /// Determines if this chunk is the last Data in a series.
     pub fn is_last(&self) -> bool {
         self.is_last
@@ -171,11 +196,106 @@ pub struct Header {
     pub value: Bstr,
     /// Parsing flags; a combination of: HTP_FIELD_INVALID, HTP_FIELD_FOLDED, HTP_FIELD_REPEATED.
     pub flags: u64,
+    /// Header name as seen on the wire, when name was converted to canonical form.
+    pub raw_name: Option<Bstr>,
+    /// How the header line was terminated on the wire.
+    pub terminator: HtpHeaderTerminator,
+    /// Header parsing flags of the name and value; a combination of HeaderParserFlags.
+    /// Repeated headers accumulate the flags of every occurrence.
+    pub parser_flags: u64,
+}
+
+/// Enumerates the ways in which a header line can be terminated.
+/// cbindgen:rename-all=QualifiedScreamingSnakeCase
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HtpHeaderTerminator {
+    /// No line terminator, or the header was not parsed from the wire.
+    NONE,
+    /// CRLF, the regular terminator.
+    CRLF,
+    /// A bare LF.
+    LF,
+    /// A bare CR.
+    CR,
+    /// A NUL byte.
+    NULL,
+    /// A deformed sequence of CR and LF characters.
+    DEFORMED,
+    /// Error retrieving the terminator.
+    ERROR,
 }
 
 /// Table of request or response headers.
 pub type Headers = Table<Header>;
 
+/// Iterator over a table of headers, yielding (name, header) pairs in table order.
+pub struct HeadersIter<'a> {
+    inner: std::slice::Iter<'a, (Bstr, Header)>,
+}
+
+impl<'a> Iterator for HeadersIter<'a> {
+    type Item = (&'a Bstr, &'a Header);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(name, header)| (name, header))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for HeadersIter<'_> {}
+
+/// Writes a request or response line followed by the headers, one per line,
+/// and the terminating empty line.
+fn serialize_head(line: Option<&Bstr>, headers: &Headers) -> Vec<u8> {
+    let mut head = Vec::new();
+    if let Some(line) = line {
+        head.extend_from_slice(line.as_slice());
+        head.extend_from_slice(b"\r\n");
+    }
+    for (name, header) in headers {
+        let name = header.raw_name.as_ref().unwrap_or(name);
+        head.extend_from_slice(name.as_slice());
+        head.extend_from_slice(b": ");
+        head.extend_from_slice(header.value.as_slice());
+        head.extend_from_slice(b"\r\n");
+    }
+    head.extend_from_slice(b"\r\n");
+    head
+}
+
+/// Determines the connection disposition from the Connection header tokens, falling
+/// back to the protocol default when the header is absent: close for HTTP/1.0 and
+/// earlier, keep-alive for HTTP/1.1.
+fn connection_disposition(headers: &Headers, protocol: HtpProtocol) -> HtpConnectionDisposition {
+    if let Some((_, header)) = headers.get_nocase_nozero("connection") {
+        let has_token = |token: &[u8]| {
+            header
+                .value
+                .split(|c| *c == b',')
+                .any(|item| trim_ows(item).eq_ignore_ascii_case(token))
+        };
+        if has_token(b"close") {
+            HtpConnectionDisposition::CLOSE
+        } else if has_token(b"upgrade") {
+            HtpConnectionDisposition::UPGRADE
+        } else if has_token(b"keep-alive") {
+            HtpConnectionDisposition::KEEP_ALIVE
+        } else {
+            HtpConnectionDisposition::UNKNOWN
+        }
+    } else {
+        match protocol {
+            HtpProtocol::V0_9 | HtpProtocol::V1_0 => HtpConnectionDisposition::CLOSE,
+            HtpProtocol::V1_1 => HtpConnectionDisposition::KEEP_ALIVE,
+            _ => HtpConnectionDisposition::UNKNOWN,
+        }
+    }
+}
+
 impl Header {
     /// Construct a new header.
     pub fn new(name: Bstr, value: Bstr) -> Self {
@@ -184,7 +304,19 @@ impl Header {
 
     /// Construct a new header with flags.
     pub fn new_with_flags(name: Bstr, value: Bstr, flags: u64) -> Self {
-        Self { name, value, flags }
+        Self {
+            name,
+            value,
+            flags,
+            raw_name: None,
+            terminator: HtpHeaderTerminator::NONE,
+            parser_flags: 0,
+        }
+    }
+
+    /// Returns how the header line was terminated on the wire.
+    pub fn terminator(&self) -> HtpHeaderTerminator {
+        self.terminator
     }
 }
 
@@ -282,6 +414,24 @@ pub enum HtpProtocol {
     V1_1 = 101,
 }
 
+/// Enumerates what the Connection header says about the connection after a message.
+/// cbindgen:rename-all=QualifiedScreamingSnakeCase
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HtpConnectionDisposition {
+    /// Headers not processed yet, or neither the header nor the protocol version
+    /// determine the disposition.
+    UNKNOWN,
+    /// The connection stays open for further transactions.
+    KEEP_ALIVE,
+    /// The connection is closed after this message.
+    CLOSE,
+    /// The connection switches to another protocol.
+    UPGRADE,
+    /// Error retrieving the disposition.
+    ERROR,
+}
+
 /// Represents a single HTTP transaction, which is a combination of a request and a response.
 pub struct Transaction {
     /// The logger structure associated with this transaction
@@ -308,6 +458,9 @@ pub struct Transaction {
     /// URI elements. Can be NULL if the request line contains only a request method (which is
     /// an extreme case of HTTP/0.9, but passes in practice.
     pub request_uri: Option<Bstr>,
+    /// The request target exactly as received on the request line. Unlike request_uri, it
+    /// is never replaced, and it never goes through decoding or normalization.
+    pub request_uri_raw: Option<Bstr>,
     /// Request protocol, as text. Can be NULL if no protocol was specified.
     pub request_protocol: Option<Bstr>,
     /// Protocol version as a number. Multiply the high version number by 100, then add the low
@@ -362,6 +515,9 @@ pub struct Transaction {
     /// has been seen over TCP; request_entity_len contains length after
     /// de-chunking and decompression.
     pub request_entity_len: i64,
+    /// The number of raw bytes of the request stream attributed to this transaction: the
+    /// request line, headers, body and trailers as seen on the wire.
+    pub request_total_size: i64,
     /// Parsed request headers.
     pub request_headers: Headers,
     /// Request transfer coding. Can be one of UNKNOWN (body presence not
@@ -382,6 +538,11 @@ pub struct Transaction {
     /// is available in request headers. The contents of the field will be converted
     /// to lowercase and any parameters (e.g., character set information) removed.
     pub request_content_type: Option<Bstr>,
+    /// The parsed request Content-Type header, including its parameters.
+    pub parsed_request_content_type: Option<ContentType>,
+    /// The request body content type guessed from the first body bytes, when
+    /// request body sniffing is enabled in configuration.
+    pub request_content_type_sniffed: Option<Bstr>,
     /// Request decompressor used to decompress request body data.
     pub request_decompressor: Option<Decompressor>,
     /// Contains the value specified in the Content-Length header. The value of this
@@ -405,6 +566,9 @@ pub struct Transaction {
     pub request_params: Table<Param>,
     /// Request cookies
     pub request_cookies: Table<Bstr>,
+    /// Matrix parameters found in the raw request path, in the order they were seen. Only
+    /// populated when matrix parameter parsing is enabled.
+    pub request_matrix_params: Vec<MatrixParam>,
     /// Authentication type used in the request.
     pub request_auth_type: HtpAuthType,
     /// Authentication username.
@@ -445,10 +609,23 @@ pub struct Transaction {
     pub response_message: Option<Bstr>,
     /// Have we seen the server respond with a 100 response?
     pub seen_100continue: bool,
+    /// Did the client send "Expect: 100-continue"?
+    pub request_expects_100_continue: bool,
+    /// The connection disposition requested by the client.
+    pub request_connection: HtpConnectionDisposition,
+    /// The connection disposition announced by the server.
+    pub response_connection: HtpConnectionDisposition,
     /// Parsed response headers. Contains instances of Header.
     pub response_headers: Headers,
+    /// Parsed response trailer headers, seen after the last chunk of a chunked body. They
+    /// are kept apart from response_headers.
+    pub response_trailer_headers: Headers,
     /// Is this a response a HTTP/2.0 upgrade?
     pub is_http_2_upgrade: bool,
+    /// Is this request an HTTP/2 cleartext (h2c) upgrade attempt? Set when the request has
+    /// "Upgrade: h2c", a Connection header listing both Upgrade and HTTP2-Settings, and an
+    /// HTTP2-Settings header.
+    pub is_h2c_upgrade_request: bool,
 
     /// HTTP 1.1 RFC
     ///
@@ -479,11 +656,29 @@ pub struct Transaction {
     /// has been seen over TCP; response_entity_len contains length after
     /// de-chunking and decompression.
     pub response_entity_len: i64,
+    /// The number of raw bytes of the response stream attributed to this transaction: the
+    /// status line, headers, body and trailers as seen on the wire.
+    pub response_total_size: i64,
+    /// Whether the remaining response body is skipped: it is still consumed and counted in
+    /// response_message_len, but is not decompressed or passed to body data callbacks.
+    pub response_body_skipped: bool,
+    /// The number of compressed response body bytes passed to the decompressor.
+    pub response_compressed_len: u64,
+    /// The number of response body bytes produced by the decompressor.
+    pub response_decompressed_len: u64,
+    /// The number of non-empty chunks seen in a chunked response body.
+    pub response_chunk_count: u32,
+    /// The raw response body bytes as seen on the wire, before dechunking and
+    /// decompression. Only stored when enabled in configuration, and limited
+    /// to Config::response_body_raw_limit bytes.
+    pub response_body_raw: Option<Bstr>,
     /// Contains the value specified in the Content-Length header. The value of this
     /// field will be -1 from the beginning of the transaction and until response
     /// headers are processed. It will stay -1 if the C-L header was not provided,
     /// or if the value in it cannot be parsed.
     pub response_content_length: i64,
+    /// The range carried by the Content-Range response header, if present and valid.
+    pub response_content_range: Option<ContentRange>,
     /// Response transfer coding, which indicates if there is a response body,
     /// and how it is transported (e.g., as-is, or chunked).
     pub response_transfer_coding: HtpTransferCoding,
@@ -497,10 +692,15 @@ pub struct Transaction {
     /// this field to NONE in a RESPONSE_HEADERS callback will prevent
     /// decompression.
     pub response_content_encoding_processing: HtpContentEncoding,
+    /// The encodings decoded from the response body, in the order they are listed in
+    /// the Content-Encoding header(s). The body is decoded starting with the last one.
+    pub response_content_encodings: Vec<HtpContentEncoding>,
     /// This field will contain the response content type when that information
     /// is available in response headers. The contents of the field will be converted
     /// to lowercase and any parameters (e.g., character set information) removed.
     pub response_content_type: Option<Bstr>,
+    /// The parsed response Content-Type header, including its parameters.
+    pub parsed_response_content_type: Option<ContentType>,
     /// Response decompressor used to decompress response body data.
     pub response_decompressor: Option<Decompressor>,
 
@@ -508,6 +708,8 @@ pub struct Transaction {
     /// Parsing flags; a combination of: HTP_REQUEST_INVALID_T_E, HTP_INVALID_FOLDING,
     /// HTP_REQUEST_SMUGGLING, HTP_MULTI_PACKET_HEAD, and HTP_FIELD_UNPARSEABLE.
     pub flags: u64,
+    /// More parsing flags; a combination of HtpFlags2 bits.
+    pub flags2: u64,
     /// Request progress.
     pub request_progress: HtpRequestProgress,
     /// Response progress.
@@ -522,14 +724,91 @@ pub struct Transaction {
     pub request_header_parser: HeaderParser,
     /// Response header parser
     pub response_header_parser: HeaderParser,
+    /// Position of the request completion among all completions on the connection.
+    request_completion_seq: Option<u64>,
+    /// Position of the response completion among all completions on the connection.
+    response_completion_seq: Option<u64>,
+    /// Timestamp of the data chunk in which the request line was parsed.
+    request_line_ts: Option<DateTime<Utc>>,
+    /// Timestamp of the data chunk in which the request was completed.
+    request_complete_ts: Option<DateTime<Utc>>,
+    /// Timestamp of the data chunk in which the response line was parsed.
+    response_line_ts: Option<DateTime<Utc>>,
+    /// Timestamp of the data chunk in which the response was completed.
+    response_complete_ts: Option<DateTime<Utc>>,
+    /// Bytes accumulated in the request and response lines, headers, parameters and
+    /// cookies, updated whenever a set of headers has been processed.
+    accumulated_fields_len: usize,
 }
 
 /// Type alias for list of transactions.
 pub type Transactions = List<Transaction>;
 
 impl Transaction {
+    /// Returns the number of bytes this transaction has accumulated so far: the
+    /// request line and URI, request and response headers, parameters, cookies and
+    /// the request and response bodies as seen on the wire.
+    pub fn accumulated_bytes(&self) -> usize {
+        self.accumulated_fields_len
+            + self.request_message_len.max(0) as usize
+            + self.response_message_len.max(0) as usize
+    }
+
+    /// Recounts the bytes accumulated outside of the bodies. Called once the request
+    /// or response headers are processed, so that body data only adds to the total.
+    fn update_accumulated_fields(&mut self) {
+        let headers_len = |headers: &Headers| {
+            headers
+                .into_iter()
+                .map(|(_, header)| header.name.len() + header.value.len())
+                .sum::<usize>()
+        };
+        let mut len = self.request_line.as_ref().map_or(0, |line| line.len())
+            + self.request_uri.as_ref().map_or(0, |uri| uri.len())
+            + self.response_line.as_ref().map_or(0, |line| line.len())
+            + headers_len(&self.request_headers)
+            + headers_len(&self.response_headers);
+        for (_, param) in &self.request_params {
+            len += param.name.len() + param.value.len();
+        }
+        for (name, value) in &self.request_cookies {
+            len += name.len() + value.len();
+        }
+        self.accumulated_fields_len = len;
+    }
+
+    /// Checks the accumulated size of the transaction against the configured
+    /// tx_bytes_limit, raising the TX_BYTES_LIMIT flag when it is exceeded.
+    ///
+    /// Returns true if further processing of this transaction should stop.
+    fn check_bytes_limit(&mut self) -> bool {
+        if let Some(limit) = self.cfg.tx_bytes_limit {
+            if self.accumulated_bytes() > limit {
+                if !self.flags.is_set(HtpFlags::TX_BYTES_LIMIT) {
+                    htp_warn!(
+                        self.logger,
+                        HtpLogCode::TX_BYTES_LIMIT_REACHED,
+                        format!("Transaction exceeded the byte limit of {}", limit)
+                    );
+                    self.flags.set(HtpFlags::TX_BYTES_LIMIT);
+                }
+                return self.cfg.tx_bytes_limit_stop;
+            }
+        }
+        false
+    }
+
     /// Construct a new transaction.
     pub fn new(cfg: &Rc<Config>, logger: &Logger, index: usize) -> Self {
+        let mut request_header_parser = HeaderParser::new(Side::Request);
+        request_header_parser.set_bare_cr_eol(cfg.request_bare_cr_eol);
+        request_header_parser.set_folding_limit(cfg.header_folding_limit);
+        request_header_parser.set_strict(cfg.strict_header_names);
+        request_header_parser.set_header_limit(cfg.number_headers_limit);
+        let mut response_header_parser = HeaderParser::new(Side::Response);
+        response_header_parser.set_folding_limit(cfg.header_folding_limit);
+        response_header_parser.set_strict(cfg.strict_header_names);
+        response_header_parser.set_header_limit(cfg.number_headers_limit);
         Self {
             logger: logger.clone(),
             cfg: Rc::clone(&cfg),
@@ -540,6 +819,7 @@ impl Transaction {
             request_method: None,
             request_method_number: HtpMethod::UNKNOWN,
             request_uri: None,
+            request_uri_raw: None,
             request_protocol: None,
             request_protocol_number: HtpProtocol::UNKNOWN,
             is_protocol_0_9: false,
@@ -549,11 +829,14 @@ impl Transaction {
             partial_normalized_uri: None,
             request_message_len: 0,
             request_entity_len: 0,
+            request_total_size: 0,
             request_headers: Table::with_capacity(32),
             request_transfer_coding: HtpTransferCoding::UNKNOWN,
             request_content_encoding: HtpContentEncoding::NONE,
             request_content_encoding_processing: HtpContentEncoding::NONE,
             request_content_type: None,
+            parsed_request_content_type: None,
+            request_content_type_sniffed: None,
             request_content_length: -1,
             request_decompressor: None,
             hook_request_body_data: DataHook::default(),
@@ -562,6 +845,7 @@ impl Transaction {
             request_mpartp: None,
             request_params: Table::with_capacity(32),
             request_cookies: Table::with_capacity(32),
+            request_matrix_params: Vec::new(),
             request_auth_type: HtpAuthType::UNKNOWN,
             request_auth_username: None,
             request_auth_password: None,
@@ -577,24 +861,46 @@ impl Transaction {
             response_status_expected_number: HtpUnwanted::IGNORE,
             response_message: None,
             seen_100continue: false,
+            request_expects_100_continue: false,
+            request_connection: HtpConnectionDisposition::UNKNOWN,
+            response_connection: HtpConnectionDisposition::UNKNOWN,
             response_headers: Table::with_capacity(32),
+            response_trailer_headers: Table::with_capacity(4),
             is_http_2_upgrade: false,
+            is_h2c_upgrade_request: false,
             response_message_len: 0,
             response_entity_len: 0,
+            response_total_size: 0,
+            response_body_skipped: false,
+            response_compressed_len: 0,
+            response_decompressed_len: 0,
+            response_chunk_count: 0,
+            response_body_raw: None,
             response_content_length: -1,
+            response_content_range: None,
             response_transfer_coding: HtpTransferCoding::UNKNOWN,
             response_content_encoding: HtpContentEncoding::NONE,
             response_content_encoding_processing: HtpContentEncoding::NONE,
+            response_content_encodings: Vec::new(),
             response_content_type: None,
+            parsed_response_content_type: None,
             response_decompressor: None,
             flags: 0,
+            flags2: 0,
             request_progress: HtpRequestProgress::NOT_STARTED,
             response_progress: HtpResponseProgress::NOT_STARTED,
             index,
             request_header_repetitions: 0,
             response_header_repetitions: 0,
-            request_header_parser: HeaderParser::new(Side::Request),
-            response_header_parser: HeaderParser::new(Side::Response),
+            request_header_parser,
+            response_header_parser,
+            request_completion_seq: None,
+            response_completion_seq: None,
+            request_line_ts: None,
+            request_complete_ts: None,
+            response_line_ts: None,
+            response_complete_ts: None,
+            accumulated_fields_len: 0,
         }
     }
 
@@ -638,25 +944,395 @@ impl Transaction {
         Ok(())
     }
 
+    /// Parses the query string of the parsed request URI with the urlencoded parser, and
+    /// stores the parameters in request_params with the QUERY_STRING source. Decoding follows
+    /// the decoder configuration, such as plusspace_decode and the handling of invalid
+    /// encodings. This runs on its own when Config::parse_urlencoded is set; calling it again
+    /// replaces the query string parameters instead of adding them twice.
+    pub fn parse_query_string(&mut self) -> Result<()> {
+        self.request_params
+            .elements
+            .retain(|(_, param)| param.source != HtpDataSource::QUERY_STRING);
+        if let Some(mut query) = self
+            .parsed_uri
+            .as_ref()
+            .and_then(|parsed_uri| parsed_uri.query.clone())
+        {
+            // The fragment is kept apart from the query unless configured otherwise. Like the
+            // query, it is taken raw, as the urlencoded parser does the decoding.
+            if self.cfg.query_fragment_included {
+                if let Some(fragment) = self
+                    .parsed_uri_raw
+                    .as_ref()
+                    .and_then(|parsed_uri| parsed_uri.fragment.as_ref())
+                {
+                    query.add("#");
+                    query.add(fragment.as_slice());
+                }
+            }
+            // We have a non-zero length query string.
+            let mut urlenp = UrlEncodedParser::new(self.cfg.decoder_cfg);
+            urlenp.parse_complete(query.as_slice());
+
+            // Add all parameters to the transaction.
+            for (name, value) in urlenp.params.elements.iter() {
+                let mut name = Bstr::from(name.as_slice());
+                let mut value = Bstr::from(value.as_slice());
+                if self.cfg.decoder_cfg.utf8_convert_bestfit_query {
+                    let decoder_cfg = &self.cfg.decoder_cfg;
+                    let name_changed = utf8_bestfit_query_inplace(decoder_cfg, &mut name);
+                    let value_changed = utf8_bestfit_query_inplace(decoder_cfg, &mut value);
+                    if name_changed || value_changed {
+                        self.flags.set(HtpFlags::QUERY_UTF8_BESTFIT);
+                    }
+                }
+                let param = Param::new(name, value, HtpDataSource::QUERY_STRING);
+                self.request_add_param(param)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of request headers. Repeated headers are counted once.
+    pub fn request_header_count(&self) -> usize {
+        self.request_headers.size()
+    }
+
+    /// Returns the number of response headers. Repeated headers are counted once.
+    pub fn response_header_count(&self) -> usize {
+        self.response_headers.size()
+    }
+
+    /// Returns the request cookies, in the order they were seen. The table is only
+    /// populated when cookie parsing is enabled, and may contain repeated names.
+    pub fn request_cookies(&self) -> &Table<Bstr> {
+        &self.request_cookies
+    }
+
+    /// Returns the response trailer headers that followed the last chunk of a chunked body.
+    pub fn response_trailers(&self) -> &Headers {
+        &self.response_trailer_headers
+    }
+
+    /// Returns true if a response header matches a line that followed a CR or LF in a
+    /// request parameter or header value, as in a reflected "a%0d%0aSet-Cookie: x=1".
+    fn response_header_reflected(&self) -> bool {
+        let request_values = self
+            .request_params
+            .elements
+            .iter()
+            .map(|(_, param)| &param.value)
+            .chain(
+                self.request_headers
+                    .elements
+                    .iter()
+                    .map(|(_, header)| &header.value),
+            );
+        for value in request_values {
+            for line in value.split(|c| *c == b'\r' || *c == b'\n').skip(1) {
+                let mut parts = line.splitn(2, |c| *c == b':');
+                let (name, injected) = match (parts.next(), parts.next()) {
+                    (Some(name), Some(injected)) => (trim_ows(name), trim_ows(injected)),
+                    _ => continue,
+                };
+                if name.is_empty() || injected.is_empty() {
+                    continue;
+                }
+                if let Some((_, header)) = self.response_headers.get_nocase(name) {
+                    if header.value.index_of(injected).is_some() {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the base64url HTTP2-Settings value of an h2c upgrade request, for handing
+    /// off to an HTTP/2 parser, or None if the request is not an h2c upgrade.
+    pub fn h2c_settings(&self) -> Option<&Bstr> {
+        if !self.is_h2c_upgrade_request {
+            return None;
+        }
+        self.request_headers
+            .get_nocase_nozero("http2-settings")
+            .map(|(_, header)| &header.value)
+    }
+
+    /// Returns the header parsing flags (HeaderParserFlags) of the named request header,
+    /// for example FOLDING for a folded header, or None if there is no such header.
+    pub fn request_header_flags(&self, name: &str) -> Option<u64> {
+        self.request_headers
+            .get_nocase_nozero(name)
+            .map(|(_, header)| header.parser_flags)
+    }
+
+    /// Returns true if the named request header lists the token, ignoring case.
+    fn request_header_has_token(&self, name: &str, token: &str) -> bool {
+        self.request_headers
+            .get_nocase_nozero(name)
+            .map(|(_, header)| {
+                header
+                    .value
+                    .split(|c| *c == b',')
+                    .any(|item| trim_ows(item).eq_ignore_ascii_case(token.as_bytes()))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Returns the connection disposition requested by the client, as given by the request
+    /// Connection header or defaulted from the request protocol version.
+    pub fn request_connection(&self) -> HtpConnectionDisposition {
+        self.request_connection
+    }
+
+    /// Returns the connection disposition announced by the server, as given by the response
+    /// Connection header or defaulted from the response protocol version.
+    pub fn response_connection(&self) -> HtpConnectionDisposition {
+        self.response_connection
+    }
+
+    /// Returns the request target exactly as received on the request line.
+    pub fn request_uri_raw(&self) -> Option<&Bstr> {
+        self.request_uri_raw.as_ref()
+    }
+
+    /// Returns true if dot-segment removal resolved a ".." segment in the request path.
+    pub fn request_path_traversal(&self) -> bool {
+        self.flags2.is_set(HtpFlags2::REQUEST_PATH_TRAVERSAL)
+    }
+
+    /// Returns true if the request URI path is longer than the configured limit.
+    pub fn request_path_too_long(&self) -> bool {
+        self.flags2.is_set(HtpFlags2::REQUEST_PATH_TOO_LONG)
+    }
+
+    /// Returns true if a response header value contains a raw CR or LF.
+    pub fn response_splitting(&self) -> bool {
+        self.flags2.is_set(HtpFlags2::RESPONSE_SPLITTING)
+    }
+
+    /// Returns true if the request body was longer than its declared Content-Length.
+    pub fn request_body_overflow(&self) -> bool {
+        self.flags2.is_set(HtpFlags2::REQUEST_BODY_OVERFLOW)
+    }
+
+    /// Returns the form-data parts of a multipart request body, in the order in which they
+    /// appeared. Empty if the body was not parsed as multipart.
+    pub fn multipart_parts(&self) -> Vec<MultipartPart> {
+        self.request_mpartp
+            .as_ref()
+            .map(|mpartp| {
+                mpartp
+                    .multipart
+                    .parts
+                    .iter()
+                    .filter(|part| {
+                        part.type_0 == HtpMultipartType::TEXT
+                            || part.type_0 == HtpMultipartType::FILE
+                    })
+                    .map(MultipartPart::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the response has a Date header.
+    pub fn response_has_date(&self) -> bool {
+        self.response_headers.get_nocase_nozero("date").is_some()
+    }
+
+    /// Returns an iterator over the request headers.
+    pub fn request_headers_iter(&self) -> HeadersIter {
+        HeadersIter {
+            inner: self.request_headers.elements.iter(),
+        }
+    }
+
+    /// Returns an iterator over the response headers.
+    pub fn response_headers_iter(&self) -> HeadersIter {
+        HeadersIter {
+            inner: self.response_headers.elements.iter(),
+        }
+    }
+
+    /// Reconstructs the request line and headers for forwarding or logging.
+    ///
+    /// Headers keep their original casing and the order in which they were first seen, and
+    /// each one is emitted on a single line. Repeated headers appear once, with the combined
+    /// value stored in the table.
+    pub fn serialize_request_head(&self) -> Vec<u8> {
+        serialize_head(self.request_line.as_ref(), &self.request_headers)
+    }
+
+    /// Reconstructs the status line and headers for forwarding or logging.
+    ///
+    /// Works like serialize_request_head. Header names that were canonicalized are
+    /// emitted as seen on the wire.
+    pub fn serialize_response_head(&self) -> Vec<u8> {
+        serialize_head(self.response_line.as_ref(), &self.response_headers)
+    }
+
+    /// Returns the sequence number of the request completion, counting both request and
+    /// response completions on the connection starting from 1, or None if the request is
+    /// not complete yet.
+    pub fn request_completion_seq(&self) -> Option<u64> {
+        self.request_completion_seq
+    }
+
+    /// Returns the sequence number of the response completion, counting both request and
+    /// response completions on the connection starting from 1, or None if the response is
+    /// not complete yet. A response completing before the response of an earlier
+    /// transaction indicates a parsing desync.
+    pub fn response_completion_seq(&self) -> Option<u64> {
+        self.response_completion_seq
+    }
+
+    /// Returns the timestamp of the request data chunk in which the request line was
+    /// parsed, or None if the request line was not parsed yet.
+    pub fn request_line_ts(&self) -> Option<DateTime<Utc>> {
+        self.request_line_ts
+    }
+
+    /// Returns the timestamp of the request data chunk in which the request was
+    /// completed, or None if the request is not complete yet.
+    pub fn request_complete_ts(&self) -> Option<DateTime<Utc>> {
+        self.request_complete_ts
+    }
+
+    /// Returns the timestamp of the response data chunk in which the response line was
+    /// parsed, or None if the response line was not parsed yet. Together with
+    /// request_complete_ts, this gives the time to first byte of the response.
+    pub fn response_line_ts(&self) -> Option<DateTime<Utc>> {
+        self.response_line_ts
+    }
+
+    /// Returns the timestamp of the response data chunk in which the response was
+    /// completed, or None if the response is not complete yet.
+    pub fn response_complete_ts(&self) -> Option<DateTime<Utc>> {
+        self.response_complete_ts
+    }
+
+    /// Returns the raw form of the request URI components, as supplied on the request line,
+    /// or None if the request line was not parsed yet.
+    pub fn raw_uri(&self) -> Option<RawUri> {
+        self.parsed_uri_raw.as_ref().map(RawUri::new)
+    }
+
+    /// Returns the decoded and normalized form of the request URI components,
+    /// or None if the request line was not parsed yet.
+    pub fn decoded_uri(&self) -> Option<&Uri> {
+        self.parsed_uri.as_ref()
+    }
+
+    /// Returns true if the request or the response has both a chunked Transfer-Encoding
+    /// and a Content-Length header.
+    pub fn has_te_cl_conflict(&self) -> bool {
+        self.flags
+            .is_set(HtpFlags::REQUEST_SMUGGLING_TE_CL | HtpFlags::RESPONSE_SMUGGLING_TE_CL)
+    }
+
+    /// Returns how much the response body expanded when decompressed, as the number of
+    /// decompressed bytes per compressed byte.
+    ///
+    /// Returns None if the response body was not decompressed.
+    pub fn decompression_ratio(&self) -> Option<f64> {
+        if self.response_compressed_len == 0 {
+            return None;
+        }
+        Some(self.response_decompressed_len as f64 / self.response_compressed_len as f64)
+    }
+
+    /// Returns the major and minor version numbers of the request protocol, as given on the
+    /// request line. Unlike request_protocol_number, any version is reported, such as 2.0.
+    ///
+    /// Returns None if there is no protocol, or if it is invalid or a number is above 99.
+    pub fn request_protocol_version(&self) -> Option<(u8, u8)> {
+        self.request_protocol
+            .as_ref()
+            .and_then(|protocol| protocol_version_numbers(protocol))
+            .filter(|(major, minor)| *major <= 99 && *minor <= 99)
+            .map(|(major, minor)| (major as u8, minor as u8))
+    }
+
+    /// Returns the major and minor version numbers of the response protocol, as given on the
+    /// response line. Unlike response_protocol_number, any version is reported, such as 2.0.
+    ///
+    /// Returns None if there is no protocol, or if it is invalid or a number is above 99.
+    pub fn response_protocol_version(&self) -> Option<(u8, u8)> {
+        self.response_protocol
+            .as_ref()
+            .and_then(|protocol| protocol_version_numbers(protocol))
+            .filter(|(major, minor)| *major <= 99 && *minor <= 99)
+            .map(|(major, minor)| (major as u8, minor as u8))
+    }
+
+    /// Returns the request and response transfer codings, in that order.
+    pub fn transfer_codings(&self) -> (HtpTransferCoding, HtpTransferCoding) {
+        (self.request_transfer_coding, self.response_transfer_coding)
+    }
+
     /// Determine if the request has a body.
     pub fn request_has_body(&self) -> bool {
         self.request_transfer_coding == HtpTransferCoding::IDENTITY
             || self.request_transfer_coding == HtpTransferCoding::CHUNKED
     }
 
+    /// Checks the tokens of a Transfer-Encoding header value against the configured
+    /// allowlist, raising the TRANSFER_ENCODING_DISALLOWED flag if any token is not allowed.
+    ///
+    /// Returns an error if a token is not allowed and the configuration treats this as an error.
+    pub fn check_transfer_encoding(&mut self, te: &[u8]) -> Result<()> {
+        if self.cfg.transfer_encoding_allowlist.is_empty() {
+            return Ok(());
+        }
+        let allowlist = &self.cfg.transfer_encoding_allowlist;
+        let disallowed = te
+            .split(|c| *c == b',')
+            .map(|token| token.trim())
+            .filter(|token| !token.is_empty())
+            .any(|token| {
+                !allowlist
+                    .iter()
+                    .any(|allowed| allowed.as_bytes().eq_ignore_ascii_case(token))
+            });
+        if disallowed {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::TRANSFER_ENCODING_DISALLOWED,
+                "Transfer-Encoding contains a token that is not allowed"
+            );
+            self.flags.set(HtpFlags::TRANSFER_ENCODING_DISALLOWED);
+            if self.cfg.transfer_encoding_disallowed_error {
+                return Err(HtpStatus::ERROR);
+            }
+        }
+        Ok(())
+    }
+
     /// Determine if we have a request body, and how it is packaged.
     pub fn process_request_headers(&mut self, connp: &mut ConnectionParser) -> Result<()> {
+        if let Some((_, te)) = self.request_headers.get_nocase_nozero("transfer-encoding") {
+            let te = te.value.clone();
+            self.check_transfer_encoding(&te)?;
+        }
         // Determine if we have a request body, and how it is packaged.
         let cl_opt = self.request_headers.get_nocase_nozero("content-length");
         // Check for the Transfer-Encoding header, which would indicate a chunked request body.
         if let Some((_, te)) = self.request_headers.get_nocase_nozero("transfer-encoding") {
-            // Make sure it contains "chunked" only.
+            // Make sure "chunked" is the final coding, as in "gzip, chunked".
             // TODO The HTTP/1.1 RFC also allows the T-E header to contain "identity", which
             //      presumably should have the same effect as T-E header absence. However, Apache
             //      (2.2.22 on Ubuntu 12.04 LTS) instead errors out with "Unknown Transfer-Encoding: identity".
             //      And it behaves strangely, too, sending a 501 and proceeding to process the request
             //      (e.g., PHP is run), but without the body. It then closes the connection.
-            if te.value.cmp_nocase("chunked") != Ordering::Equal {
+            let chunked = te
+                .value
+                .split_on_trimmed(b',')
+                .filter(|coding| !coding.is_empty())
+                .last()
+                .map_or(false, |coding| coding.eq_ignore_ascii_case(b"chunked"));
+            if !chunked {
                 // Invalid T-E header value.
                 self.request_transfer_coding = HtpTransferCoding::INVALID;
                 self.flags.set(HtpFlags::REQUEST_INVALID_T_E);
@@ -684,7 +1360,8 @@ impl Transaction {
                     //  Transfer-Encoding header field and a Content-Length header field,
                     //  the latter MUST be ignored."
                     //
-                    self.flags.set(HtpFlags::REQUEST_SMUGGLING)
+                    self.flags.set(HtpFlags::REQUEST_SMUGGLING);
+                    self.flags.set(HtpFlags::REQUEST_SMUGGLING_TE_CL)
                 }
             }
         } else if let Some((_, cl)) = cl_opt {
@@ -786,6 +1463,7 @@ impl Transaction {
         // Determine Content-Type.
         if let Some((_, ct)) = self.request_headers.get_nocase_nozero("content-type") {
             self.request_content_type = Some(parse_content_type(ct.value.as_slice())?);
+            self.parsed_request_content_type = parse_content_type_header(ct.value.as_slice()).ok();
             let mut flags = 0;
             // Check the request content type for urlencoded or see if it matches our MIME type
             if self.cfg.parse_urlencoded
@@ -794,7 +1472,16 @@ impl Transaction {
                 // Create parser instance.
                 self.request_urlenp_body = Some(UrlEncodedParser::new(self.cfg.decoder_cfg));
             } else if self.cfg.parse_multipart {
-                if let Some(boundary) = find_boundary(ct.value.as_slice(), &mut flags) {
+                // find_boundary also flags unusual or evasive boundary syntax, and locates
+                // a boundary where the parameter list is malformed.
+                let found = find_boundary(ct.value.as_slice(), &mut flags);
+                let boundary = self
+                    .parsed_request_content_type
+                    .as_ref()
+                    .and_then(|ct| ct.boundary.as_ref())
+                    .map(|boundary| boundary.as_slice())
+                    .or(found);
+                if let Some(boundary) = boundary {
                     if !boundary.is_empty() {
                         // Create a Multipart parser instance.
                         self.request_mpartp =
@@ -819,6 +1506,17 @@ impl Transaction {
                 }
             })?;
         }
+        self.request_expects_100_continue = self.request_header_has_token("expect", "100-continue");
+        self.request_connection =
+            connection_disposition(&self.request_headers, self.request_protocol_number);
+        // Detect an HTTP/2 cleartext upgrade attempt.
+        self.is_h2c_upgrade_request = self.request_header_has_token("upgrade", "h2c")
+            && self.request_header_has_token("connection", "upgrade")
+            && self.request_header_has_token("connection", "http2-settings")
+            && self
+                .request_headers
+                .get_nocase_nozero("http2-settings")
+                .is_some();
 
         let ce = (*self)
             .request_headers
@@ -874,7 +1572,8 @@ impl Transaction {
             HtpContentEncoding::GZIP
             | HtpContentEncoding::DEFLATE
             | HtpContentEncoding::ZLIB
-            | HtpContentEncoding::LZMA => {
+            | HtpContentEncoding::LZMA
+            | HtpContentEncoding::BROTLI => {
                 self.request_prepend_decompressor(connp, self.request_content_encoding_processing)?;
             }
             HtpContentEncoding::NONE => {
@@ -969,6 +1668,34 @@ impl Transaction {
         Ok(())
     }
 
+    /// Guesses the request body content type from the first chunk of (decompressed) body
+    /// data, if enabled in configuration, and flags a mismatch with the declared type.
+    fn request_sniff_body(&mut self, data: Option<&[u8]>) {
+        if !self.cfg.request_body_sniffing || self.request_content_type_sniffed.is_some() {
+            return;
+        }
+        let data = if let Some(data) = data.filter(|data| !data.is_empty()) {
+            data
+        } else {
+            return;
+        };
+        let sniffed = sniff_content_type(data);
+        if let Some(declared) = &self.request_content_type {
+            if !content_type_matches(declared.as_slice(), sniffed.as_bytes()) {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::REQUEST_CONTENT_TYPE_MISMATCH,
+                    format!(
+                        "Request body looks like {}, not the declared Content-Type",
+                        sniffed
+                    )
+                );
+                self.flags.set(HtpFlags::REQUEST_CONTENT_TYPE_MISMATCH);
+            }
+        }
+        self.request_content_type_sniffed = Some(Bstr::from(sniffed));
+    }
+
     /// Process the provided data as Urlencoded Data
     ///
     /// Returns HtpStatus::DECLINED if the provided data is not urlencoded (i.e. no urlencoded parser was ever created)
@@ -1050,11 +1777,32 @@ impl Transaction {
         // Keep track of body size before decompression.
         self.request_message_len =
             (self.request_message_len as u64).wrapping_add(data.unwrap_or(b"").len() as u64) as i64;
+        if self.check_bytes_limit() {
+            // The data is dropped, but the end of the body is still signalled.
+            if data.is_none() {
+                self.request_decompressor = None;
+                let data = ParserData::from(data);
+                let mut data = Data::new(self, &data, false);
+                connp.request_run_hook_body_data(&mut data)?;
+            }
+            return Ok(());
+        }
+        // Send the data to the raw body callbacks before any decompression.
+        // Do not invoke callbacks with an empty data chunk.
+        if data.map_or(true, |data| !data.is_empty()) {
+            let raw_data = ParserData::from(data);
+            let mut raw_tx_data = Data::new(self, &raw_data, false);
+            connp
+                .cfg
+                .hook_request_body_data_raw
+                .run_all(connp, &mut raw_tx_data)?;
+        }
         match self.request_content_encoding_processing {
             HtpContentEncoding::GZIP
             | HtpContentEncoding::DEFLATE
             | HtpContentEncoding::ZLIB
-            | HtpContentEncoding::LZMA => {
+            | HtpContentEncoding::LZMA
+            | HtpContentEncoding::BROTLI => {
                 // Send data buffer to the decompressor if it exists
                 if self.request_decompressor.is_none() && data.is_none() {
                     return Ok(());
@@ -1064,6 +1812,9 @@ impl Transaction {
                     decompressor
                         .decompress(data)
                         .map_err(|_| HtpStatus::ERROR)?;
+                    if decompressor.raw_deflate() {
+                        self.flags2.set(HtpFlags2::DEFLATE_RAW);
+                    }
                     if decompressor.time_spent()
                         > self.cfg.compression_options.get_time_limit() as u64
                     {
@@ -1094,6 +1845,7 @@ impl Transaction {
                 self.request_entity_len = (self.request_entity_len as u64)
                     .wrapping_add(data.unwrap_or(b"").len() as u64)
                     as i64;
+                self.request_sniff_body(data);
                 let _ = self.request_process_multipart_data(data);
                 let _ = self.request_process_urlencoded_data(data);
                 // Send data to the callbacks.
@@ -1122,6 +1874,7 @@ impl Transaction {
 
     /// Change transaction state to RESPONSE_LINE and invoke registered callbacks.
     pub fn state_response_line(&mut self, connp: &mut ConnectionParser) -> Result<()> {
+        self.response_line_ts = Some(connp.response_timestamp);
         // Is the response line valid?
         if self.response_protocol_number == HtpProtocol::INVALID {
             htp_warn!(
@@ -1140,6 +1893,25 @@ impl Transaction {
             self.response_status_number = HtpResponseNumber::INVALID;
             self.flags.set(HtpFlags::STATUS_LINE_INVALID)
         }
+        // The status code must be exactly three digits.
+        if self.response_status.as_ref().map_or(false, |status| {
+            status.len() != 3 || !status.as_slice().iter().all(u8::is_ascii_digit)
+        }) {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::RESPONSE_STATUS_FORMAT_INVALID,
+                "Invalid response line: status code is not three digits"
+            );
+            self.flags.set(HtpFlags::STATUS_LINE_INVALID);
+            if connp.cfg.response_status_format_reject {
+                return Err(HtpStatus::ERROR);
+            }
+        }
+        if let HtpResponseNumber::VALID(status) = self.response_status_number {
+            if connp.cfg.interesting_status_codes.contains(&status) {
+                self.flags.set(HtpFlags::STATUS_INTERESTING)
+            }
+        }
         // Run hook HTP_RESPONSE_LINE
         connp.cfg.hook_response_line.run_all(connp, self)
     }
@@ -1165,21 +1937,47 @@ impl Transaction {
         self.response_message_len = (self.response_message_len as u64)
             .wrapping_add(data.unwrap_or(b"").len() as u64)
             as i64;
+        if self.check_bytes_limit() {
+            // The data is dropped, but the end of the body is still signalled.
+            if data.is_none() {
+                self.response_decompressor = None;
+                let data = ParserData::from(data);
+                let mut data = Data::new(self, &data, false);
+                connp.response_run_hook_body_data(&mut data)?;
+            }
+            return Ok(());
+        }
+        // Send the data to the raw body callbacks before any decompression.
+        // Do not invoke callbacks with an empty data chunk.
+        if data.map_or(true, |data| !data.is_empty()) {
+            let raw_data = ParserData::from(data);
+            let mut raw_tx_data = Data::new(self, &raw_data, false);
+            connp
+                .cfg
+                .hook_response_body_data_raw
+                .run_all(connp, &mut raw_tx_data)?;
+        }
 
         match self.response_content_encoding_processing {
             HtpContentEncoding::GZIP
             | HtpContentEncoding::DEFLATE
             | HtpContentEncoding::ZLIB
-            | HtpContentEncoding::LZMA => {
+            | HtpContentEncoding::LZMA
+            | HtpContentEncoding::BROTLI => {
                 // Send data buffer to the decompressor if it exists
                 if self.response_decompressor.is_none() && data.is_none() {
                     return Ok(());
                 }
                 let mut decompressor = self.response_decompressor.take().ok_or(HtpStatus::ERROR)?;
                 if let Some(data) = data {
+                    self.response_compressed_len =
+                        self.response_compressed_len.wrapping_add(data.len() as u64);
                     decompressor
                         .decompress(data)
                         .map_err(|_| HtpStatus::ERROR)?;
+                    if decompressor.raw_deflate() {
+                        self.flags2.set(HtpFlags2::DEFLATE_RAW);
+                    }
 
                     if decompressor.time_spent()
                         > self.cfg.compression_options.get_time_limit() as u64
@@ -1235,6 +2033,9 @@ impl Transaction {
             self.request_process_body_data(connp, None)?;
         }
         self.request_progress = HtpRequestProgress::COMPLETE;
+        connp.completion_count += 1;
+        self.request_completion_seq = Some(connp.completion_count);
+        self.request_complete_ts = Some(connp.request_timestamp);
         // Run hook REQUEST_COMPLETE.
         connp.cfg.hook_request_complete.run_all(connp, self)?;
         Ok(())
@@ -1291,6 +2092,8 @@ impl Transaction {
                 self.flags.set(HtpFlags::MULTI_PACKET_HEAD)
             }
             self.process_request_headers(connp)?;
+            self.update_accumulated_fields();
+            self.check_bytes_limit();
             connp.request_state = State::CONNECT_CHECK;
         } else {
             htp_warn!(
@@ -1312,16 +2115,23 @@ impl Transaction {
     /// Returns OK on success; ERROR on error, HTP_STOP if one of the
     ///         callbacks does not want to follow the transaction any more.
     pub fn state_request_line(&mut self, connp: &mut ConnectionParser) -> Result<()> {
+        self.request_line_ts = Some(connp.request_timestamp);
         // Determine how to process the request URI.
         let mut parsed_uri = Uri::with_config(connp.cfg.decoder_cfg);
-        if self.request_method_number == HtpMethod::CONNECT {
-            // When CONNECT is used, the request URI contains an authority string.
-            parsed_uri.parse_uri_hostport(
-                self.request_uri.as_ref().ok_or(HtpStatus::ERROR)?,
-                &mut self.flags,
-            );
-        } else if let Some(uri) = self.request_uri.as_ref() {
-            parsed_uri.parse_uri(uri.as_slice());
+        if self.request_method_number == HtpMethod::CONNECT && self.request_uri.is_none() {
+            return Err(HtpStatus::ERROR);
+        }
+        if let Some(uri) = self.request_uri.as_ref() {
+            // When CONNECT is used, the request URI contains an authority string,
+            // while OPTIONS may use an asterisk to designate the server itself.
+            parsed_uri.parse_request_target(self.request_method_number, uri, &mut self.flags);
+            if self.flags.is_set(HtpFlags::REQUEST_TARGET_INVALID) {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::REQUEST_TARGET_INVALID,
+                    "Request target form does not fit the request method"
+                );
+            }
         }
         self.parsed_uri_raw = Some(parsed_uri);
         // Parse the request URI into Transaction::parsed_uri_raw.
@@ -1330,25 +2140,114 @@ impl Transaction {
             // Keep the original URI components, but create a copy which we can normalize and use internally.
             self.normalize_parsed_uri();
         }
+        // Enforce the path length limit on the path as received, but truncate only the
+        // normalized copy.
+        if let Some(limit) = self.cfg.uri_path_limit {
+            let raw_path_len = self
+                .parsed_uri_raw
+                .as_ref()
+                .and_then(|uri| uri.path.as_ref())
+                .map_or(0, |path| path.len());
+            if raw_path_len > limit {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::PATH_TOO_LONG,
+                    "Request URI path is longer than the configured limit"
+                );
+                self.flags2.set(HtpFlags2::REQUEST_PATH_TOO_LONG);
+                if let Some(path) = self.parsed_uri.as_mut().and_then(|uri| uri.path.as_mut()) {
+                    path.truncate(limit);
+                }
+            }
+        }
+        // Check the raw and decoded paths for control characters. NUL, CR and LF are handled elsewhere.
+        let is_control = |c: &u8| (0x01..0x20).contains(c) && *c != b'\r' && *c != b'\n';
+        let raw_path = self
+            .parsed_uri_raw
+            .as_ref()
+            .and_then(|uri| uri.path.as_ref());
+        let path = self.parsed_uri.as_ref().and_then(|uri| uri.path.as_ref());
+        if raw_path
+            .into_iter()
+            .chain(path)
+            .any(|path| path.as_slice().iter().any(is_control))
+        {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::PATH_CONTROL_CHARS,
+                "Request path contains control characters"
+            );
+            self.flags.set(HtpFlags::PATH_CONTROL_CHARS);
+            if self.cfg.path_control_chars_reject {
+                return Err(HtpStatus::ERROR);
+            }
+        }
+        // Check the request line for raw bytes with the high bit set. Percent-encoded
+        // bytes are plain ASCII on the wire and therefore not affected.
+        if self
+            .request_line
+            .as_ref()
+            .map_or(false, |line| line.as_slice().iter().any(|c| *c >= 0x80))
+        {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::REQUEST_LINE_HIGH_BIT,
+                "Request line contains bytes with the high bit set"
+            );
+            self.flags.set(HtpFlags::REQUEST_LINE_HIGH_BIT);
+            if self.cfg.request_line_high_bit_reject {
+                return Err(HtpStatus::ERROR);
+            }
+        }
+        // More than one "?" makes the start of the query ambiguous. A "?" in the
+        // fragment is not part of the query.
+        if self.request_uri.as_ref().map_or(false, |uri| {
+            uri.as_slice()
+                .iter()
+                .take_while(|c| **c != b'#')
+                .filter(|c| **c == b'?')
+                .count()
+                > 1
+        }) {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::URI_MULTIPLE_QUERY_MARKS,
+                "Request target contains more than one question mark"
+            );
+            self.flags2.set(HtpFlags2::URI_MULTIPLE_QUERY_MARKS);
+            if self.cfg.uri_multiple_query_marks_reject {
+                return Err(HtpStatus::ERROR);
+            }
+        }
+        // Check the method against the allow-list.
+        if !self.cfg.allowed_methods.is_empty()
+            && !self
+                .cfg
+                .allowed_methods
+                .contains(&self.request_method_number)
+        {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::REQUEST_METHOD_DISALLOWED,
+                "Request method is not allowed"
+            );
+            self.flags2.set(HtpFlags2::REQUEST_METHOD_DISALLOWED);
+            if self.cfg.method_disallowed_unwanted != HtpUnwanted::IGNORE {
+                self.response_status_expected_number = self.cfg.method_disallowed_unwanted;
+            }
+        }
         if self.cfg.parse_urlencoded {
-            if let Some(query) = self
-                .parsed_uri
+            self.parse_query_string()?;
+        }
+
+        // Extract matrix parameters from the raw path.
+        if self.cfg.parse_matrix_params {
+            if let Some(path) = self
+                .parsed_uri_raw
                 .as_ref()
-                .and_then(|parsed_uri| parsed_uri.query.clone())
+                .and_then(|uri| uri.path.as_ref())
             {
-                // We have a non-zero length query string.
-                let mut urlenp = UrlEncodedParser::new(self.cfg.decoder_cfg);
-                urlenp.parse_complete(query.as_slice());
-
-                // Add all parameters to the transaction.
-                for (name, value) in urlenp.params.elements.iter() {
-                    let param = Param::new(
-                        Bstr::from(name.as_slice()),
-                        Bstr::from(value.as_slice()),
-                        HtpDataSource::QUERY_STRING,
-                    );
-                    self.request_add_param(param)?;
-                }
+                self.request_matrix_params = parse_matrix_params(path.as_slice());
             }
         }
 
@@ -1398,8 +2297,47 @@ impl Transaction {
         connp: &mut ConnectionParser,
         hybrid_mode: i32,
     ) -> Result<()> {
+        let mut reject = false;
         if self.response_progress != HtpResponseProgress::COMPLETE {
             self.response_progress = HtpResponseProgress::COMPLETE;
+            connp.completion_count += 1;
+            self.response_completion_seq = Some(connp.completion_count);
+            self.response_complete_ts = Some(connp.response_timestamp);
+            // The server did not wait for the declared request body.
+            if self.request_has_body() && self.request_progress < HtpRequestProgress::COMPLETE {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::REQUEST_BODY_UNREAD,
+                    "Response completed before the request body was received"
+                );
+                self.flags.set(HtpFlags::REQUEST_BODY_UNREAD);
+            }
+            // The server ignored the expectation of the client.
+            if self.request_expects_100_continue && !self.seen_100continue {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::EXPECT_MISMATCH,
+                    "Request expected 100 Continue but none was seen"
+                );
+                self.flags2.set(HtpFlags2::EXPECT_MISMATCH);
+            }
+            // Compare the delivered body with the declared Content-Length.
+            if connp.cfg.response_content_length_validate
+                && self.response_transfer_coding == HtpTransferCoding::IDENTITY
+                && self.response_content_length >= 0
+                && self.response_message_len != self.response_content_length
+            {
+                htp_warn!(
+                    self.logger,
+                    HtpLogCode::RESPONSE_CONTENT_LENGTH_MISMATCH,
+                    format!(
+                        "Response body length {} does not match Content-Length {}",
+                        self.response_message_len, self.response_content_length
+                    )
+                );
+                self.flags.set(HtpFlags::RESPONSE_CONTENT_LENGTH_MISMATCH);
+                reject = connp.cfg.response_content_length_mismatch_reject;
+            }
             // Run the last RESPONSE_BODY_DATA HOOK, but only if there was a response body present.
             if self.response_transfer_coding != HtpTransferCoding::NO_BODY {
                 let _ = self.response_process_body_data(connp, None);
@@ -1407,6 +2345,11 @@ impl Transaction {
             // Run hook RESPONSE_COMPLETE.
             connp.cfg.hook_response_complete.run_all(connp, self)?;
         }
+        if reject {
+            // Fail the stream only once the completed transaction has been reported.
+            self.finalize(connp)?;
+            return Err(HtpStatus::ERROR);
+        }
         if hybrid_mode == 0 {
             // Check if the inbound parser is waiting on us. If it is, that means that
             // there might be request data that the inbound parser hasn't consumed yet.
@@ -1455,6 +2398,9 @@ impl Transaction {
         // Keep track of actual response body length.
         self.response_entity_len =
             (self.response_entity_len as u64).wrapping_add(tx_data.len() as u64) as i64;
+        self.response_decompressed_len = self
+            .response_decompressed_len
+            .wrapping_add(tx_data.len() as u64);
 
         // Invoke all callbacks.
         connp
@@ -1526,6 +2472,7 @@ impl Transaction {
         // Keep track of actual request body length.
         self.request_entity_len =
             (self.request_entity_len as u64).wrapping_add(tx_data.len() as u64) as i64;
+        self.request_sniff_body(data);
 
         // Invoke all callbacks.
         connp
@@ -1620,6 +2567,7 @@ impl Transaction {
         encoding: HtpContentEncoding,
     ) -> Result<()> {
         if encoding != HtpContentEncoding::NONE {
+            self.response_content_encodings.push(encoding);
             if let Some(decompressor) = self.response_decompressor.take() {
                 self.response_decompressor
                     .replace(decompressor.prepend(encoding, self.cfg.compression_options)?);
@@ -1653,6 +2601,9 @@ impl Transaction {
     /// Returns OK on success; ERROR on error, HTP_STOP if one of the
     ///         callbacks does not want to follow the transaction any more.
     pub fn state_response_headers(&mut self, connp: &mut ConnectionParser) -> Result<()> {
+        self.update_accumulated_fields();
+        self.response_connection =
+            connection_disposition(&self.response_headers, self.response_protocol_number);
         let ce = (*self)
             .response_headers
             .get_nocase_nozero("content-encoding")
@@ -1672,6 +2623,8 @@ impl Transaction {
                 HtpContentEncoding::DEFLATE
             } else if ce.cmp_nocase_nozero(b"lzma") == Ordering::Equal {
                 HtpContentEncoding::LZMA
+            } else if ce.cmp_nocase_nozero(b"br") == Ordering::Equal {
+                HtpContentEncoding::BROTLI
             } else if ce.cmp_nocase_nozero(b"inflate") == Ordering::Equal {
                 HtpContentEncoding::NONE
             } else {
@@ -1690,6 +2643,37 @@ impl Transaction {
             HtpContentEncoding::NONE
         };
 
+        // Infer the server personality from the first response on the connection.
+        if connp.cfg.auto_personality && self.index == 0 {
+            if let Some(personality) =
+                self.response_headers
+                    .get_nocase_nozero("server")
+                    .and_then(|(_, server)| {
+                        HtpServerPersonality::from_server_header(server.value.as_slice())
+                    })
+            {
+                if personality != connp.cfg.server_personality
+                    && connp.set_server_personality(personality).is_ok()
+                {
+                    htp_info!(
+                        self.logger,
+                        HtpLogCode::SERVER_PERSONALITY_INFERRED,
+                        format!("Inferred server personality {:?}", personality)
+                    );
+                }
+            }
+        }
+
+        // Look for response headers injected through the request.
+        if connp.cfg.response_header_injection_detect && self.response_header_reflected() {
+            htp_warn!(
+                self.logger,
+                HtpLogCode::RESPONSE_HEADER_INJECTION,
+                "Response header reflected from request data"
+            );
+            self.flags2.set(HtpFlags2::RESPONSE_HEADER_INJECTION);
+        }
+
         // Run hook RESPONSE_HEADERS.
         //TODO: remove clone
         let hook_response_headers = self.cfg.hook_response_headers.clone();
@@ -1710,7 +2694,8 @@ impl Transaction {
             HtpContentEncoding::GZIP
             | HtpContentEncoding::DEFLATE
             | HtpContentEncoding::ZLIB
-            | HtpContentEncoding::LZMA => {
+            | HtpContentEncoding::LZMA
+            | HtpContentEncoding::BROTLI => {
                 self.response_prepend_decompressor(
                     connp,
                     self.response_content_encoding_processing,
@@ -1778,6 +2763,8 @@ impl Transaction {
                                     }
                                 }
                                 HtpContentEncoding::LZMA
+                            } else if encoding.cmp(b"br") == Ordering::Equal {
+                                HtpContentEncoding::BROTLI
                             } else if encoding.cmp(b"inflate") == Ordering::Equal {
                                 HtpContentEncoding::NONE
                             } else {
@@ -1882,8 +2869,14 @@ impl Transaction {
             uri.port_number = incomplete.normalized_port(&mut self.flags);
             uri.query = incomplete.query.clone();
             uri.fragment = incomplete.normalized_fragment(&mut self.flags);
-            uri.path = incomplete
-                .normalized_path(&mut self.flags, &mut self.response_status_expected_number);
+            let (path, traversal) = incomplete.normalized_path_with_traversal(
+                &mut self.flags,
+                &mut self.response_status_expected_number,
+            );
+            uri.path = path;
+            if traversal {
+                self.flags2.set(HtpFlags2::REQUEST_PATH_TRAVERSAL);
+            }
         }
         self.parsed_uri = Some(uri);
     }
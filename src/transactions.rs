@@ -50,6 +50,17 @@ impl Transactions {
         }
     }
 
+    /// Replace the config used for new transactions. The current request
+    /// transaction also picks it up if it has not started yet.
+    pub fn set_config(&mut self, cfg: &Rc<Config>) {
+        self.config = Rc::clone(cfg);
+        if let Some(tx) = self.transactions.get_mut(&self.request) {
+            if !tx.is_started() {
+                tx.cfg = Rc::clone(cfg);
+            }
+        }
+    }
+
     /// Get the current request transaction index
     pub fn request_index(&self) -> usize {
         self.request
@@ -3,6 +3,7 @@ use crate::{
     config::{DecoderConfig, HtpUnwanted},
     log::Logger,
     parsers::{credentials, fragment, hostname, parse_hostport, path, port, query, scheme},
+    request::HtpMethod,
     util::{
         convert_port, decode_uri_path_inplace, urldecode_inplace, urldecode_uri_inplace,
         utf8_decode_and_validate_uri_path_inplace, FlagOperations, HtpFlags,
@@ -187,6 +188,16 @@ impl Uri {
 
     /// Normalize uri path.
     pub fn normalized_path(&self, flags: &mut u64, status: &mut HtpUnwanted) -> Option<Bstr> {
+        self.normalized_path_with_traversal(flags, status).0
+    }
+
+    /// Normalize uri path, also returning whether the RFC normalization resolved a ".."
+    /// segment, which means that the raw path contained a traversal.
+    pub fn normalized_path_with_traversal(
+        &self,
+        flags: &mut u64,
+        status: &mut HtpUnwanted,
+    ) -> (Option<Bstr>, bool) {
         if let Some(mut path) = self.path.clone() {
             // Decode URL-encoded (and %u-encoded) characters, as well as lowercase,
             // compress separators and convert backslashes.
@@ -195,10 +206,10 @@ impl Uri {
             // Handle UTF-8 in the path. Validate it first, and only save it if cfg specifies it
             utf8_decode_and_validate_uri_path_inplace(&self.cfg, flags, status, &mut path);
             // RFC normalization.
-            normalize_uri_path_inplace(&mut path);
-            Some(path)
+            let traversal = normalize_uri_path_inplace(&mut path);
+            (Some(path), traversal)
         } else {
-            None
+            (None, false)
         }
     }
 
@@ -264,6 +275,26 @@ impl Uri {
         }
     }
 
+    /// Parses the request target according to its form (RFC 7230 5.3), which depends on the method.
+    ///
+    /// An asterisk-form target is not parsed at all, and the authority-form target of a CONNECT
+    /// only populates the hostname and port. Any other target is parsed using parse_uri.
+    /// Sets HtpFlags::REQUEST_TARGET_INVALID if the target form does not fit the method.
+    pub fn parse_request_target(&mut self, method: HtpMethod, target: &Bstr, flags: &mut u64) {
+        if target.eq("*") {
+            if method != HtpMethod::OPTIONS {
+                flags.set(HtpFlags::REQUEST_TARGET_INVALID)
+            }
+        } else if method == HtpMethod::CONNECT {
+            if target.starts_with("/") || target.index_of("://").is_some() {
+                flags.set(HtpFlags::REQUEST_TARGET_INVALID)
+            }
+            self.parse_uri_hostport(target, flags);
+        } else {
+            self.parse_uri(target.as_slice());
+        }
+    }
+
     /// Generate a normalized uri string.
     pub fn generate_normalized_uri(
         &self,
@@ -385,15 +416,74 @@ impl Uri {
     }
 }
 
+/// Raw form of the request URI components, exactly as they were supplied on the
+/// request line. The decoded form of the same components is kept in a Uri.
+#[derive(Copy, Clone)]
+pub struct RawUri<'a> {
+    uri: &'a Uri,
+}
+
+impl<'a> RawUri<'a> {
+    /// Wraps the raw components held in the provided Uri.
+    pub fn new(uri: &'a Uri) -> Self {
+        Self { uri }
+    }
+
+    /// Raw scheme, e.g., "HTTP".
+    pub fn scheme(&self) -> Option<&'a Bstr> {
+        self.uri.scheme.as_ref()
+    }
+
+    /// Raw username.
+    pub fn username(&self) -> Option<&'a Bstr> {
+        self.uri.username.as_ref()
+    }
+
+    /// Raw password.
+    pub fn password(&self) -> Option<&'a Bstr> {
+        self.uri.password.as_ref()
+    }
+
+    /// Raw hostname.
+    pub fn hostname(&self) -> Option<&'a Bstr> {
+        self.uri.hostname.as_ref()
+    }
+
+    /// Raw port, as string.
+    pub fn port(&self) -> Option<&'a Bstr> {
+        self.uri.port.as_ref()
+    }
+
+    /// Raw path, without any decoding or normalization.
+    pub fn path(&self) -> Option<&'a Bstr> {
+        self.uri.path.as_ref()
+    }
+
+    /// Raw query string, without the leading question mark.
+    pub fn query(&self) -> Option<&'a Bstr> {
+        self.uri.query.as_ref()
+    }
+
+    /// Raw fragment, without the leading hash mark.
+    pub fn fragment(&self) -> Option<&'a Bstr> {
+        self.uri.fragment.as_ref()
+    }
+}
+
 /// Normalize URI path in place. This function implements the remove dot segments algorithm
 /// specified in RFC 3986, section 5.2.4.
-fn normalize_uri_path_inplace(s: &mut Bstr) {
+///
+/// Returns true if the path contained a ".." segment, whether it removed a preceding
+/// segment or tried to go above the root.
+fn normalize_uri_path_inplace(s: &mut Bstr) -> bool {
     let mut out = Vec::<&[u8]>::with_capacity(10);
+    let mut traversal = false;
     s.as_slice()
         .split(|c| *c == b'/')
         .for_each(|segment| match segment {
             b"." => {}
             b".." => {
+                traversal = true;
                 if !(out.len() == 1 && out[0] == b"") {
                     out.pop();
                 }
@@ -403,6 +493,7 @@ fn normalize_uri_path_inplace(s: &mut Bstr) {
     let out = out.join(b"/" as &[u8]);
     s.clear();
     s.add(out.as_slice());
+    traversal
 }
 
 //Tests
@@ -536,6 +627,78 @@ fn ParseUri() {
     }
 }
 
+#[test]
+fn ParseRequestTarget() {
+    let tests = [
+        (
+            HtpMethod::OPTIONS,
+            Bstr::from("*"),
+            Uri::new(None, None, None, None, None, None, None, None, None),
+            false,
+        ),
+        (
+            HtpMethod::GET,
+            Bstr::from("*"),
+            Uri::new(None, None, None, None, None, None, None, None, None),
+            true,
+        ),
+        (
+            HtpMethod::CONNECT,
+            Bstr::from("www.example.com:443"),
+            Uri::new(
+                None,
+                None,
+                None,
+                Some(Bstr::from("www.example.com")),
+                Some(Bstr::from("443")),
+                Some(443),
+                None,
+                None,
+                None,
+            ),
+            false,
+        ),
+        (
+            HtpMethod::CONNECT,
+            Bstr::from("/path"),
+            Uri::new(None, None, None, None, None, None, None, None, None),
+            true,
+        ),
+        (
+            HtpMethod::OPTIONS,
+            Bstr::from("/path?a=b"),
+            Uri::new(
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Bstr::from("/path")),
+                Some(Bstr::from("a=b")),
+                None,
+            ),
+            false,
+        ),
+    ]
+    .to_vec();
+    for test in tests {
+        let mut uri = Uri::default();
+        let mut flags = 0;
+        uri.parse_request_target(test.0, &test.1, &mut flags);
+        assert_eq!(test.2.scheme, uri.scheme);
+        assert_eq!(test.2.username, uri.username);
+        assert_eq!(test.2.password, uri.password);
+        assert_eq!(test.2.hostname, uri.hostname);
+        assert_eq!(test.2.port, uri.port);
+        assert_eq!(test.2.port_number, uri.port_number);
+        assert_eq!(test.2.path, uri.path);
+        assert_eq!(test.2.query, uri.query);
+        assert_eq!(test.2.fragment, uri.fragment);
+        assert_eq!(test.3, flags.is_set(HtpFlags::REQUEST_TARGET_INVALID));
+    }
+}
+
 #[test]
 fn GenerateNormalizedUri1() {
     let mut uri = Uri::default();
@@ -671,3 +834,26 @@ fn NormalizeUriPath() {
     normalize_uri_path_inplace(&mut s);
     assert!(s.eq("/images.gif"));
 }
+
+#[test]
+fn NormalizeUriPathTraversal() {
+    let mut s = Bstr::from("/a/../b");
+    assert!(normalize_uri_path_inplace(&mut s));
+    assert!(s.eq("/b"));
+
+    let mut s = Bstr::from("/a/..b");
+    assert!(!normalize_uri_path_inplace(&mut s));
+    assert!(s.eq("/a/..b"));
+
+    let mut s = Bstr::from("/a/./b");
+    assert!(!normalize_uri_path_inplace(&mut s));
+    assert!(s.eq("/a/b"));
+
+    let mut s = Bstr::from("/../b");
+    assert!(normalize_uri_path_inplace(&mut s));
+    assert!(s.eq("/b"));
+
+    let mut s = Bstr::from("/../../etc/passwd");
+    assert!(normalize_uri_path_inplace(&mut s));
+    assert!(s.eq("/etc/passwd"));
+}
@@ -5,6 +5,7 @@ use crate::{
     config::{DecoderConfig, HtpServerPersonality, HtpUnwanted, HtpUrlEncodingHandling},
     error::Result,
     hook::FileDataHook,
+    multipart::find_boundary,
     utf8_decoder::Utf8Decoder,
     HtpStatus,
 };
@@ -154,6 +155,90 @@ impl HtpFlags {
     pub const REQUEST_MISSING_BYTES: u64 = (0x0010_0000_0000 | Self::MISSING_BYTES);
     /// Missing bytes in the response data.
     pub const RESPONSE_MISSING_BYTES: u64 = (0x0020_0000_0000 | Self::MISSING_BYTES);
+    /// Response status is one of the configured interesting status codes.
+    pub const STATUS_INTERESTING: u64 = 0x0040_0000_0000;
+    /// Transaction accumulated more bytes than the configured limit.
+    pub const TX_BYTES_LIMIT: u64 = 0x0080_0000_0000;
+    /// Transfer-Encoding contains a token that is not in the configured allowlist.
+    pub const TRANSFER_ENCODING_DISALLOWED: u64 = 0x0100_0000_0000;
+    /// A bare CR was accepted as a request header line terminator.
+    pub const REQUEST_BARE_CR_EOL: u64 = 0x0200_0000_0000;
+    /// The raw or decoded request path contains control characters.
+    pub const PATH_CONTROL_CHARS: u64 = 0x0400_0000_0000;
+    /// A response was seen without any preceding request data.
+    pub const REQUEST_EMPTY: u64 = 0x0800_0000_0000;
+    /// The connection closed before any response data was seen for the request.
+    pub const RESPONSE_EMPTY: u64 = 0x1000_0000_0000;
+    /// Best-fit mapping was applied to a query string parameter.
+    pub const QUERY_UTF8_BESTFIT: u64 = 0x2000_0000_0000;
+    /// A chunked request was answered with a response delimited by the connection closing.
+    pub const TRANSFER_CODING_MISMATCH: u64 = 0x4000_0000_0000;
+    /// A header value is longer than the configured soft limit.
+    pub const FIELD_VALUE_SOFT_LIMIT: u64 = 0x8000_0000_0000;
+    /// An HTTP/1.1 response with a body has no Date header.
+    pub const RESPONSE_DATE_MISSING: u64 = 0x0001_0000_0000_0000;
+    /// More headers than the configured limit were seen; parsing stopped with an error.
+    pub const HEADERS_TOO_MANY: u64 = 0x0002_0000_0000_0000;
+    /// A header value was folded over more lines than the configured limit.
+    pub const FIELD_FOLDING_LIMIT: u64 = 0x0004_0000_0000_0000;
+    /// The request target form does not fit the request method (e.g., an asterisk target with
+    /// a method other than OPTIONS, or a CONNECT target that is not in authority form).
+    pub const REQUEST_TARGET_INVALID: u64 = 0x0008_0000_0000_0000;
+    /// A second response line was seen for a transaction which already had a final status.
+    pub const RESPONSE_MULTIPLE_STATUS_LINES: u64 = 0x0010_0000_0000_0000;
+    /// The request has both a chunked Transfer-Encoding and a Content-Length header.
+    pub const REQUEST_SMUGGLING_TE_CL: u64 = 0x0020_0000_0000_0000;
+    /// The response has both a chunked Transfer-Encoding and a Content-Length header.
+    pub const RESPONSE_SMUGGLING_TE_CL: u64 = 0x0040_0000_0000_0000;
+    /// The request line contains raw (not percent-encoded) bytes with the high bit set.
+    pub const REQUEST_LINE_HIGH_BIT: u64 = 0x0080_0000_0000_0000;
+    /// The sniffed request body content type contradicts the declared Content-Type.
+    pub const REQUEST_CONTENT_TYPE_MISMATCH: u64 = 0x0100_0000_0000_0000;
+    /// More cookies, or more cookie bytes, than the configured limits were seen; the extra
+    /// cookies were dropped.
+    pub const COOKIES_LIMIT: u64 = 0x0200_0000_0000_0000;
+    /// The request declared a body that had not been fully received when the response
+    /// completed.
+    pub const REQUEST_BODY_UNREAD: u64 = 0x0400_0000_0000_0000;
+    /// The server sent a 100 Continue response to a request that did not declare a body.
+    pub const RESPONSE_CONTINUE_WITHOUT_BODY: u64 = 0x0800_0000_0000_0000;
+    /// A chunk length line has whitespace between the chunk size and the line ending.
+    pub const CHUNKED_LENGTH_WHITESPACE: u64 = 0x1000_0000_0000_0000;
+    /// The response body has more chunks than the configured limit.
+    pub const RESPONSE_TOO_MANY_CHUNKS: u64 = 0x2000_0000_0000_0000;
+    /// The request or response protocol version has a major or minor number above 99.
+    pub const PROTOCOL_INVALID: u64 = 0x4000_0000_0000_0000;
+    /// The number of response body bytes delivered differs from the declared Content-Length.
+    pub const RESPONSE_CONTENT_LENGTH_MISMATCH: u64 = 0x8000_0000_0000_0000;
+}
+
+/// Flag bits of the second transaction flags field, Transaction::flags2, used once all
+/// the HtpFlags bits were taken.
+pub struct HtpFlags2;
+
+impl HtpFlags2 {
+    /// More request body bytes than the declared Content-Length arrived before the next
+    /// request line.
+    pub const REQUEST_BODY_OVERFLOW: u64 = 0x0000_0000_0001;
+    /// The request expected a 100 Continue response but the server did not send one.
+    pub const EXPECT_MISMATCH: u64 = 0x0000_0000_0002;
+    /// The request method is not in the configured allow-list.
+    pub const REQUEST_METHOD_DISALLOWED: u64 = 0x0000_0000_0004;
+    /// Normalizing the request path resolved a ".." segment.
+    pub const REQUEST_PATH_TRAVERSAL: u64 = 0x0000_0000_0008;
+    /// The request URI path is longer than Config::uri_path_limit.
+    pub const REQUEST_PATH_TOO_LONG: u64 = 0x0000_0000_0010;
+    /// A response header value contains a raw CR or LF that was not removed by folding,
+    /// which indicates response splitting.
+    pub const RESPONSE_SPLITTING: u64 = 0x0000_0000_0020;
+    /// A deflate request or response body was decompressed as raw deflate data because
+    /// it did not start with a zlib header.
+    pub const DEFLATE_RAW: u64 = 0x0000_0000_0040;
+    /// The request target contains more than one "?" before the fragment.
+    pub const URI_MULTIPLE_QUERY_MARKS: u64 = 0x0000_0000_0080;
+    /// A response header matches a line injected through a CR or LF in a request
+    /// parameter or header value.
+    pub const RESPONSE_HEADER_INJECTION: u64 = 0x0000_0000_0100;
 }
 
 /// Enumerates file sources.
@@ -323,6 +408,23 @@ pub fn is_space(c: u8) -> bool {
     matches!(c as char, ' ' | '\t' | '\r' | '\n' | '\x0b' | '\x0c')
 }
 
+/// Determines if character is optional whitespace, as defined by RFC 7230.
+/// ows = ' ' | '\t'
+pub fn is_ows(c: u8) -> bool {
+    c == b' ' || c == b'\t'
+}
+
+/// Removes optional whitespace (SP and HTAB only, see RFC 7230) from both ends of the input.
+/// Unlike trimming based on is_space, a CR, LF, VT or FF at either end is kept.
+pub fn trim_ows(data: &[u8]) -> &[u8] {
+    let start = data.iter().position(|&c| !is_ows(c)).unwrap_or(data.len());
+    let end = data
+        .iter()
+        .rposition(|&c| !is_ows(c))
+        .map_or(start, |pos| pos + 1);
+    &data[start..end]
+}
+
 /// Helper function that mimics the functionality of bytes::complete::take_until, ignoring tag case
 /// Returns the longest input slice till it case insensitively matches the pattern. It doesn't consume the pattern.
 ///
@@ -508,6 +610,25 @@ pub fn utf8_decode_and_validate_uri_path_inplace(
     }
 }
 
+/// Converts a UTF-8 encoded (and already URL-decoded) query string name or value into
+/// a single-byte stream using best-fit mapping. Invalid characters are replaced with the
+/// replacement byte specified in the cfg.
+///
+/// Returns true if the input was changed.
+pub fn utf8_bestfit_query_inplace(cfg: &DecoderConfig, input: &mut Bstr) -> bool {
+    if input.as_slice().is_ascii() {
+        return false;
+    }
+    let mut decoder = Utf8Decoder::new(cfg.bestfit_map);
+    decoder.decode_and_validate(input.as_slice());
+    if decoder.decoded_bytes.as_slice() == input.as_slice() {
+        return false;
+    }
+    input.clear();
+    input.add(decoder.decoded_bytes.as_slice());
+    true
+}
+
 /// Decode a %u-encoded character, using best-fit mapping as necessary. Path version.
 ///
 /// Sets i to decoded byte
@@ -879,6 +1000,51 @@ pub fn urldecode_inplace(cfg: &DecoderConfig, input: &mut Bstr) -> Result<()> {
     }
 }
 
+/// Performs decoding of the input string into a new Bstr, according to the configuration
+/// specified by cfg, leaving the input untouched. Decodes exactly as urldecode_inplace.
+///
+/// Returns the decoded string and the flags (HTP_URLEN_*) set during decoding.
+pub fn urldecode(cfg: &DecoderConfig, input: &[u8]) -> (Bstr, u64) {
+    if let Ok((_, (consumed, flags, _))) = urldecode_ex(input, cfg) {
+        (Bstr::from(consumed), flags)
+    } else {
+        (Bstr::from(input), 0)
+    }
+}
+
+/// Normalizes percent-encoding in place without fully decoding the input: the hex digits
+/// of each escape are uppercased and escapes of RFC 3986 unreserved characters (ALPHA,
+/// DIGIT, "-", ".", "_" and "~") are decoded, while reserved and other octets stay encoded.
+/// Invalid escapes are left untouched. e.g. "%2f%7e%41" -> "%2F~A"
+pub fn normalize_percent_encoding_inplace(input: &mut Bstr) {
+    let data = input.as_slice();
+    let mut normalized = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'%'
+            && i + 2 < data.len()
+            && data[i + 1].is_ascii_hexdigit()
+            && data[i + 2].is_ascii_hexdigit()
+        {
+            if let Ok((_, byte)) = x2c(&data[i + 1..i + 3]) {
+                if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                    normalized.push(byte);
+                } else {
+                    normalized.push(b'%');
+                    normalized.push(data[i + 1].to_ascii_uppercase());
+                    normalized.push(data[i + 2].to_ascii_uppercase());
+                }
+                i += 3;
+                continue;
+            }
+        }
+        normalized.push(data[i]);
+        i += 1;
+    }
+    input.clear();
+    input.add(normalized);
+}
+
 /// Decodes valid uencoded hex bytes according to the given cfg settings.
 /// e.g. "u0064" -> "d"
 ///
@@ -1115,6 +1281,172 @@ pub fn treat_response_line_as_body(data: &[u8]) -> bool {
     tuple((opt(take_is_space_or_null), tag_no_case("http")))(data).is_err()
 }
 
+/// Determines if data is a status line: an HTTP/x.y protocol, followed by
+/// whitespace and a three-digit status code.
+pub fn is_status_line(data: &[u8]) -> bool {
+    if let Ok((rest, _)) = tuple((
+        tag_no_case("HTTP/"),
+        digit1,
+        char('.'),
+        digit1,
+        take_while1(|c: u8| c == b' ' || c == b'\t'),
+        take_while_m_n(3, 3, |c: u8| c.is_ascii_digit()),
+    ))(data)
+    {
+        rest.first().map_or(true, |c| is_space(*c))
+    } else {
+        false
+    }
+}
+
+/// Byte range and complete length carried by a Content-Range header.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ContentRange {
+    /// Position of the first byte of the range, or None for an unsatisfied range.
+    pub start: Option<u64>,
+    /// Position of the last byte of the range (inclusive), or None for an unsatisfied range.
+    pub end: Option<u64>,
+    /// Complete length of the representation, or None if it is unknown.
+    pub total: Option<u64>,
+}
+
+/// Parses a Content-Range header value (RFC 7233, section 4.2), such as "bytes 0-499/1234",
+/// the unknown complete length form "bytes 0-499/*" or the unsatisfied form "bytes */1234".
+///
+/// Returns the parsed range, or None if the value is malformed.
+pub fn parse_content_range(value: &[u8]) -> Option<ContentRange> {
+    let number = |data: &[u8]| -> Option<u64> {
+        if data.is_empty() || !data.iter().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        std::str::from_utf8(data).ok()?.parse().ok()
+    };
+    let start = value.iter().position(|c| !c.is_ascii_whitespace())?;
+    let end = value.iter().rposition(|c| !c.is_ascii_whitespace())?;
+    let value = &value[start..=end];
+    if value.len() < 6 || !value[..5].eq_ignore_ascii_case(b"bytes") || value[5] != b' ' {
+        return None;
+    }
+    let value = &value[5..];
+    let value = &value[value.iter().position(|c| *c != b' ')?..];
+    let slash = value.iter().position(|c| *c == b'/')?;
+    let (range, total) = (&value[..slash], &value[slash + 1..]);
+    let total = if total == b"*" {
+        None
+    } else {
+        Some(number(total)?)
+    };
+    if range == b"*" {
+        // An unsatisfied range must carry the complete length.
+        if total.is_none() {
+            return None;
+        }
+        return Some(ContentRange {
+            start: None,
+            end: None,
+            total,
+        });
+    }
+    let dash = range.iter().position(|c| *c == b'-')?;
+    let first = number(&range[..dash])?;
+    let last = number(&range[dash + 1..])?;
+    if first > last || total.map_or(false, |total| last >= total) {
+        return None;
+    }
+    Some(ContentRange {
+        start: Some(first),
+        end: Some(last),
+        total,
+    })
+}
+
+/// Guesses the content type of a body from its first bytes. This is a best-effort
+/// heuristic which only distinguishes JSON, XML, urlencoded forms, plain text (ASCII
+/// or UTF-8) and binary data.
+///
+/// Returns the guessed MIME type.
+pub fn sniff_content_type(data: &[u8]) -> &'static str {
+    let start = data
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let data = &data[start..];
+    match data.first() {
+        Some(b'{') | Some(b'[') => "application/json",
+        Some(b'<') => "application/xml",
+        _ if data
+            .iter()
+            .any(|c| c.is_ascii_control() && !c.is_ascii_whitespace()) =>
+        {
+            "application/octet-stream"
+        }
+        _ if data.contains(&b'=')
+            && data
+                .iter()
+                .all(|c| c.is_ascii_alphanumeric() || b"=&%+-_.*~".contains(c)) =>
+        {
+            "application/x-www-form-urlencoded"
+        }
+        _ if std::str::from_utf8(data).is_ok() => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The broad kinds of content that sniff_content_type can tell apart.
+#[derive(PartialEq)]
+enum ContentTypeFamily {
+    Json,
+    Xml,
+    Form,
+    Text,
+    Binary,
+    Other,
+}
+
+impl ContentTypeFamily {
+    fn of(media_type: &[u8]) -> Self {
+        let media_type = media_type.to_ascii_lowercase();
+        let media_type = media_type.as_slice();
+        if media_type == b"application/json"
+            || media_type == b"text/json"
+            || media_type.ends_with(b"+json")
+        {
+            Self::Json
+        } else if media_type == b"application/xml"
+            || media_type == b"text/xml"
+            || media_type.ends_with(b"+xml")
+        {
+            Self::Xml
+        } else if media_type == b"application/x-www-form-urlencoded" {
+            Self::Form
+        } else if media_type.starts_with(b"text/") {
+            Self::Text
+        } else if media_type == b"application/octet-stream" {
+            Self::Binary
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Determines whether a declared media type is consistent with the content type guessed
+/// by sniff_content_type. Types are compared by family, so text/xml matches
+/// application/xml and application/problem+json matches application/json. A form body
+/// may be declared as plain text and the other way around. Declared types that cannot
+/// be sniffed, such as images or multipart bodies, match any guess.
+///
+/// Returns false only if the declared type is clearly contradicted by the body.
+pub fn content_type_matches(declared: &[u8], sniffed: &[u8]) -> bool {
+    let declared = ContentTypeFamily::of(declared);
+    let sniffed = ContentTypeFamily::of(sniffed);
+    match (declared, sniffed) {
+        (ContentTypeFamily::Other, _) => true,
+        (ContentTypeFamily::Text, ContentTypeFamily::Form)
+        | (ContentTypeFamily::Form, ContentTypeFamily::Text) => true,
+        (declared, sniffed) => declared == sniffed,
+    }
+}
+
 /// Implements relaxed (not strictly RFC) hostname validation.
 ///
 /// Returns true if the supplied hostname is valid; false if it is not.
@@ -1123,8 +1455,24 @@ pub fn validate_hostname(input: &[u8]) -> bool {
         return false;
     }
     if char::<_, (&[u8], nom::error::ErrorKind)>('[')(input).is_ok() {
-        if let Ok((input, _)) = is_not::<_, _, (&[u8], nom::error::ErrorKind)>("#?/]")(input) {
-            return char::<_, (&[u8], nom::error::ErrorKind)>(']')(input).is_ok();
+        if let Ok((rest, address)) = is_not::<_, _, (&[u8], nom::error::ErrorKind)>("#?/]")(input) {
+            // An IPv6 address may carry a zone identifier after '%' (RFC 6874),
+            // which must be present and made of unreserved characters only.
+            if let Some(pos) = address.iter().position(|&c| c == b'%') {
+                let zone = &address[pos + 1..];
+                if zone.is_empty()
+                    || !zone.iter().all(|&c| {
+                        c.is_ascii_alphanumeric()
+                            || c == b'-'
+                            || c == b'.'
+                            || c == b'_'
+                            || c == b'~'
+                    })
+                {
+                    return false;
+                }
+            }
+            return char::<_, (&[u8], nom::error::ErrorKind)>(']')(rest).is_ok();
         } else {
             return false;
         }
@@ -1293,15 +1641,57 @@ fn is_chunked_ctl_char(c: u8) -> bool {
     matches!(c, 0x0d | 0x0a | 0x20 | 0x09 | 0x0b | 0x0c)
 }
 
+/// Converts a header name to its canonical form, in which the first letter and every
+/// letter following a hyphen are uppercase and all other letters are lowercase.
+/// e.g. "content-TYPE" -> "Content-Type"
+pub fn canonicalize_header_name(name: &[u8]) -> Bstr {
+    let mut upper = true;
+    let canonical: Vec<u8> = name
+        .iter()
+        .map(|c| {
+            let c = if upper {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            };
+            upper = c == b'-';
+            c
+        })
+        .collect();
+    Bstr::from(canonical)
+}
+
+/// Extracts the multipart boundary from a Content-Type header value, without creating
+/// a multipart parser.
+///
+/// Returns the boundary, without quotes, together with the multipart header boundary
+/// flags: HBOUNDARY_QUOTED for a quoted boundary, HBOUNDARY_UNUSUAL for unusual
+/// whitespace (such as a trailing space) or characters, and HBOUNDARY_INVALID for
+/// invalid syntax or a boundary over 70 bytes. Returns None if there is no boundary.
+pub fn extract_multipart_boundary(content_type: &[u8]) -> Option<(Bstr, u64)> {
+    let mut flags = 0;
+    let boundary = find_boundary(content_type, &mut flags)?;
+    Some((Bstr::from(boundary), flags))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{config::Config, util::*};
+    use crate::{config::Config, multipart::Flags as MultipartFlags, util::*};
     use nom::{
         error::ErrorKind::TakeUntil,
         Err::{Error, Incomplete},
         Needed,
     };
 
+    #[test]
+    fn CanonicalizeHeaderName() {
+        assert!(canonicalize_header_name(b"content-TYPE").eq("Content-Type"));
+        assert!(canonicalize_header_name(b"X-FORWARDED-FOR").eq("X-Forwarded-For"));
+        assert!(canonicalize_header_name(b"etag").eq("Etag"));
+        assert!(canonicalize_header_name(b"-a--b").eq("-A--B"));
+        assert!(canonicalize_header_name(b"").eq(""));
+    }
+
     #[test]
     fn TakeUntilNull() {
         assert_eq!(
@@ -1376,6 +1766,19 @@ mod test {
         assert_eq!(true, treat_response_line_as_body(b"kfgjl  hTtp "));
     }
 
+    #[test]
+    fn IsStatusLine() {
+        assert!(is_status_line(b"HTTP/1.1 302 Found"));
+        assert!(is_status_line(b"http/1.0 200"));
+        assert!(is_status_line(b"HTTP/1.1\t404 \r\n"));
+        assert!(!is_status_line(b"HTTPS-Redirect yes"));
+        assert!(!is_status_line(b"HTTP/1.1 2000 OK"));
+        assert!(!is_status_line(b"HTTP/1.1 20 OK"));
+        assert!(!is_status_line(b"HTTP/1 200 OK"));
+        assert!(!is_status_line(b"HTTP/1.1"));
+        assert!(!is_status_line(b" HTTP/1.1 200 OK"));
+    }
+
     #[test]
     fn RemoveLWS() {
         assert_eq!(
@@ -1632,6 +2035,14 @@ mod test {
         assert!(!validate_hostname(b"[:::?]"));
     }
 
+    #[test]
+    fn ValidateHostname_14() {
+        assert!(validate_hostname(b"[fe80::1%eth0]"));
+        assert!(!validate_hostname(b"[fe80::1%]"));
+        assert!(!validate_hostname(b"[fe80::1%eth%0]"));
+        assert!(!validate_hostname(b"fe80%eth0"));
+    }
+
     #[test]
     fn AsciiDigits() {
         // Returns (any trailing non-LWS characters, (non-LWS leading characters, ascii digits))
@@ -2405,6 +2816,18 @@ mod test {
         assert!(i.eq("?.?}abcd"));
     }
 
+    #[test]
+    fn Utf8BestfitQuery() {
+        let cfg = Config::default();
+        let mut i = Bstr::from(b"a\xef\xbd\x9db".to_vec());
+        assert!(utf8_bestfit_query_inplace(&cfg.decoder_cfg, &mut i));
+        assert!(i.eq("a}b"));
+
+        let mut i = Bstr::from("abc");
+        assert!(!utf8_bestfit_query_inplace(&cfg.decoder_cfg, &mut i));
+        assert!(i.eq("abc"));
+    }
+
     #[test]
     fn UrlDecode() {
         let mut cfg = Config::default();
@@ -2443,4 +2866,191 @@ mod test {
         urldecode_inplace(&cfg.decoder_cfg, &mut s).unwrap();
         assert_eq!(e, s);
     }
+
+    #[test]
+    fn UrlDecodeCopy() {
+        let mut cfg = Config::default();
+        cfg.set_u_encoding_decode(true);
+        let inputs = [
+            "/one/tw%u006f/three/%u123",
+            "/one/tw%u006f/three/%uXXXX",
+            "/one/tw%u006f/three/%3",
+            "/one/%00two",
+            "a+b%20c",
+        ];
+        for handling in &[
+            HtpUrlEncodingHandling::PRESERVE_PERCENT,
+            HtpUrlEncodingHandling::REMOVE_PERCENT,
+            HtpUrlEncodingHandling::PROCESS_INVALID,
+        ] {
+            cfg.set_url_encoding_invalid_handling(*handling);
+            for nul_terminates in &[false, true] {
+                cfg.set_nul_encoded_terminates(*nul_terminates);
+                for input in &inputs {
+                    let mut s = Bstr::from(*input);
+                    urldecode_inplace(&cfg.decoder_cfg, &mut s).unwrap();
+                    let (decoded, _) = urldecode(&cfg.decoder_cfg, input.as_bytes());
+                    assert_eq!(s, decoded);
+                }
+            }
+        }
+
+        cfg.set_url_encoding_invalid_handling(HtpUrlEncodingHandling::PRESERVE_PERCENT);
+        cfg.set_nul_encoded_terminates(false);
+        let input = Bstr::from("/one/%00two/%3");
+        let (decoded, flags) = urldecode(&cfg.decoder_cfg, input.as_slice());
+        assert!(input.eq("/one/%00two/%3"));
+        assert!(decoded.len() < input.len());
+        assert!(flags.is_set(HtpFlags::URLEN_ENCODED_NUL));
+        assert!(flags.is_set(HtpFlags::URLEN_INVALID_ENCODING));
+    }
+
+    #[test]
+    fn SniffContentType() {
+        let inputs: [(&[u8], &str); 9] = [
+            (b"{\"a\": 1}", "application/json"),
+            (b"  \r\n[1, 2]", "application/json"),
+            (b"<?xml version=\"1.0\"?><a/>", "application/xml"),
+            (b"p=0123&q=%20+x", "application/x-www-form-urlencoded"),
+            (b"Hello World!", "text/plain"),
+            (b"", "text/plain"),
+            (b"\x1f\x8b\x08\x00", "application/octet-stream"),
+            (b"caf\xc3\xa9", "text/plain"),
+            (b"caf\xe9", "application/octet-stream"),
+        ];
+        for (input, expected) in inputs.iter() {
+            assert_eq!(*expected, sniff_content_type(input));
+        }
+    }
+
+    #[test]
+    fn ContentTypeMatches() {
+        let inputs: [(&str, &str, bool); 11] = [
+            ("application/json", "application/json", true),
+            ("application/problem+json", "application/json", true),
+            ("text/xml", "application/xml", true),
+            ("application/soap+xml", "application/xml", true),
+            ("text/plain", "application/x-www-form-urlencoded", true),
+            ("application/x-www-form-urlencoded", "text/plain", true),
+            ("text/html", "text/plain", true),
+            ("multipart/form-data", "text/plain", true),
+            ("application/octet-stream", "application/json", false),
+            ("application/json", "application/xml", false),
+            ("text/plain", "application/octet-stream", false),
+        ];
+        for (declared, sniffed, expected) in inputs.iter() {
+            assert_eq!(
+                *expected,
+                content_type_matches(declared.as_bytes(), sniffed.as_bytes())
+            );
+        }
+    }
+
+    #[test]
+    fn ParseContentRange() {
+        let range = |start, end, total| Some(ContentRange { start, end, total });
+        assert_eq!(
+            range(Some(0), Some(499), Some(1234)),
+            parse_content_range(b"bytes 0-499/1234")
+        );
+        assert_eq!(
+            range(Some(500), Some(1233), Some(1234)),
+            parse_content_range(b" Bytes  500-1233/1234 ")
+        );
+        assert_eq!(
+            range(Some(0), Some(499), None),
+            parse_content_range(b"bytes 0-499/*")
+        );
+        assert_eq!(
+            range(None, None, Some(1234)),
+            parse_content_range(b"bytes */1234")
+        );
+        assert_eq!(None, parse_content_range(b""));
+        assert_eq!(None, parse_content_range(b"bytes"));
+        assert_eq!(None, parse_content_range(b"bytes */*"));
+        assert_eq!(None, parse_content_range(b"bytes 0-499"));
+        assert_eq!(None, parse_content_range(b"bytes 499-0/1234"));
+        assert_eq!(None, parse_content_range(b"bytes 0-1234/1234"));
+        assert_eq!(None, parse_content_range(b"bytes -499/1234"));
+        assert_eq!(None, parse_content_range(b"bytes 0-+499/1234"));
+        assert_eq!(None, parse_content_range(b"items 0-499/1234"));
+        assert_eq!(None, parse_content_range(b"bytes0-499/1234"));
+    }
+
+    #[test]
+    fn TrimOws() {
+        let inputs: Vec<(&[u8], &[u8])> = vec![
+            (b"", b""),
+            (b" \t ", b""),
+            (b"value", b"value"),
+            (b" \tvalue\t ", b"value"),
+            (b" a b ", b"a b"),
+            (b"value\r", b"value\r"),
+            (b"\x0bvalue\x0c", b"\x0bvalue\x0c"),
+            (b" value\r\n ", b"value\r\n"),
+        ];
+        for (input, expected) in inputs.iter() {
+            assert_eq!(*expected, trim_ows(input));
+        }
+    }
+
+    #[test]
+    fn NormalizePercentEncoding() {
+        let inputs: Vec<(&[u8], &[u8])> = vec![
+            (b"", b""),
+            (b"/a/b", b"/a/b"),
+            (b"/a%2fb", b"/a%2Fb"),
+            (b"/a%2Fb", b"/a%2Fb"),
+            (
+                b"%3a%3A%3f%23%5b%40%21%24%26%27%2c%3b%3d",
+                b"%3A%3A%3F%23%5B%40%21%24%26%27%2C%3B%3D",
+            ),
+            (b"%41%7a%30%39", b"Az09"),
+            (b"%2d%2E%5f%7E", b"-._~"),
+            (b"%2c%2F%3a%40%5b%60%7b%7f", b"%2C%2F%3A%40%5B%60%7B%7F"),
+            (b"%20%ff%00", b"%20%FF%00"),
+            (b"%", b"%"),
+            (b"%4", b"%4"),
+            (b"%zz%4g", b"%zz%4g"),
+            (b"%%41", b"%A"),
+            (b"%2541", b"%2541"),
+        ];
+        for (input, expected) in inputs {
+            let mut input = Bstr::from(input);
+            normalize_percent_encoding_inplace(&mut input);
+            assert_eq!(Bstr::from(expected), input);
+        }
+    }
+
+    #[test]
+    fn ExtractMultipartBoundary() {
+        let (boundary, flags) =
+            extract_multipart_boundary(b"multipart/form-data; boundary=myboundary").unwrap();
+        assert!(boundary.eq("myboundary"));
+        assert_eq!(0, flags);
+
+        let (boundary, flags) =
+            extract_multipart_boundary(b"multipart/form-data; boundary=\"myboundary\"").unwrap();
+        assert!(boundary.eq("myboundary"));
+        assert!(flags.is_set(MultipartFlags::HBOUNDARY_QUOTED));
+        assert!(!flags.is_set(MultipartFlags::HBOUNDARY_INVALID));
+
+        let (boundary, flags) =
+            extract_multipart_boundary(b"multipart/form-data; boundary=my.boundary:1 ").unwrap();
+        assert!(boundary.eq("my.boundary:1"));
+        assert!(flags.is_set(MultipartFlags::HBOUNDARY_UNUSUAL));
+        assert!(!flags.is_set(MultipartFlags::HBOUNDARY_INVALID));
+
+        let (boundary, flags) =
+            extract_multipart_boundary(b"multipart/form-data; boundary=my<boundary").unwrap();
+        assert!(boundary.eq("my<boundary"));
+        assert!(flags.is_set(MultipartFlags::HBOUNDARY_INVALID));
+
+        let long = format!("multipart/form-data; boundary={}", "a".repeat(71));
+        let (_, flags) = extract_multipart_boundary(long.as_bytes()).unwrap();
+        assert!(flags.is_set(MultipartFlags::HBOUNDARY_INVALID));
+
+        assert!(extract_multipart_boundary(b"multipart/form-data").is_none());
+        assert!(extract_multipart_boundary(b"multipart/form-data; charset=utf-8").is_none());
+    }
 }
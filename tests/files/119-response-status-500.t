@@ -0,0 +1,10 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 500 Internal Server Error
+Content-Length: 5
+
+error
\ No newline at end of file
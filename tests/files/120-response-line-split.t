@@ -0,0 +1,12 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 2
+<<<
+00 OK
+Content-Length: 12
+
+Hello World!
\ No newline at end of file
@@ -0,0 +1,25 @@
+>>>
+GET /1 HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Transfer-Encoding: chunked
+
+5
+Hello
+0
+
+
+>>>
+GET /2 HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Transfer-Encoding: gzip
+Content-Length: 5
+
+Hello
\ No newline at end of file
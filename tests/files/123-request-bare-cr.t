@@ -0,0 +1,9 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.comUser-Agent: testX-Test: 1
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
@@ -0,0 +1,12 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+content-TYPE: text/plain
+X-CUSTOM-header: a
+Content-Length: 5
+
+Hello
\ No newline at end of file
@@ -0,0 +1,9 @@
+>>>
+GET /?p=%EF%BD%9Dx&q=plain HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
@@ -0,0 +1,15 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+Transfer-Encoding: chunked
+
+5
+Hello
+0
+
+
+<<<
+HTTP/1.1 200 OK
+Connection: close
+
+Hello World!
\ No newline at end of file
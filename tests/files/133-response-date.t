@@ -0,0 +1,29 @@
+>>>
+GET /a HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Date: Mon, 31 Aug 2009 20:25:50 GMT
+Content-Length: 5
+
+Hello
+>>>
+GET /b HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 5
+
+Hello
+>>>
+GET /c HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 204 No Content
+
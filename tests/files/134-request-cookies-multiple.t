@@ -0,0 +1,11 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+Cookie: a=1; b=; c
+Cookie: a=2; q="quoted value"
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
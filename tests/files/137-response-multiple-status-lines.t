@@ -0,0 +1,12 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+HTTP/1.1 302 Found
+Location: /login
+Content-Length: 5
+
+Hello
\ No newline at end of file
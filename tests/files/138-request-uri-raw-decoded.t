@@ -0,0 +1,9 @@
+>>>
+GET HTTP://WWW.Example.COM:8080/p%61th/%7Euser?a=%41#fr%61g HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
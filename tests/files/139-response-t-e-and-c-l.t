@@ -0,0 +1,14 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Transfer-Encoding: chunked
+Content-Length: 12
+
+5
+Hello
+0
+
@@ -0,0 +1,9 @@
+>>>
+GET /café?q=%C3%A9 HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
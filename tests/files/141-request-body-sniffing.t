@@ -0,0 +1,11 @@
+>>>
+POST /api HTTP/1.1
+Host: www.example.com
+Content-Type: application/octet-stream
+Content-Length: 26
+
+{"user": "admin", "id": 1}
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
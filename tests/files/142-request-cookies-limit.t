@@ -0,0 +1,10 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+Cookie: c0=v0; c1=v1; c2=v2; c3=v3; c4=v4; c5=v5; c6=v6; c7=v7; c8=v8; c9=v9; c10=v10; c11=v11; c12=v12; c13=v13; c14=v14; c15=v15; c16=v16; c17=v17; c18=v18; c19=v19
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
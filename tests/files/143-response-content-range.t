@@ -0,0 +1,12 @@
+>>>
+GET /file HTTP/1.1
+Host: www.example.com
+Range: bytes=0-4
+
+
+<<<
+HTTP/1.1 206 Partial Content
+Content-Range: bytes 0-4/1234
+Content-Length: 5
+
+Hello
\ No newline at end of file
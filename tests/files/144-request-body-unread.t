@@ -0,0 +1,11 @@
+>>>
+POST /upload HTTP/1.1
+Host: www.example.com
+Content-Length: 10
+Expect: 100-continue
+
+<<<
+HTTP/1.1 413 Payload Too Large
+Content-Length: 0
+Connection: close
+
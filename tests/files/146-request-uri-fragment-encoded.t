@@ -0,0 +1,9 @@
+>>>
+GET /p?a=1#x%26y=2%2526 HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
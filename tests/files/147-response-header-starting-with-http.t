@@ -0,0 +1,11 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+HTTPS-Redirect yes
+Content-Length: 5
+
+Hello
\ No newline at end of file
@@ -0,0 +1,9 @@
+>>>
+GET /p?a=1?b=2 HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
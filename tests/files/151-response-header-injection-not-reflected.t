@@ -0,0 +1,11 @@
+>>>
+GET /?lang=en%0d%0aSet-Cookie:%20session=evil HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Language: en
+Set-Cookie: session=good
+Content-Length: 0
+
@@ -0,0 +1,7 @@
+>>>
+GET /a HTTP/1.1
+Host: www.example.com
+
+GET /b HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,9 @@
+<<<
+HTTP/1.1 200 OK
+Content-Length: 2
+
+OK
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,10 @@
+>>>
+GET /a HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 10
+
+Hell
\ No newline at end of file
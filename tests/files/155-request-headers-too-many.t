@@ -0,0 +1,160 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+X-Header-0: 0
+X-Header-1: 1
+X-Header-2: 2
+X-Header-3: 3
+X-Header-4: 4
+X-Header-5: 5
+X-Header-6: 6
+X-Header-7: 7
+X-Header-8: 8
+X-Header-9: 9
+X-Header-10: 10
+X-Header-11: 11
+X-Header-12: 12
+X-Header-13: 13
+X-Header-14: 14
+X-Header-15: 15
+X-Header-16: 16
+X-Header-17: 17
+X-Header-18: 18
+X-Header-19: 19
+X-Header-20: 20
+X-Header-21: 21
+X-Header-22: 22
+X-Header-23: 23
+X-Header-24: 24
+X-Header-25: 25
+X-Header-26: 26
+X-Header-27: 27
+X-Header-28: 28
+X-Header-29: 29
+X-Header-30: 30
+X-Header-31: 31
+X-Header-32: 32
+X-Header-33: 33
+X-Header-34: 34
+X-Header-35: 35
+X-Header-36: 36
+X-Header-37: 37
+X-Header-38: 38
+X-Header-39: 39
+X-Header-40: 40
+X-Header-41: 41
+X-Header-42: 42
+X-Header-43: 43
+X-Header-44: 44
+X-Header-45: 45
+X-Header-46: 46
+X-Header-47: 47
+X-Header-48: 48
+X-Header-49: 49
+X-Header-50: 50
+X-Header-51: 51
+X-Header-52: 52
+X-Header-53: 53
+X-Header-54: 54
+X-Header-55: 55
+X-Header-56: 56
+X-Header-57: 57
+X-Header-58: 58
+X-Header-59: 59
+X-Header-60: 60
+X-Header-61: 61
+X-Header-62: 62
+X-Header-63: 63
+X-Header-64: 64
+X-Header-65: 65
+X-Header-66: 66
+X-Header-67: 67
+X-Header-68: 68
+X-Header-69: 69
+X-Header-70: 70
+X-Header-71: 71
+X-Header-72: 72
+X-Header-73: 73
+X-Header-74: 74
+X-Header-75: 75
+X-Header-76: 76
+X-Header-77: 77
+X-Header-78: 78
+X-Header-79: 79
+X-Header-80: 80
+X-Header-81: 81
+X-Header-82: 82
+X-Header-83: 83
+X-Header-84: 84
+X-Header-85: 85
+X-Header-86: 86
+X-Header-87: 87
+X-Header-88: 88
+X-Header-89: 89
+X-Header-90: 90
+X-Header-91: 91
+X-Header-92: 92
+X-Header-93: 93
+X-Header-94: 94
+X-Header-95: 95
+X-Header-96: 96
+X-Header-97: 97
+X-Header-98: 98
+X-Header-99: 99
+X-Header-100: 100
+X-Header-101: 101
+X-Header-102: 102
+X-Header-103: 103
+X-Header-104: 104
+X-Header-105: 105
+X-Header-106: 106
+X-Header-107: 107
+X-Header-108: 108
+X-Header-109: 109
+X-Header-110: 110
+X-Header-111: 111
+X-Header-112: 112
+X-Header-113: 113
+X-Header-114: 114
+X-Header-115: 115
+X-Header-116: 116
+X-Header-117: 117
+X-Header-118: 118
+X-Header-119: 119
+X-Header-120: 120
+X-Header-121: 121
+X-Header-122: 122
+X-Header-123: 123
+X-Header-124: 124
+X-Header-125: 125
+X-Header-126: 126
+X-Header-127: 127
+X-Header-128: 128
+X-Header-129: 129
+X-Header-130: 130
+X-Header-131: 131
+X-Header-132: 132
+X-Header-133: 133
+X-Header-134: 134
+X-Header-135: 135
+X-Header-136: 136
+X-Header-137: 137
+X-Header-138: 138
+X-Header-139: 139
+X-Header-140: 140
+X-Header-141: 141
+X-Header-142: 142
+X-Header-143: 143
+X-Header-144: 144
+X-Header-145: 145
+X-Header-146: 146
+X-Header-147: 147
+X-Header-148: 148
+X-Header-149: 149
+Content-Length: 5
+
+hello
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
@@ -0,0 +1,11 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Transfer-Encoding: chunked
+
+5
+hello
@@ -0,0 +1,7 @@
+<<<
+6
+ world
+1
+!
+0
+
@@ -0,0 +1,12 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 100 Continue
+
+HTTP/1.1 200 OK
+Content-Length: 2
+
+ok
\ No newline at end of file
@@ -0,0 +1,7 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+<html>Hello</html>
@@ -0,0 +1,11 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+<html>Hello</html>
+
+>>>
+GET /
+
@@ -0,0 +1,3 @@
+>>>
+GET / HTTP/1.1
+Host: www.exa
\ No newline at end of file
@@ -0,0 +1,6 @@
+>>>
+mple.com
+
+
+<<<
+HTTP/1.1 200
\ No newline at end of file
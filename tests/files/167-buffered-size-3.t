@@ -0,0 +1,4 @@
+<<<
+ OK
+Content-Length: 0
+
@@ -0,0 +1,14 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+Transfer-Encoding: chunked
+
+1a
+abcdefghijklmnopqrstuvwxyz
+0
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
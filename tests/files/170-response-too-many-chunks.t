@@ -0,0 +1,411 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Transfer-Encoding: chunked
+
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+1
+a
+0
+
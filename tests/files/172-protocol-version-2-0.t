@@ -0,0 +1,9 @@
+>>>
+GET / HTTP/2.0
+Host: www.example.com
+
+
+<<<
+HTTP/2.0 200 OK
+Content-Length: 0
+
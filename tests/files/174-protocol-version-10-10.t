@@ -0,0 +1,9 @@
+>>>
+GET / HTTP/10.10
+Host: www.example.com
+
+
+<<<
+HTTP/10.10 200 OK
+Content-Length: 0
+
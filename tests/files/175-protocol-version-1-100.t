@@ -0,0 +1,9 @@
+>>>
+GET / HTTP/1.100
+Host: www.example.com
+
+
+<<<
+HTTP/1.100 200 OK
+Content-Length: 0
+
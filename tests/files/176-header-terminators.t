@@ -0,0 +1,12 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+User-Agent: test
+Accept: */*
+
+
+<<<
+HTTP/1.1 200 OK
+Server: test
+Content-Length: 0
+
@@ -0,0 +1,14 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Transfer-Encoding: chunked
+
+5
+hello
+0
+Content-MD5: XUFAKrxLKna5cZ2REBfFkg==
+
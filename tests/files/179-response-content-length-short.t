@@ -0,0 +1,10 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 10
+
+hello
\ No newline at end of file
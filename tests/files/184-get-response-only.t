@@ -0,0 +1,5 @@
+<<<
+HTTP/1.1 200 OK
+Content-Length: 5
+
+hello
\ No newline at end of file
@@ -0,0 +1,7 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+Upgrade: h2c
+Connection: Upgrade, HTTP2-Settings
+HTTP2-Settings: AAMAAABkAARAAAAAAAIAAAAA
+
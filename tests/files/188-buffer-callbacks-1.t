@@ -0,0 +1,2 @@
+>>>
+GET /inde
\ No newline at end of file
@@ -0,0 +1,4 @@
+>>>
+x.html HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,2 @@
+<<<
+HTTP/1.1 20
\ No newline at end of file
@@ -0,0 +1,9 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+Content-Length: 4
+
+abcdefghij
+GET /next HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,14 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+Expect: 100-continue
+Content-Length: 4
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
+
+>>>
+body
\ No newline at end of file
@@ -0,0 +1,4 @@
+>>>
+TRACE / HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,5 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+Transfer-Encoding: gzip,	chunked
+
@@ -0,0 +1,15 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
+Content-Length: 4
+
+abcd
+<<<
+HTTP/1.1 200 OK
+Content-Length: 5
+
+hello
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,4 @@
+>>>
+GET /One%2f..%5cTwo HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,9 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 0200 OK
+Content-Length: 0
+
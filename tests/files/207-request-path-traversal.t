@@ -0,0 +1,4 @@
+>>>
+GET /a/../b HTTP/1.1
+Host: www.example.com
+
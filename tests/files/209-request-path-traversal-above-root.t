@@ -0,0 +1,4 @@
+>>>
+GET /../../etc/passwd HTTP/1.1
+Host: www.example.com
+
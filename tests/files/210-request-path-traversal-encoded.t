@@ -0,0 +1,4 @@
+>>>
+GET /%2e%2e/etc/passwd HTTP/1.1
+Host: www.example.com
+
@@ -0,0 +1,6 @@
+>>>
+GET / HTTP/1.1
+Host: www.example.com
+X-Folded: a
+ b
+
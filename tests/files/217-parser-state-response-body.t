@@ -0,0 +1,3 @@
+<<<
+
+hel
\ No newline at end of file
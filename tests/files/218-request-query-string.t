@@ -0,0 +1,4 @@
+>>>
+GET /?a=b&c=%20&d HTTP/1.1
+Host: www.example.com
+
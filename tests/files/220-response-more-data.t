@@ -0,0 +1,2 @@
+<<<
+more data
\ No newline at end of file
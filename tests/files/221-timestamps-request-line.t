@@ -0,0 +1,3 @@
+>>>
+POST / HTTP/1.1
+Host: www.example.com
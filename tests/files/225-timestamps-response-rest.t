@@ -0,0 +1,4 @@
+<<<
+Content-Length: 5
+
+world
\ No newline at end of file
@@ -0,0 +1,11 @@
+>>>
+GET / HTTP/1.1
+Host: a
+Connection: close
+
+
+<<<
+HTTP/1.1 200 OK
+Connection: close
+Content-Length: 0
+
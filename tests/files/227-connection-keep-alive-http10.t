@@ -0,0 +1,10 @@
+>>>
+GET / HTTP/1.0
+Connection: Keep-Alive
+
+
+<<<
+HTTP/1.0 200 OK
+Connection: keep-alive
+Content-Length: 0
+
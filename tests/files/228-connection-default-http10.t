@@ -0,0 +1,8 @@
+>>>
+GET / HTTP/1.0
+
+
+<<<
+HTTP/1.0 200 OK
+Content-Length: 0
+
@@ -0,0 +1,9 @@
+>>>
+GET / HTTP/1.1
+Host: a
+
+
+<<<
+HTTP/1.1 200 OK
+Content-Length: 0
+
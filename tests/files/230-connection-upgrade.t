@@ -0,0 +1,12 @@
+>>>
+GET / HTTP/1.1
+Host: a
+Upgrade: websocket
+Connection: keep-alive, Upgrade
+
+
+<<<
+HTTP/1.1 200 OK
+Connection: foo
+Content-Length: 0
+
@@ -0,0 +1,4 @@
+>>>
+GET /short HTTP/1.1
+Host: www.example.com
+
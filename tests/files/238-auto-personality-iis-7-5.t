@@ -0,0 +1,15 @@
+>>>
+GET /a\b HTTP/1.1
+Host: www.example.com
+
+
+<<<
+HTTP/1.1 200 OK
+Server: Microsoft-IIS/7.5
+Content-Length: 0
+
+
+>>>
+GET /a\b HTTP/1.1
+Host: www.example.com
+
@@ -2,25 +2,35 @@
 use chrono::{DateTime, Utc};
 use htp::{
     bstr::Bstr,
-    config::{Config, HtpServerPersonality},
+    config::{
+        Config, ConfigBuilder, ConfigError, HtpDupHeaderPolicy, HtpResponseBodyFallback,
+        HtpServerPersonality, HtpUnwanted,
+    },
     connection::Flags as ConnectionFlags,
     connection_parser::{ConnectionParser, HtpStreamState},
+    decompressors::HtpContentEncoding,
     error::Result,
-    log::{HtpLogCode, HtpLogLevel},
+    log::{HtpLogCode, HtpLogLevel, Log},
+    request::HtpMethod,
     transaction::{
-        Data, HtpAuthType, HtpDataSource, HtpProtocol, HtpRequestProgress, HtpResponseNumber,
-        HtpResponseProgress,
+        Data, HeaderParserFlags, Headers, HtpAuthType, HtpConnectionDisposition, HtpDataSource,
+        HtpHeaderTerminator, HtpProtocol, HtpRequestProgress, HtpResponseNumber,
+        HtpResponseProgress, HtpTransferCoding, Transaction,
     },
-    util::{FlagOperations, HtpFileSource, HtpFlags},
+    util::{FlagOperations, HtpFileSource, HtpFlags, HtpFlags2},
+    HtpStatus,
 };
 use std::{
+    cell::Cell,
     convert::TryInto,
     env,
     iter::IntoIterator,
     net::{IpAddr, Ipv4Addr},
     path::PathBuf,
+    rc::Rc,
     slice,
-    time::SystemTime,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
 };
 
 // import common testing utilities
@@ -158,14 +168,33 @@ impl Test {
         t
     }
     fn run(&mut self, file: &str) -> std::result::Result<(), TestError> {
-        let tv_start = DateTime::<Utc>::from(SystemTime::now());
-        self.connp.open(
-            Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
-            Some(10000),
-            Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
-            Some(80),
-            Some(tv_start),
-        );
+        self.feed(file)?;
+        self.connp
+            .close(Some(DateTime::<Utc>::from(SystemTime::now())));
+        Ok(())
+    }
+
+    /// Plays a test file like run, but leaves the connection open, for tests that act
+    /// on the parser between test files. The connection is opened if it is new.
+    fn feed(&mut self, file: &str) -> std::result::Result<(), TestError> {
+        self.feed_at(file, DateTime::<Utc>::from(SystemTime::now()))
+    }
+
+    /// Plays a test file like feed, with all of its data arriving at the given time.
+    fn feed_at(
+        &mut self,
+        file: &str,
+        tv_start: DateTime<Utc>,
+    ) -> std::result::Result<(), TestError> {
+        if self.connp.request_status == HtpStreamState::NEW {
+            self.connp.open(
+                Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
+                Some(10000),
+                Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
+                Some(80),
+                Some(tv_start),
+            );
+        }
 
         let mut path = self.basedir.clone();
         path.push(file);
@@ -244,25 +273,25 @@ impl Test {
                 return Err(TestError::StreamError);
             }
         }
-        self.connp
-            .close(Some(DateTime::<Utc>::from(SystemTime::now())));
         Ok(())
     }
 }
 
 fn response_body_data(d: &mut Data) -> Result<()> {
     let user_data = unsafe { (*d.tx()).user_data_mut::<MainUserData>().unwrap() };
-    user_data
-        .response_data
-        .push(Bstr::from(d.as_slice().unwrap()));
+    // The end of the body is signalled with a call carrying no data.
+    if let Some(data) = d.as_slice() {
+        user_data.response_data.push(Bstr::from(data));
+    }
     Ok(())
 }
 
 fn request_body_data(d: &mut Data) -> Result<()> {
     let user_data = unsafe { (*d.tx()).user_data_mut::<MainUserData>().unwrap() };
-    user_data
-        .request_data
-        .push(Bstr::from(d.as_slice().unwrap()));
+    // The end of the body is signalled with a call carrying no data.
+    if let Some(data) = d.as_slice() {
+        user_data.request_data.push(Bstr::from(data));
+    }
     Ok(())
 }
 
@@ -1179,7 +1208,7 @@ fn AuthBasicInvalid() {
 
     assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
 
-    assert_eq!(HtpAuthType::BASIC, tx.request_auth_type);
+    assert_eq!(HtpAuthType::UNRECOGNIZED, tx.request_auth_type);
 
     assert!(tx.request_auth_username.is_none());
 
@@ -2138,6 +2167,7 @@ fn CompressedResponseZlibAsDeflate() {
     );
     assert_response_header_eq!(tx, "content-encoding", "deflate");
     assert_eq!(68, tx.response_entity_len);
+    assert!(!tx.flags2.is_set(HtpFlags2::DEFLATE_RAW));
     let user_data = tx.user_data::<MainUserData>().unwrap();
     assert!(user_data.request_data.is_empty());
     assert_eq!(1, user_data.response_data.len());
@@ -3103,3 +3133,2376 @@ fn HttpEvader423() {
     assert_eq!(89, tx.response_message_len);
     assert_eq!(68, tx.response_entity_len);
 }
+
+#[test]
+fn InterestingStatusCodes() {
+    let mut cfg = TestConfig();
+    cfg.set_interesting_status_codes(&[500, 502]);
+    let mut t = Test::new(cfg);
+    assert!(t.run("119-response-status-500.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_status_number.eq_num(500));
+    assert!(tx.flags.is_set(HtpFlags::STATUS_INTERESTING));
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("119-response-status-500.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::STATUS_INTERESTING));
+}
+
+static TX_BYTES_LIMIT_BODY_DATA_CALLS: AtomicUsize = AtomicUsize::new(0);
+static TX_BYTES_LIMIT_BODY_END_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn TxBytesLimitRequestBodyData(d: &mut Data) -> Result<()> {
+    if unsafe { (*d.tx()).index } == 0 {
+        if d.as_slice().is_none() {
+            TX_BYTES_LIMIT_BODY_END_CALLS.fetch_add(1, Ordering::SeqCst);
+        } else {
+            TX_BYTES_LIMIT_BODY_DATA_CALLS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn TxBytesLimit() {
+    let mut cfg = TestConfig();
+    cfg.set_tx_bytes_limit(Some(300));
+    cfg.set_tx_bytes_limit_stop(true);
+    cfg.register_request_body_data(TxBytesLimitRequestBodyData);
+    let mut t = Test::new(cfg);
+    assert!(t.run("03-post-urlencoded.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::TX_BYTES_LIMIT));
+    assert!(tx.accumulated_bytes() > 300);
+    // The body was not processed once the limit was reached.
+    assert!(tx.request_params.get_nocase_nozero("p").is_none());
+    assert_eq!(0, tx.response_entity_len);
+    // No body data reached the callbacks, but the end of the body did.
+    assert_eq!(0, TX_BYTES_LIMIT_BODY_DATA_CALLS.load(Ordering::SeqCst));
+    assert_eq!(1, TX_BYTES_LIMIT_BODY_END_CALLS.load(Ordering::SeqCst));
+    assert_eq!(tx.request_progress, HtpRequestProgress::COMPLETE);
+    assert_eq!(tx.response_progress, HtpResponseProgress::COMPLETE);
+
+    // The next transaction on the connection is unaffected.
+    let tx2 = t.connp.tx(1).unwrap();
+    assert!(!tx2.flags.is_set(HtpFlags::TX_BYTES_LIMIT));
+    assert_eq!(tx2.request_progress, HtpRequestProgress::COMPLETE);
+    assert_eq!(tx2.response_progress, HtpResponseProgress::COMPLETE);
+    assert_eq!(10, tx2.response_entity_len);
+}
+
+#[test]
+fn CompressedResponseRawBodyHook() {
+    let mut cfg = TestConfig();
+    cfg.register_response_body_data_raw(response_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("13-compressed-response-gzip-ct.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(187, tx.response_message_len);
+    assert_eq!(225, tx.response_entity_len);
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut raw = Vec::new();
+    for chunk in &user_data.response_data {
+        raw.extend_from_slice(chunk.as_slice());
+    }
+    // The raw hook sees the compressed bytes, starting with the gzip magic number.
+    assert_eq!(187, raw.len());
+    assert!(raw.starts_with(&[0x1f, 0x8b]));
+}
+
+#[test]
+fn ServerPersonalitySettings() {
+    let mut cfg = Config::default();
+    cfg.set_server_personality(HtpServerPersonality::IIS_7_5)
+        .unwrap();
+    let settings = cfg.get_server_personality_settings();
+    assert_eq!(HtpServerPersonality::IIS_7_5, settings.personality);
+    assert!(settings.backslash_convert_slashes);
+    assert!(settings.u_encoding_decode);
+    assert!(settings.path_separators_decode);
+    assert!(settings.path_separators_compress);
+    assert!(!settings.utf8_convert_bestfit);
+    assert!(!settings.convert_lowercase);
+}
+
+#[test]
+fn ResponseLineSplit() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("120-response-line-split.t").is_ok());
+    assert_eq!(1, t.connp.tx_size());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_line.as_ref().unwrap().eq("HTTP/1.1 200 OK"));
+    assert!(tx.response_status_number.eq_num(200));
+    assert_eq!(HtpProtocol::V1_1, tx.response_protocol_number);
+    assert!(tx.response_message.as_ref().unwrap().eq("OK"));
+    assert!(!tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+    assert_response_header_eq!(tx, "Content-Length", "12");
+    assert_eq!(12, tx.response_entity_len);
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+}
+
+#[test]
+fn ResponseLineSplitAfterCR() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("121-response-line-split-cr.t").is_ok());
+    assert_eq!(1, t.connp.tx_size());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_line.as_ref().unwrap().eq("HTTP/1.1 200 OK"));
+    assert!(tx.response_status_number.eq_num(200));
+    assert!(tx.response_message.as_ref().unwrap().eq("OK"));
+    assert!(!tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+    assert_response_header_eq!(tx, "Content-Length", "12");
+    assert_eq!(12, tx.response_entity_len);
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+}
+
+#[test]
+fn TransferEncodingAllowlist() {
+    let mut cfg = TestConfig();
+    cfg.set_transfer_encoding_allowlist(&["chunked"]);
+    let mut t = Test::new(cfg);
+    assert!(t.run("122-response-transfer-encoding.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::TRANSFER_ENCODING_DISALLOWED));
+    assert_eq!(HtpTransferCoding::CHUNKED, tx.response_transfer_coding);
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::TRANSFER_ENCODING_DISALLOWED));
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+
+    let mut cfg = TestConfig();
+    cfg.set_transfer_encoding_allowlist(&["chunked"]);
+    cfg.set_transfer_encoding_disallowed_error(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("122-response-transfer-encoding.t").is_err());
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::TRANSFER_ENCODING_DISALLOWED));
+}
+
+#[test]
+fn CompressedResponseChunkedRawBody() {
+    let mut cfg = TestConfig();
+    cfg.set_response_body_raw(true);
+    cfg.register_response_body_data(response_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("14-compressed-response-gzip-chunked.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    let raw = tx.response_body_raw.as_ref().unwrap();
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut body = Vec::new();
+    for chunk in &user_data.response_data {
+        body.extend_from_slice(chunk.as_slice());
+    }
+    assert_eq!(159_590, body.len());
+    // The raw body is still chunk-framed and compressed.
+    assert!(raw.len() < body.len());
+    assert!(raw.as_slice() != body.as_slice());
+    assert!(raw.index_of("\r\n").is_some());
+
+    let mut cfg = TestConfig();
+    cfg.set_response_body_raw(true);
+    cfg.set_response_body_raw_limit(100);
+    let mut t = Test::new(cfg);
+    assert!(t.run("14-compressed-response-gzip-chunked.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(100, tx.response_body_raw.as_ref().unwrap().len());
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("14-compressed-response-gzip-chunked.t").is_ok());
+    assert!(t.connp.tx(0).unwrap().response_body_raw.is_none());
+}
+
+#[test]
+fn RequestBareCrLineTerminator() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("123-request-bare-cr.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_BARE_CR_EOL));
+    assert_eq!(1, tx.request_headers.size());
+    assert!(tx.request_headers.get_nocase_nozero("User-Agent").is_none());
+
+    let mut cfg = TestConfig();
+    cfg.set_request_bare_cr_eol(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("123-request-bare-cr.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_BARE_CR_EOL));
+    assert_eq!(3, tx.request_headers.size());
+    assert!(tx
+        .request_headers
+        .get_nocase_nozero("Host")
+        .unwrap()
+        .1
+        .value
+        .eq("www.example.com"));
+    assert!(tx
+        .request_headers
+        .get_nocase_nozero("User-Agent")
+        .unwrap()
+        .1
+        .value
+        .eq("test"));
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+}
+
+#[test]
+fn RequestPathControlChars() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("124-request-path-control-chars.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::PATH_CONTROL_CHARS));
+    assert!(tx
+        .parsed_uri
+        .as_ref()
+        .unwrap()
+        .path
+        .as_ref()
+        .unwrap()
+        .eq("/a\x07b"));
+    assert!(tx.is_complete());
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::PATH_CONTROL_CHARS));
+
+    let mut cfg = TestConfig();
+    cfg.set_path_control_chars_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("124-request-path-control-chars.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::PATH_CONTROL_CHARS));
+}
+
+#[test]
+fn ResponseHeaderNameCanonicalize() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("125-response-header-case.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    let names: Vec<&[u8]> = (&tx.response_headers)
+        .into_iter()
+        .map(|(_, h)| h.name.as_slice())
+        .collect();
+    assert_eq!(
+        vec![
+            b"content-TYPE".as_ref(),
+            b"X-CUSTOM-header".as_ref(),
+            b"Content-Length".as_ref()
+        ],
+        names
+    );
+    assert!((&tx.response_headers)
+        .into_iter()
+        .all(|(_, h)| h.raw_name.is_none()));
+
+    let mut cfg = TestConfig();
+    cfg.set_response_header_name_canonicalize(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("125-response-header-case.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    let names: Vec<(&[u8], &[u8])> = (&tx.response_headers)
+        .into_iter()
+        .map(|(_, h)| (h.name.as_slice(), h.raw_name.as_ref().unwrap().as_slice()))
+        .collect();
+    assert_eq!(
+        vec![
+            (b"Content-Type".as_ref(), b"content-TYPE".as_ref()),
+            (b"X-Custom-Header".as_ref(), b"X-CUSTOM-header".as_ref()),
+            (b"Content-Length".as_ref(), b"Content-Length".as_ref())
+        ],
+        names
+    );
+    assert!(tx.response_content_type.as_ref().unwrap().eq("text/plain"));
+    assert_eq!(5, tx.response_content_length);
+}
+
+#[test]
+fn EmptyRequestOrResponse() {
+    // A connection closed without any data creates no transaction.
+    let mut t = Test::new(TestConfig());
+    t.connp.open(None, None, None, None, None);
+    t.connp.close(None);
+    assert_eq!(0, t.connp.tx_size());
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("126-request-no-response.t").is_ok());
+    assert_eq!(1, t.connp.tx_size());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_EMPTY));
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_EMPTY));
+
+    // Every pipelined request left without a response is flagged.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("152-requests-pipelined-no-response.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+    assert!(t
+        .connp
+        .tx(0)
+        .unwrap()
+        .flags
+        .is_set(HtpFlags::RESPONSE_EMPTY));
+    assert!(t
+        .connp
+        .tx(1)
+        .unwrap()
+        .flags
+        .is_set(HtpFlags::RESPONSE_EMPTY));
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("http-start-from-response.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_EMPTY));
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_EMPTY));
+    let tx = t.connp.tx(1).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_EMPTY));
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_EMPTY));
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_EMPTY));
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_EMPTY));
+}
+
+#[test]
+fn PerDirectionFieldLimits() {
+    let mut cfg = TestConfig();
+    cfg.set_request_field_limit(16);
+    let mut t = Test::new(cfg);
+    assert!(t.run("67-long-request-line.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(HtpRequestProgress::LINE, tx.request_progress);
+
+    let mut cfg = TestConfig();
+    cfg.set_request_field_limit(16);
+    let mut t = Test::new(cfg);
+    assert!(t.run("69-long-response-header.t").is_ok());
+
+    let mut cfg = TestConfig();
+    cfg.set_response_field_limit(18);
+    let mut t = Test::new(cfg);
+    assert!(t.run("69-long-response-header.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(HtpResponseProgress::HEADERS, tx.response_progress);
+
+    let mut cfg = TestConfig();
+    cfg.set_response_field_limit(18);
+    let mut t = Test::new(cfg);
+    assert!(t.run("67-long-request-line.t").is_ok());
+
+    // The single limit applies to both directions.
+    let mut cfg = TestConfig();
+    cfg.set_response_field_limit(100_000);
+    cfg.set_field_limit(16);
+    let mut t = Test::new(cfg);
+    assert!(t.run("69-long-response-header.t").is_err());
+}
+
+#[test]
+fn HeaderCounts() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(1, tx.request_header_count());
+    assert_eq!(5, tx.response_header_count());
+
+    // Repeated headers are folded into a single entry.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("02-header-test-apache2.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(9, tx.request_header_count());
+}
+
+#[test]
+fn QueryUtf8Bestfit() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("127-query-utf8-bestfit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::QUERY_UTF8_BESTFIT));
+    let param = tx.request_params.get_nocase_nozero("p").unwrap();
+    assert!(param.1.value.eq(b"\xef\xbd\x9dx"));
+
+    let mut cfg = TestConfig();
+    cfg.set_utf8_convert_bestfit_query(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("127-query-utf8-bestfit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::QUERY_UTF8_BESTFIT));
+    let param = tx.request_params.get_nocase_nozero("p").unwrap();
+    assert!(param.1.value.eq("}x"));
+    let param = tx.request_params.get_nocase_nozero("q").unwrap();
+    assert!(param.1.value.eq("plain"));
+}
+
+#[test]
+fn TransferCodingMismatch() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("128-chunked-request-close-response.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(
+        (HtpTransferCoding::CHUNKED, HtpTransferCoding::IDENTITY),
+        tx.transfer_codings()
+    );
+    assert!(tx.flags.is_set(HtpFlags::TRANSFER_CODING_MISMATCH));
+    assert_eq!(12, tx.response_entity_len);
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(
+        (HtpTransferCoding::NO_BODY, HtpTransferCoding::IDENTITY),
+        tx.transfer_codings()
+    );
+    assert!(!tx.flags.is_set(HtpFlags::TRANSFER_CODING_MISMATCH));
+}
+
+#[test]
+fn ResponseBeforeRequestStart() {
+    // Transactions are created on demand, so response data arriving before any request
+    // data resynchronizes on a synthetic transaction instead of failing the stream.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("153-response-before-request.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_EMPTY));
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+
+    // The next request is tracked in a new transaction.
+    assert_eq!(2, t.connp.tx_size());
+    let tx = t.connp.tx(1).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_EMPTY));
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+}
+
+#[test]
+fn ConfigBuilderBuild() {
+    let cfg = ConfigBuilder::new()
+        .server_personality(HtpServerPersonality::APACHE_2)
+        .parse_urlencoded(true)
+        .field_limit(16)
+        .build()
+        .unwrap();
+    assert_eq!(HtpServerPersonality::APACHE_2, cfg.server_personality);
+    assert!(cfg.parse_urlencoded);
+    let mut t = Test::new(cfg);
+    assert!(t.run("67-long-request-line.t").is_err());
+
+    let cfg = ConfigBuilder::new()
+        .server_personality(HtpServerPersonality::APACHE_2)
+        .parse_urlencoded(true)
+        .build()
+        .unwrap();
+    let mut t = Test::new(cfg);
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.request_params.get_nocase_nozero("p").is_some());
+
+    assert_eq!(
+        Err(ConfigError::UnsupportedPersonality(
+            HtpServerPersonality::IIS_4_0
+        )),
+        ConfigBuilder::new()
+            .server_personality(HtpServerPersonality::IIS_4_0)
+            .build()
+            .map(|_| ())
+    );
+    assert_eq!(
+        Err(ConfigError::InvalidReplacementByte(0x80)),
+        ConfigBuilder::new()
+            .bestfit_replacement_byte(0x80)
+            .build()
+            .map(|_| ())
+    );
+}
+
+#[test]
+fn HeadersIter() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+
+    let iter = tx.response_headers_iter();
+    assert_eq!(tx.response_header_count(), iter.len());
+    let names: Vec<&[u8]> = iter.map(|(name, _)| name.as_slice()).collect();
+    assert_eq!(
+        vec![
+            b"Date".as_ref(),
+            b"Server".as_ref(),
+            b"Connection".as_ref(),
+            b"Content-Type".as_ref(),
+            b"Content-Length".as_ref()
+        ],
+        names
+    );
+
+    let mut iter = tx.request_headers_iter();
+    assert_eq!(1, iter.len());
+    let (name, header) = iter.next().unwrap();
+    assert!(name.eq("User-Agent"));
+    assert!(header.value.eq("Mozilla"));
+    assert!(iter.next().is_none());
+    assert_eq!(0, iter.len());
+}
+
+#[test]
+fn HeaderValueSoftLimit() {
+    let mut cfg = TestConfig();
+    cfg.set_header_value_soft_limit(Some(100));
+    let mut t = Test::new(cfg);
+    assert!(t.run("129-header-value-soft-limit.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+
+    // At the limit on the request, beyond it on the response.
+    let tx = t.connp.tx(0).unwrap();
+    let h = tx.request_headers.get_nocase_nozero("x-long").unwrap().1;
+    assert_eq!(100, h.value.len());
+    assert!(!h.flags.is_set(HtpFlags::FIELD_VALUE_SOFT_LIMIT));
+    let h = tx.response_headers.get_nocase_nozero("x-long").unwrap().1;
+    assert_eq!(101, h.value.len());
+    assert!(h.flags.is_set(HtpFlags::FIELD_VALUE_SOFT_LIMIT));
+    assert!(tx.flags.is_set(HtpFlags::FIELD_VALUE_SOFT_LIMIT));
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+
+    // Beyond the limit on the request.
+    let tx = t.connp.tx(1).unwrap();
+    let h = tx.request_headers.get_nocase_nozero("x-long").unwrap().1;
+    assert_eq!(101, h.value.len());
+    assert!(h.flags.is_set(HtpFlags::FIELD_VALUE_SOFT_LIMIT));
+    assert!(tx.flags.is_set(HtpFlags::FIELD_VALUE_SOFT_LIMIT));
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+    assert!(!tx
+        .response_headers
+        .get_nocase_nozero("x-long")
+        .unwrap()
+        .1
+        .flags
+        .is_set(HtpFlags::FIELD_VALUE_SOFT_LIMIT));
+}
+
+#[cfg(feature = "brotli")]
+#[test]
+fn CompressedResponseBrotli() {
+    let mut cfg = TestConfig();
+    cfg.register_response_body_data(response_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("130-compressed-response-brotli.t").is_ok());
+    assert_eq!(1, t.connp.tx_size());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    assert_eq!(HtpContentEncoding::BROTLI, tx.response_content_encoding);
+    assert_eq!(19, tx.response_message_len);
+    assert_eq!(15, tx.response_entity_len);
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut body = Vec::new();
+    for chunk in &user_data.response_data {
+        body.extend_from_slice(chunk.as_slice());
+    }
+    assert_eq!(b"Hello, brotli!\n".as_ref(), body.as_slice());
+}
+
+#[cfg(not(feature = "brotli"))]
+#[test]
+fn CompressedResponseBrotliPassthrough() {
+    let mut cfg = TestConfig();
+    cfg.register_response_body_data(response_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("130-compressed-response-brotli.t").is_ok());
+    assert_eq!(1, t.connp.tx_size());
+
+    // Without brotli support the encoding is still reported, but the body is passed
+    // through undecoded.
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    assert_eq!(HtpContentEncoding::BROTLI, tx.response_content_encoding);
+    assert_eq!(19, tx.response_message_len);
+    assert_eq!(19, tx.response_entity_len);
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut body = Vec::new();
+    for chunk in &user_data.response_data {
+        body.extend_from_slice(chunk.as_slice());
+    }
+    assert_eq!(
+        b"\xe0\x00\x10Hello, brotli!\n\x03".as_ref(),
+        body.as_slice()
+    );
+}
+
+fn response_complete_mark(tx: &mut Transaction) -> Result<()> {
+    tx.set_user_data(Box::new(true));
+    Ok(())
+}
+
+#[test]
+fn ResponseForceComplete() {
+    let mut cfg = TestConfig();
+    cfg.register_response_complete(response_complete_mark);
+    let mut t = Test::new(cfg);
+    assert!(t.feed("154-response-force-complete.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(HtpResponseProgress::BODY, tx.response_progress);
+    assert!(tx.user_data::<bool>().is_none());
+
+    assert!(t.connp.response_force_complete().is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+    assert!(tx.is_complete());
+    assert_eq!(Some(&true), tx.user_data::<bool>());
+    assert_eq!(4, tx.response_entity_len);
+    assert_eq!(HtpStreamState::DATA, t.connp.response_status);
+
+    // Nothing left to complete, and no transaction is created for it.
+    assert!(t.connp.response_force_complete().is_ok());
+    assert!(t.connp.request_force_complete().is_ok());
+    assert_eq!(1, t.connp.tx_size());
+
+    // Parsing continues with the next transaction.
+    assert!(t.feed("01-get.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx.is_complete());
+    assert_eq!(12, tx.response_entity_len);
+}
+
+#[test]
+fn CompressedResponseGzipDeflate() {
+    let mut cfg = TestConfig();
+    cfg.register_response_body_data(response_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("131-compressed-response-gzip-deflate.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    assert_eq!(
+        vec![HtpContentEncoding::GZIP, HtpContentEncoding::DEFLATE],
+        tx.response_content_encodings
+    );
+    assert_eq!(49, tx.response_message_len);
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut body = Vec::new();
+    for chunk in &user_data.response_data {
+        body.extend_from_slice(chunk.as_slice());
+    }
+    assert_eq!(b"Hello, doubly encoded world!\n".as_ref(), body.as_slice());
+
+    // The layer limit caps the length of the chain.
+    let mut cfg = TestConfig();
+    cfg.compression_options.set_layer_limit(Some(1));
+    let mut t = Test::new(cfg);
+    assert!(t.run("131-compressed-response-gzip-deflate.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(
+        vec![HtpContentEncoding::GZIP],
+        tx.response_content_encodings
+    );
+    assert!(t
+        .connp
+        .conn
+        .get_logs()
+        .iter()
+        .any(|log| log.msg.code == HtpLogCode::RESPONSE_TOO_MANY_ENCODING_LAYERS));
+}
+
+#[test]
+fn SerializeHead() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(
+        b"GET /?p=%20 HTTP/1.0\r\nUser-Agent: Mozilla\r\n\r\n".as_ref(),
+        tx.serialize_request_head().as_slice()
+    );
+    assert_eq!(
+        b"HTTP/1.0 200 OK\r\n\
+        Date: Mon, 31 Aug 2009 20:25:50 GMT\r\n\
+        Server: Apache\r\n\
+        Connection: close\r\n\
+        Content-Type: text/html\r\n\
+        Content-Length: 12\r\n\r\n"
+            .as_ref(),
+        tx.serialize_response_head().as_slice()
+    );
+
+    // Canonicalized names are emitted as seen on the wire.
+    let mut cfg = TestConfig();
+    cfg.set_response_header_name_canonicalize(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("125-response-header-case.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(
+        b"HTTP/1.1 200 OK\r\n\
+        content-TYPE: text/plain\r\n\
+        X-CUSTOM-header: a\r\n\
+        Content-Length: 5\r\n\r\n"
+            .as_ref(),
+        tx.serialize_response_head().as_slice()
+    );
+}
+
+#[test]
+fn PipelinedCompletionOrder() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("07-pipelined-connection.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+
+    // Both requests complete before the responses are seen.
+    let tx1 = t.connp.tx(0).unwrap();
+    let tx2 = t.connp.tx(1).unwrap();
+    assert_eq!(Some(1), tx1.request_completion_seq());
+    assert_eq!(Some(2), tx2.request_completion_seq());
+    assert_eq!(Some(3), tx1.response_completion_seq());
+    assert_eq!(Some(4), tx2.response_completion_seq());
+    assert_eq!(4, t.connp.completion_count);
+}
+
+#[test]
+fn QueryFragment() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("132-request-uri-fragment.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    let parsed_uri = tx.parsed_uri.as_ref().unwrap();
+    assert!(parsed_uri.query.as_ref().unwrap().eq("a=1"));
+    assert!(parsed_uri.fragment.as_ref().unwrap().eq("frag"));
+    assert_eq!(1, tx.request_params.size());
+    let param = tx.request_params.get_nocase_nozero("a").unwrap().1;
+    assert!(param.value.eq("1"));
+
+    let mut cfg = TestConfig();
+    cfg.set_query_fragment_included(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("132-request-uri-fragment.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(1, tx.request_params.size());
+    let param = tx.request_params.get_nocase_nozero("a").unwrap().1;
+    assert!(param.value.eq("1#frag"));
+
+    // An encoded separator in the fragment is decoded only once.
+    let mut cfg = TestConfig();
+    cfg.set_query_fragment_included(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("146-request-uri-fragment-encoded.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(1, tx.request_params.size());
+    let param = tx.request_params.get_nocase_nozero("a").unwrap().1;
+    assert!(param.value.eq("1#x&y=2%26"));
+}
+
+#[test]
+fn ResponseDateMissing() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("133-response-date.t").is_ok());
+    assert_eq!(3, t.connp.tx_size());
+    assert!(t.connp.tx(0).unwrap().response_has_date());
+    let tx = t.connp.tx(1).unwrap();
+    assert!(!tx.response_has_date());
+    // Not flagged unless configured.
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_DATE_MISSING));
+
+    let mut cfg = TestConfig();
+    cfg.set_response_date_required(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("133-response-date.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_DATE_MISSING));
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_DATE_MISSING));
+    // A response without a body does not need one.
+    let tx = t.connp.tx(2).unwrap();
+    assert!(!tx.response_has_date());
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_DATE_MISSING));
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+}
+
+#[test]
+fn RequestCookiesMultipleHeaders() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("134-request-cookies-multiple.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    let cookies = tx.request_cookies();
+    assert_eq!(5, cookies.size());
+    let expected: [(&str, &str); 5] = [
+        ("a", "1"),
+        ("b", ""),
+        ("c", ""),
+        ("a", "2"),
+        ("q", "\"quoted value\""),
+    ];
+    for (i, (name, value)) in expected.iter().enumerate() {
+        let (n, v) = &cookies[i];
+        assert!(n.eq(*name));
+        assert!(v.eq(*value));
+    }
+    // Lookups return the first cookie with a repeated name.
+    assert!(cookies.get_nocase("a").unwrap().1.eq("1"));
+
+    let mut cfg = TestConfig();
+    cfg.set_parse_request_cookies(false);
+    let mut t = Test::new(cfg);
+    assert!(t.run("134-request-cookies-multiple.t").is_ok());
+    assert_eq!(0, t.connp.tx(0).unwrap().request_cookies().size());
+}
+
+#[test]
+fn HeadersTooMany() {
+    // 150 headers, followed by a Content-Length header: a framing header past the
+    // limit must never be dropped silently.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("155-request-headers-too-many.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(152, tx.request_header_count());
+    assert!(!tx.flags.is_set(HtpFlags::HEADERS_TOO_MANY));
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+
+    let mut cfg = TestConfig();
+    cfg.set_number_headers_limit(100);
+    let mut t = Test::new(cfg);
+    assert!(t.run("155-request-headers-too-many.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::HEADERS_TOO_MANY));
+    assert_eq!(100, tx.request_header_count());
+    assert!(tx
+        .request_headers
+        .get_nocase_nozero("content-length")
+        .is_none());
+    assert_ne!(HtpRequestProgress::COMPLETE, tx.request_progress);
+    let errors = t
+        .connp
+        .conn
+        .get_logs()
+        .iter()
+        .filter(|log| log.msg.code == HtpLogCode::HEADERS_TOO_MANY)
+        .count();
+    assert_eq!(1, errors);
+
+    // The limit applies to responses as well, and counts across data chunks.
+    let mut cfg = TestConfig();
+    cfg.set_number_headers_limit(100);
+    let mut t = Test::new(cfg);
+    assert!(t.run("156-response-headers-too-many.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::HEADERS_TOO_MANY));
+    assert_eq!(100, tx.response_header_count());
+}
+
+#[test]
+fn HeaderFoldingLimit() {
+    let mut cfg = TestConfig();
+    cfg.set_header_folding_limit(10);
+    let mut t = Test::new(cfg);
+    assert!(t.run("136-request-header-folding-limit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::FIELD_FOLDING_LIMIT));
+    let h = tx.request_headers.get_nocase_nozero("x-folded").unwrap().1;
+    assert!(h.flags.is_set(HtpFlags::FIELD_FOLDING_LIMIT));
+    assert!(h
+        .value
+        .eq("start line0 line1 line2 line3 line4 line5 line6 line7 line8 line9"));
+    assert_eq!(2, tx.request_header_count());
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+}
+
+#[test]
+fn StreamStatusAccessors() {
+    use htp::c_api::connection_parser::{htp_connp_get_in_status, htp_connp_get_out_status};
+
+    // Leave the connection open, as closing it ends the tunnel.
+    let mut t = Test::new(TestConfig());
+    assert!(t.feed("157-connect-tunnel.t").is_ok());
+    unsafe {
+        assert_eq!(HtpStreamState::TUNNEL, htp_connp_get_in_status(&t.connp));
+        assert_eq!(HtpStreamState::TUNNEL, htp_connp_get_out_status(&t.connp));
+    }
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("63-post-chunked-invalid-1.t").is_err());
+    unsafe {
+        assert_eq!(HtpStreamState::ERROR, htp_connp_get_in_status(&t.connp));
+        assert_eq!(
+            HtpStreamState::ERROR,
+            htp_connp_get_in_status(std::ptr::null())
+        );
+        assert_eq!(
+            HtpStreamState::ERROR,
+            htp_connp_get_out_status(std::ptr::null())
+        );
+    }
+}
+
+#[test]
+fn ResponseMultipleStatusLines() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("137-response-multiple-status-lines.t").is_ok());
+    assert_eq!(1, t.connp.tx_size());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_MULTIPLE_STATUS_LINES));
+    assert!(tx.response_status_number.eq_num(200));
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+    let warnings = t
+        .connp
+        .conn
+        .get_logs()
+        .iter()
+        .filter(|log| log.msg.code == HtpLogCode::RESPONSE_MULTIPLE_STATUS_LINES)
+        .count();
+    assert_eq!(1, warnings);
+
+    let mut cfg = TestConfig();
+    cfg.set_response_multiple_status_lines_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("137-response-multiple-status-lines.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_MULTIPLE_STATUS_LINES));
+
+    // A header line without a colon that merely starts with "http" is not a status line.
+    let mut cfg = TestConfig();
+    cfg.set_response_multiple_status_lines_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("147-response-header-starting-with-http.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_MULTIPLE_STATUS_LINES));
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+}
+
+#[test]
+fn RawAndDecodedUri() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("138-request-uri-raw-decoded.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+
+    let raw = tx.raw_uri().unwrap();
+    assert!(raw.scheme().unwrap().eq("HTTP"));
+    assert!(raw.hostname().unwrap().eq("WWW.Example.COM"));
+    assert!(raw.port().unwrap().eq("8080"));
+    assert!(raw.path().unwrap().eq("/p%61th/%7Euser"));
+    assert!(raw.query().unwrap().eq("a=%41"));
+    assert!(raw.fragment().unwrap().eq("fr%61g"));
+
+    let decoded = tx.decoded_uri().unwrap();
+    assert!(decoded.scheme.as_ref().unwrap().eq("http"));
+    assert!(decoded.hostname.as_ref().unwrap().eq("www.example.com"));
+    assert_eq!(Some(8080), decoded.port_number);
+    assert!(decoded.path.as_ref().unwrap().eq("/path/~user"));
+    assert!(decoded.fragment.as_ref().unwrap().eq("frag"));
+}
+
+#[test]
+fn TransferEncodingContentLengthConflict() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("37-invalid-request-2-t-e-and-c-l.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_SMUGGLING_TE_CL));
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_SMUGGLING_TE_CL));
+    assert!(tx.has_te_cl_conflict());
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("139-response-t-e-and-c-l.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_SMUGGLING_TE_CL));
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_SMUGGLING_TE_CL));
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_SMUGGLING));
+    assert!(tx.has_te_cl_conflict());
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    assert!(!t.connp.tx(0).unwrap().has_te_cl_conflict());
+}
+
+#[test]
+fn RequestLineHighBit() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("140-request-line-high-bit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_LINE_HIGH_BIT));
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+
+    // Percent-encoded bytes are not flagged.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("138-request-uri-raw-decoded.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_LINE_HIGH_BIT));
+
+    let mut cfg = TestConfig();
+    cfg.set_request_line_high_bit_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("140-request-line-high-bit.t").is_err());
+}
+
+#[test]
+fn RequestBodyDataRawHook() {
+    let mut cfg = TestConfig();
+    cfg.set_request_decompression(true);
+    cfg.register_request_body_data_raw(request_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("116-request-compression.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(1355, tx.request_message_len);
+    assert_eq!(2614, tx.request_entity_len);
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut raw = Vec::new();
+    for chunk in &user_data.request_data {
+        raw.extend_from_slice(chunk.as_slice());
+    }
+    // The raw hook sees the compressed bytes, starting with the gzip magic number.
+    assert_eq!(1355, raw.len());
+    assert!(raw.starts_with(&[0x1f, 0x8b]));
+}
+
+#[test]
+fn RequestBodySniffing() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("141-request-body-sniffing.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.request_content_type_sniffed.is_none());
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_CONTENT_TYPE_MISMATCH));
+
+    let mut cfg = TestConfig();
+    cfg.set_request_body_sniffing(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("141-request-body-sniffing.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx
+        .request_content_type
+        .as_ref()
+        .unwrap()
+        .eq("application/octet-stream"));
+    assert!(tx
+        .request_content_type_sniffed
+        .as_ref()
+        .unwrap()
+        .eq("application/json"));
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_CONTENT_TYPE_MISMATCH));
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+}
+
+#[test]
+fn ResponseSkipBody() {
+    let mut t = Test::new_with_callbacks();
+    assert!(t.feed("158-response-skip-body.t").is_ok());
+    t.connp.response_skip_body();
+    let body = vec![b'a'; 65536];
+    for _ in 0..15 {
+        assert_eq!(
+            HtpStreamState::DATA,
+            t.connp.response_data((&body).into(), None)
+        );
+    }
+    // A gap is skipped just like data.
+    assert_eq!(
+        HtpStreamState::DATA,
+        t.connp.response_data(65536usize.into(), None)
+    );
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_body_skipped);
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+    assert_eq!(1048576, tx.response_message_len);
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    assert!(user_data.response_data.is_empty());
+
+    // Without a response in progress, nothing is skipped and no transaction is created.
+    t.connp.response_skip_body();
+    assert_eq!(1, t.connp.tx_size());
+    let mut t = Test::new(TestConfig());
+    t.connp.response_skip_body();
+    assert_eq!(0, t.connp.tx_size());
+}
+
+static SKIP_BODY_RAW_DATA_CALLS: AtomicUsize = AtomicUsize::new(0);
+static SKIP_BODY_RAW_END_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn SkipBodyRawData(d: &mut Data) -> Result<()> {
+    if d.as_slice().is_none() {
+        SKIP_BODY_RAW_END_CALLS.fetch_add(1, Ordering::SeqCst);
+    } else {
+        SKIP_BODY_RAW_DATA_CALLS.fetch_add(1, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+#[test]
+fn ResponseSkipBodyChunked() {
+    let mut cfg = TestConfig();
+    cfg.register_response_body_data(response_body_data);
+    cfg.register_response_body_data_raw(SkipBodyRawData);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.feed("159-response-skip-body-chunked.t").is_ok());
+    t.connp.response_skip_body();
+    assert!(t.feed("160-response-skip-body-chunked-rest.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_body_skipped);
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+    assert_eq!(12, tx.response_message_len);
+    // Only the data seen before the skip reached the callbacks.
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    assert_eq!(1, user_data.response_data.len());
+    assert!(user_data.response_data[0].eq("hello"));
+    assert_eq!(1, SKIP_BODY_RAW_DATA_CALLS.load(Ordering::SeqCst));
+    // The end of the body is still signalled.
+    assert_eq!(1, SKIP_BODY_RAW_END_CALLS.load(Ordering::SeqCst));
+}
+
+#[test]
+fn RequestCookiesLimit() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("142-request-cookies-limit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(20, tx.request_cookies().size());
+    assert!(!tx.flags.is_set(HtpFlags::COOKIES_LIMIT));
+
+    let mut cfg = TestConfig();
+    cfg.set_cookies_limit(5);
+    let mut t = Test::new(cfg);
+    assert!(t.run("142-request-cookies-limit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(5, tx.request_cookies().size());
+    assert!(tx.request_cookies().get_nocase("c4").is_some());
+    assert!(tx.request_cookies().get_nocase("c5").is_none());
+    assert!(tx.flags.is_set(HtpFlags::COOKIES_LIMIT));
+
+    // Each cookie is 4 bytes long, the limit is crossed by the fourth one.
+    let mut cfg = TestConfig();
+    cfg.set_cookies_size_limit(15);
+    let mut t = Test::new(cfg);
+    assert!(t.run("142-request-cookies-limit.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(3, tx.request_cookies().size());
+    assert!(tx.flags.is_set(HtpFlags::COOKIES_LIMIT));
+}
+
+#[test]
+fn ResponseContentRange() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("143-response-content-range.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    let range = tx.response_content_range.unwrap();
+    assert_eq!(Some(0), range.start);
+    assert_eq!(Some(4), range.end);
+    assert_eq!(Some(1234), range.total);
+    assert_eq!(5, tx.response_content_length);
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    assert!(t.connp.tx(0).unwrap().response_content_range.is_none());
+}
+
+fn request_body_data_bytes(d: &mut Data) -> Result<()> {
+    let user_data = unsafe { (*d.tx()).user_data_mut::<MainUserData>().unwrap() };
+    user_data.request_data.push(Bstr::from(d.as_bytes()));
+    Ok(())
+}
+
+#[test]
+fn RequestBodyDataAsBytes() {
+    let mut cfg = TestConfig();
+    cfg.register_request_body_data(request_body_data_bytes);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    assert!(t.run("04-post-urlencoded-chunked.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut body = Vec::new();
+    for chunk in &user_data.request_data {
+        body.extend_from_slice(chunk.as_slice());
+    }
+    assert_eq!(b"p=0123456789", body.as_slice());
+    // The final call carries no data and is seen as an empty slice.
+    assert_eq!(3, user_data.request_data.len());
+    assert!(user_data.request_data.last().unwrap().is_empty());
+}
+
+#[test]
+fn RequestBodyUnread() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("144-request-body-unread.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::REQUEST_BODY_UNREAD));
+    assert!(!tx.flags.is_set(HtpFlags::RESPONSE_CONTINUE_WITHOUT_BODY));
+    assert_eq!(0, tx.request_entity_len);
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("03-post-urlencoded.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_BODY_UNREAD));
+}
+
+#[test]
+fn ResponseContinueWithoutBody() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("161-response-continue-without-body.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_CONTINUE_WITHOUT_BODY));
+    assert!(!tx.flags.is_set(HtpFlags::REQUEST_BODY_UNREAD));
+    assert!(tx.is_complete());
+}
+
+#[test]
+fn Http_0_9_Disallowed() {
+    let mut cfg = TestConfig();
+    cfg.set_allow_http_0_9(false);
+    let mut t = Test::new(cfg);
+    assert!(t.run("21-http09.t").is_err());
+    assert_eq!(HtpStreamState::ERROR, t.connp.request_status);
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_protocol_0_9);
+}
+
+#[test]
+fn ResponseHttp_0_9() {
+    // Allowed by default: the response line is treated as the start of the body.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("162-response-http09.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_status.is_none());
+    assert!(tx.response_entity_len > 0);
+
+    let mut cfg = TestConfig();
+    cfg.set_allow_http_0_9(false);
+    let mut t = Test::new(cfg);
+    assert!(t.run("162-response-http09.t").is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(0, tx.response_entity_len);
+    assert!(tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+}
+
+#[test]
+fn Http_0_9_ResponseBodyFallback() {
+    // Disallowing HTTP/0.9 never lets data fall back to the response body.
+    let mut cfg = TestConfig();
+    cfg.set_allow_http_0_9(false);
+    assert_eq!(HtpResponseBodyFallback::NEVER, cfg.response_body_fallback);
+    cfg.set_allow_http_0_9(true);
+    assert_eq!(HtpResponseBodyFallback::ALWAYS, cfg.response_body_fallback);
+
+    // A fallback set afterwards decides for responses, while HTTP/0.9 requests are
+    // still rejected.
+    let cfg = ConfigBuilder::new()
+        .allow_http_0_9(false)
+        .response_body_fallback(HtpResponseBodyFallback::ONLY_FIRST_LINE)
+        .build()
+        .unwrap();
+    assert!(!cfg.allow_http_0_9);
+    let mut t = Test::new(cfg);
+    // The response falls back to the body, the HTTP/0.9 request that follows fails.
+    assert!(t.run("163-response-http09-request-http09.t").is_err());
+    assert!(t.connp.tx(0).unwrap().response_entity_len > 0);
+    assert_eq!(HtpStreamState::ERROR, t.connp.request_status);
+}
+
+fn response_body_fallback_test(
+    fallback: HtpResponseBodyFallback,
+    file: &str,
+) -> (Test, std::result::Result<(), TestError>) {
+    let mut cfg = TestConfig();
+    cfg.set_response_body_fallback(fallback);
+    let mut t = Test::new(cfg);
+    let rc = t.run(file);
+    (t, rc)
+}
+
+#[test]
+fn ResponseBodyFallback() {
+    // An invalid response line.
+    let file = "162-response-http09.t";
+    for fallback in &[
+        HtpResponseBodyFallback::ALWAYS,
+        HtpResponseBodyFallback::ONLY_FIRST_LINE,
+    ] {
+        let (t, rc) = response_body_fallback_test(*fallback, file);
+        assert!(rc.is_ok());
+        let tx = t.connp.tx(0).unwrap();
+        assert!(tx.response_entity_len > 0);
+        assert!(!tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+    }
+    let (t, rc) = response_body_fallback_test(HtpResponseBodyFallback::NEVER, file);
+    assert!(rc.is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(0, tx.response_entity_len);
+    assert!(tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+
+    // Unexpected data after a complete response.
+    let file = "164-response-data-after-complete.t";
+    let (t, rc) = response_body_fallback_test(HtpResponseBodyFallback::ALWAYS, file);
+    assert!(rc.is_ok());
+    assert!(t.connp.tx(0).unwrap().response_entity_len > 2);
+
+    let (t, rc) = response_body_fallback_test(HtpResponseBodyFallback::ONLY_FIRST_LINE, file);
+    assert!(rc.is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(2, tx.response_entity_len);
+    assert!(!tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+
+    let (t, rc) = response_body_fallback_test(HtpResponseBodyFallback::NEVER, file);
+    assert!(rc.is_err());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(2, tx.response_entity_len);
+    assert!(tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+}
+
+#[test]
+fn BufferedSize() {
+    let mut t = Test::new(TestConfig());
+    assert_eq!(0, t.connp.request_buffered_size());
+    // A partial request header line.
+    assert!(t.feed("165-buffered-size-1.t").is_ok());
+    assert!(t.connp.request_buffered_size() > 0);
+    assert_eq!(0, t.connp.response_buffered_size());
+    // The rest of the request, and a partial response line.
+    assert!(t.feed("166-buffered-size-2.t").is_ok());
+    assert_eq!(0, t.connp.request_buffered_size());
+    assert_eq!(b"HTTP/1.1 200".len(), t.connp.response_buffered_size());
+
+    // The partial response line must not be dropped.
+    assert!(!t.connp.clear_response_buffer());
+    assert_eq!(b"HTTP/1.1 200".len(), t.connp.response_buffered_size());
+    assert!(t.feed("167-buffered-size-3.t").is_ok());
+    assert_eq!(0, t.connp.response_buffered_size());
+    assert!(t.connp.clear_response_buffer());
+    assert_eq!(0, t.connp.response_buffered_size());
+}
+
+#[test]
+fn DecompressionRatio() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("145-compressed-response-ratio.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(tx.response_message_len as u64, tx.response_compressed_len);
+    assert_eq!(65536, tx.response_decompressed_len);
+    assert!(tx.decompression_ratio().unwrap() > 100.0);
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("01-get.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(0, tx.response_compressed_len);
+    assert!(tx.decompression_ratio().is_none());
+}
+
+#[test]
+fn ChunkedLengthWhitespace() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("168-request-chunked-length.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(26, tx.request_entity_len);
+    assert!(!tx.flags.is_set(HtpFlags::CHUNKED_LENGTH_WHITESPACE));
+
+    // The chunk size "1a " has trailing whitespace.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("169-request-chunked-length-whitespace.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(26, tx.request_entity_len);
+    assert!(tx.flags.is_set(HtpFlags::CHUNKED_LENGTH_WHITESPACE));
+
+    let mut cfg = TestConfig();
+    cfg.set_chunked_length_whitespace_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("169-request-chunked-length-whitespace.t").is_err());
+}
+
+#[test]
+fn ResponseTooManyChunks() {
+    let mut cfg = TestConfig();
+    cfg.set_max_chunks(100);
+    cfg.register_response_body_data(response_body_data);
+    let mut t = Test::new(cfg);
+    t.connp
+        .response_mut()
+        .set_user_data(Box::new(MainUserData::new()));
+    // 200 chunks of one byte each.
+    assert!(t.run("170-response-too-many-chunks.t").is_err());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_TOO_MANY_CHUNKS));
+    assert_eq!(101, tx.response_chunk_count);
+    // Only the chunks within the limit reach the body; the rest is not parsed as
+    // the start of another response.
+    let user_data = tx.user_data::<MainUserData>().unwrap();
+    let mut body = Vec::new();
+    for chunk in &user_data.response_data {
+        body.extend_from_slice(chunk.as_slice());
+    }
+    assert_eq!(vec![b'a'; 100], body);
+    assert_eq!(100, tx.response_entity_len);
+    assert_eq!(HtpResponseProgress::BODY, tx.response_progress);
+    assert_eq!(1, t.connp.tx_size());
+    assert!(!t
+        .connp
+        .conn
+        .get_logs()
+        .iter()
+        .any(|log| log.msg.code == HtpLogCode::RESPONSE_BODY_UNEXPECTED));
+}
+
+#[test]
+fn ProtocolVersion() {
+    // The request and the response use the same protocol.
+    let inputs = vec![
+        ("171-protocol-version-1-1.t", Some((1, 1)), false),
+        ("172-protocol-version-2-0.t", Some((2, 0)), false),
+        ("173-protocol-version-1-10.t", Some((1, 10)), false),
+        ("174-protocol-version-10-10.t", Some((10, 10)), false),
+        ("175-protocol-version-1-100.t", None, true),
+    ];
+    for (file, expected, invalid) in inputs {
+        let mut t = Test::new(TestConfig());
+        assert!(t.run(file).is_ok());
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(expected, tx.request_protocol_version());
+        assert_eq!(expected, tx.response_protocol_version());
+        assert_eq!(invalid, tx.flags.is_set(HtpFlags::PROTOCOL_INVALID));
+    }
+}
+
+#[test]
+fn HeaderTerminators() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("176-header-terminators.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    let terminator =
+        |headers: &Headers, name: &str| headers.get_nocase(name).unwrap().1.terminator();
+    assert_eq!(
+        HtpHeaderTerminator::CRLF,
+        terminator(&tx.request_headers, "host")
+    );
+    assert_eq!(
+        HtpHeaderTerminator::LF,
+        terminator(&tx.request_headers, "user-agent")
+    );
+    assert_eq!(
+        HtpHeaderTerminator::CRLF,
+        terminator(&tx.request_headers, "accept")
+    );
+    assert_eq!(
+        HtpHeaderTerminator::LF,
+        terminator(&tx.response_headers, "server")
+    );
+    assert_eq!(
+        HtpHeaderTerminator::CRLF,
+        terminator(&tx.response_headers, "content-length")
+    );
+}
+
+#[test]
+fn ResponseContentLengthValidate() {
+    let inputs = vec![
+        ("178-response-content-length-match.t", false, 5),
+        ("179-response-content-length-short.t", true, 5),
+        ("180-response-content-length-long.t", true, 13),
+    ];
+    for (file, mismatch, len) in inputs {
+        let mut cfg = TestConfig();
+        cfg.set_response_content_length_validate(true);
+        let mut t = Test::new(cfg);
+        assert!(t.run(file).is_ok());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert!(tx.is_complete());
+        assert_eq!(len, tx.response_message_len);
+        assert_eq!(
+            mismatch,
+            tx.flags.is_set(HtpFlags::RESPONSE_CONTENT_LENGTH_MISMATCH)
+        );
+    }
+}
+
+static MISMATCH_RESPONSE_COMPLETE_CALLS: AtomicUsize = AtomicUsize::new(0);
+static MISMATCH_TRANSACTION_COMPLETE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn MismatchResponseComplete(_tx: &mut Transaction) -> Result<()> {
+    MISMATCH_RESPONSE_COMPLETE_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+fn MismatchTransactionComplete(_tx: &mut Transaction) -> Result<()> {
+    MISMATCH_TRANSACTION_COMPLETE_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+#[test]
+fn ResponseContentLengthMismatchReject() {
+    let mut cfg = TestConfig();
+    cfg.set_response_content_length_validate(true);
+    cfg.set_response_content_length_mismatch_reject(true);
+    cfg.register_response_complete(MismatchResponseComplete);
+    cfg.register_transaction_complete(MismatchTransactionComplete);
+    let mut t = Test::new(cfg);
+    // The short body is only detected when the connection closes.
+    assert!(t.run("179-response-content-length-short.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags.is_set(HtpFlags::RESPONSE_CONTENT_LENGTH_MISMATCH));
+    assert_eq!(HtpStreamState::ERROR, t.connp.response_status);
+    // The transaction was completed and reported before the stream failed.
+    assert!(tx.is_complete());
+    assert_eq!(1, MISMATCH_RESPONSE_COMPLETE_CALLS.load(Ordering::SeqCst));
+    assert_eq!(
+        1,
+        MISMATCH_TRANSACTION_COMPLETE_CALLS.load(Ordering::SeqCst)
+    );
+}
+
+#[test]
+fn ResponseTrailers() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("177-response-trailers.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    assert_eq!(1, tx.response_trailers().size());
+    let (_, trailer) = tx.response_trailers().get_nocase("content-md5").unwrap();
+    assert!(trailer.value.eq("XUFAKrxLKna5cZ2REBfFkg=="));
+    assert!(tx.response_headers.get_nocase("content-md5").is_none());
+    assert_eq!(1, tx.response_headers.size());
+}
+
+#[test]
+fn AuthBasicPassword() {
+    let inputs = vec![
+        (
+            "181-auth-basic-password-colon.t",
+            Some("user"),
+            Some("pass:word"),
+            false,
+        ),
+        ("182-auth-basic-no-password.t", None, None, true),
+    ];
+    for (file, username, password, invalid) in inputs {
+        let mut t = Test::new(TestConfig());
+        assert!(t.run(file).is_ok());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(HtpAuthType::BASIC, tx.request_auth_type);
+        assert_eq!(username.map(Bstr::from), tx.request_auth_username);
+        assert_eq!(password.map(Bstr::from), tx.request_auth_password);
+        assert_eq!(invalid, tx.flags.is_set(HtpFlags::AUTH_INVALID));
+    }
+}
+
+#[test]
+fn ResponseHeaderInjection() {
+    let inputs = vec![
+        ("150-response-header-injection.t", true),
+        ("151-response-header-injection-not-reflected.t", false),
+    ];
+    for (file, expected) in inputs {
+        let mut cfg = TestConfig();
+        cfg.set_response_header_injection_detect(true);
+        let mut t = Test::new(cfg);
+        assert!(t.run(file).is_ok());
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(
+            expected,
+            tx.flags2.is_set(HtpFlags2::RESPONSE_HEADER_INJECTION)
+        );
+    }
+}
+
+#[test]
+fn SafeConnectionParser() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.feed("183-get-request-only.t").is_ok());
+    assert!(t
+        .connp
+        .response_tx()
+        .unwrap()
+        .request_method
+        .as_ref()
+        .unwrap()
+        .eq("GET"));
+
+    assert!(t.run("184-get-response-only.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    assert!(tx.response_status_number.eq_num(200));
+    assert_eq!(5, tx.response_message_len);
+}
+
+#[test]
+fn H2cUpgradeRequest() {
+    let inputs = vec![
+        ("185-request-h2c-upgrade.t", true),
+        ("186-request-h2c-upgrade-no-settings-token.t", false),
+        ("187-request-websocket-upgrade-with-settings.t", false),
+    ];
+    for (file, expected) in inputs {
+        let mut t = Test::new(TestConfig());
+        assert!(t.run(file).is_ok());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(expected, tx.is_h2c_upgrade_request);
+        if expected {
+            assert!(tx.h2c_settings().unwrap().eq("AAMAAABkAARAAAAAAAIAAAAA"));
+        } else {
+            assert!(tx.h2c_settings().is_none());
+        }
+    }
+}
+
+/// Bytes reported to the buffer callbacks, as (allocated, released).
+type BufferAccounting = Rc<Cell<(usize, usize)>>;
+
+fn BufferCallback_ALLOC(connp: &ConnectionParser, size: usize) -> Result<()> {
+    let held = connp.user_data::<BufferAccounting>().unwrap();
+    let (allocated, released) = held.get();
+    held.set((allocated + size, released));
+    Ok(())
+}
+
+fn BufferCallback_FREE(connp: &ConnectionParser, size: usize) -> Result<()> {
+    let held = connp.user_data::<BufferAccounting>().unwrap();
+    let (allocated, released) = held.get();
+    held.set((allocated, released + size));
+    Ok(())
+}
+
+fn BufferCallback_REFUSE(_connp: &ConnectionParser, _size: usize) -> Result<()> {
+    Err(HtpStatus::ERROR)
+}
+
+#[test]
+fn BufferCallbacks() {
+    let mut cfg = TestConfig();
+    cfg.register_buffer_alloc(BufferCallback_ALLOC);
+    cfg.register_buffer_free(BufferCallback_FREE);
+    let mut t = Test::new(cfg);
+    let held = BufferAccounting::default();
+    t.connp.set_user_data(Box::new(held.clone()));
+    // A partial request line is buffered.
+    assert!(t.feed("188-buffer-callbacks-1.t").is_ok());
+    let (allocated, released) = held.get();
+    assert!(allocated - released >= b"GET /inde".len());
+    assert!(t.feed("189-buffer-callbacks-2.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.request_uri.as_ref().unwrap().eq("/index.html"));
+    // The buffered line was released once it was complete.
+    let (allocated, released) = held.get();
+    assert_eq!(allocated, released);
+
+    assert!(t.feed("190-buffer-callbacks-3.t").is_ok());
+    let (allocated, released) = held.get();
+    assert!(allocated - released >= b"HTTP/1.1 20".len());
+    // Dropping the parser releases the buffers that are still held.
+    drop(t);
+    let (allocated, released) = held.get();
+    assert_eq!(allocated, released);
+
+    let mut cfg = TestConfig();
+    cfg.register_buffer_alloc(BufferCallback_REFUSE);
+    let mut t = Test::new(cfg);
+    assert!(t.feed("188-buffer-callbacks-1.t").is_err());
+}
+
+#[test]
+fn RequestBodyOverflow() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("191-request-body-overflow.t").is_ok());
+    assert_eq!(2, t.connp.tx_size());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(4, tx.request_content_length);
+    assert!(tx.request_body_overflow());
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx.request_uri.as_ref().unwrap().eq("/next"));
+    assert!(!tx.request_body_overflow());
+}
+
+#[test]
+fn MatrixParams() {
+    let mut cfg = TestConfig();
+    cfg.set_parse_matrix_params(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("194-request-matrix-params.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(1, tx.request_matrix_params.len());
+    let param = &tx.request_matrix_params[0];
+    assert!(param.segment.eq("a"));
+    assert!(param.name.eq("sid"));
+    assert!(param.value.eq("123"));
+
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("194-request-matrix-params.t").is_ok());
+    assert!(t.connp.tx(0).unwrap().request_matrix_params.is_empty());
+}
+
+#[test]
+fn ExpectContinue() {
+    let inputs = vec![
+        ("192-request-expect-continue.t", false),
+        ("193-request-expect-continue-mismatch.t", true),
+    ];
+    for (file, mismatch) in inputs {
+        let mut t = Test::new(TestConfig());
+        assert!(t.run(file).is_ok());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert!(tx.request_expects_100_continue);
+        assert_eq!(!mismatch, tx.seen_100continue);
+        assert_eq!(mismatch, tx.flags2.is_set(HtpFlags2::EXPECT_MISMATCH));
+    }
+}
+
+#[test]
+fn AllowedMethods() {
+    let inputs = vec![
+        ("01-get.t", false, HtpUnwanted::IGNORE),
+        ("195-request-method-trace.t", true, HtpUnwanted::CODE_400),
+    ];
+    for (file, disallowed, expected) in inputs {
+        let mut cfg = TestConfig();
+        cfg.set_allowed_methods(&[HtpMethod::GET, HtpMethod::POST]);
+        cfg.set_method_disallowed_unwanted(HtpUnwanted::CODE_400);
+        let mut t = Test::new(cfg);
+        assert!(t.run(file).is_ok());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(
+            disallowed,
+            tx.flags2.is_set(HtpFlags2::REQUEST_METHOD_DISALLOWED)
+        );
+        assert_eq!(expected, tx.response_status_expected_number);
+    }
+}
+
+#[test]
+fn UriMultipleQueryMarks() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("148-request-uri-multiple-query-marks.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.flags2.is_set(HtpFlags2::URI_MULTIPLE_QUERY_MARKS));
+    let query = tx.parsed_uri.as_ref().unwrap().query.as_ref().unwrap();
+    assert!(query.eq("a=1?b=2"));
+
+    let mut cfg = TestConfig();
+    cfg.set_uri_multiple_query_marks_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("148-request-uri-multiple-query-marks.t").is_err());
+
+    // A "?" in the fragment does not count.
+    let mut cfg = TestConfig();
+    cfg.set_uri_multiple_query_marks_reject(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("149-request-uri-query-mark-in-fragment.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.flags2.is_set(HtpFlags2::URI_MULTIPLE_QUERY_MARKS));
+}
+
+#[test]
+fn TotalSize() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("202-total-size.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(65, tx.request_total_size);
+    assert_eq!(43, tx.response_total_size);
+    let tx = t.connp.tx(1).unwrap();
+    assert_eq!(41, tx.request_total_size);
+    assert_eq!(0, tx.response_total_size);
+}
+
+#[test]
+fn RequestTransferEncodingList() {
+    let inputs = vec![
+        ("196-request-t-e-chunked.t", HtpTransferCoding::CHUNKED),
+        ("197-request-t-e-gzip-chunked.t", HtpTransferCoding::CHUNKED),
+        (
+            "198-request-t-e-gzip-tab-chunked.t",
+            HtpTransferCoding::CHUNKED,
+        ),
+        ("199-request-t-e-chunked-gzip.t", HtpTransferCoding::INVALID),
+        ("200-request-t-e-gzip.t", HtpTransferCoding::INVALID),
+        // Only SP and HTAB are optional whitespace around a coding.
+        (
+            "201-request-t-e-gzip-vtab-chunked.t",
+            HtpTransferCoding::INVALID,
+        ),
+    ];
+    for (file, expected) in inputs {
+        let mut t = Test::new(TestConfig());
+        // A request body of unknown length fails the stream.
+        let rc = t.run(file);
+        assert_eq!(expected == HtpTransferCoding::INVALID, rc.is_err());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(expected, tx.request_transfer_coding);
+    }
+}
+
+#[test]
+fn ResponseStatusFormat() {
+    let inputs = vec![
+        ("204-response-status-200.t", false, Some(200)),
+        ("205-response-status-leading-zero.t", true, Some(200)),
+        ("206-response-status-two-digits.t", true, None),
+    ];
+    for (file, invalid, number) in inputs {
+        for reject in [false, true].iter() {
+            let mut cfg = TestConfig();
+            cfg.set_response_status_format_reject(*reject);
+            let mut t = Test::new(cfg);
+            let rc = t.run(file);
+
+            let tx = t.connp.tx(0).unwrap();
+            assert_eq!(invalid, tx.flags.is_set(HtpFlags::STATUS_LINE_INVALID));
+            assert_eq!(*reject && invalid, rc.is_err());
+            if !*reject {
+                match number {
+                    Some(number) => assert!(tx.response_status_number.eq_num(number)),
+                    None => assert_eq!(HtpResponseNumber::INVALID, tx.response_status_number),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn RequestUriRaw() {
+    let mut cfg = TestConfig();
+    cfg.set_backslash_convert_slashes(true);
+    cfg.set_path_separators_decode(true);
+    cfg.set_convert_lowercase(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("203-request-uri-raw.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.request_uri_raw().unwrap().eq("/One%2f..%5cTwo"));
+    let path = tx.parsed_uri.as_ref().unwrap().path.as_ref().unwrap();
+    assert!(!path.eq("/One%2f..%5cTwo"));
+    assert!(!path.as_slice().iter().any(u8::is_ascii_uppercase));
+}
+
+#[test]
+fn ResponseDupHeaderPolicy() {
+    let policies = vec![
+        (HtpDupHeaderPolicy::ALLOW, false, false),
+        (HtpDupHeaderPolicy::FLAG, true, false),
+        (HtpDupHeaderPolicy::ERROR, true, true),
+    ];
+    for (policy, flagged, error) in policies {
+        let mut cfg = TestConfig();
+        cfg.set_response_dup_header_policy(policy);
+        let mut t = Test::new(cfg);
+        let rc = t.run("211-response-duplicate-content-length.t");
+
+        let tx = t.connp.tx(0).unwrap();
+        let (_, cl) = tx
+            .response_headers
+            .get_nocase_nozero("content-length")
+            .unwrap();
+        assert!(cl.flags.is_set(HtpFlags::FIELD_REPEATED));
+        assert_eq!(flagged, tx.flags.is_set(HtpFlags::FIELD_REPEATED));
+        assert_eq!(error, rc.is_err());
+    }
+}
+
+#[test]
+fn RequestPathTraversal() {
+    let inputs = vec![
+        ("207-request-path-traversal.t", "/b", true),
+        ("208-request-path-dots-in-segment.t", "/a/..b", false),
+        (
+            "209-request-path-traversal-above-root.t",
+            "/etc/passwd",
+            true,
+        ),
+        ("210-request-path-traversal-encoded.t", "/etc/passwd", true),
+    ];
+    for (file, normalized, traversal) in inputs {
+        let mut t = Test::new(TestConfig());
+        assert!(t.run(file).is_ok());
+
+        let tx = t.connp.tx(0).unwrap();
+        assert!(tx
+            .parsed_uri
+            .as_ref()
+            .unwrap()
+            .path
+            .as_ref()
+            .unwrap()
+            .eq(normalized));
+        assert_eq!(traversal, tx.request_path_traversal());
+    }
+}
+
+#[test]
+fn RequestHeaderParserFlags() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("212-request-header-folded.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx
+        .request_header_flags("x-folded")
+        .unwrap()
+        .is_set(HeaderParserFlags::FOLDING));
+    assert!(!tx
+        .request_header_flags("host")
+        .unwrap()
+        .is_set(HeaderParserFlags::FOLDING));
+    assert_eq!(None, tx.request_header_flags("x-missing"));
+}
+
+static FIELD_SOFT_LIMIT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn FieldSoftLimitCallback(_tx: &mut Transaction, size: usize) -> Result<()> {
+    assert!(size > 100);
+    FIELD_SOFT_LIMIT_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+#[test]
+fn FieldSoftLimit() {
+    let mut cfg = TestConfig();
+    cfg.set_field_soft_limit(100);
+    cfg.register_field_soft_limit(FieldSoftLimitCallback);
+    let mut t = Test::new(cfg);
+    // The header value arrives in three chunks of 80 bytes.
+    assert!(t.run("213-request-header-soft-limit.t").is_ok());
+    assert_eq!(1, FIELD_SOFT_LIMIT_CALLS.load(Ordering::SeqCst));
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(HtpRequestProgress::COMPLETE, tx.request_progress);
+    assert_eq!(
+        240,
+        tx.request_headers
+            .get_nocase_nozero("x-long")
+            .unwrap()
+            .1
+            .value
+            .len()
+    );
+
+    // A soft limit above the field limit never fires; the field limit fails first.
+    let mut cfg = TestConfig();
+    cfg.set_field_limit(200);
+    cfg.set_field_soft_limit(300);
+    cfg.register_field_soft_limit(FieldSoftLimitCallback);
+    let mut t = Test::new(cfg);
+    assert!(t
+        .run("214-request-header-soft-limit-above-field-limit.t")
+        .is_err());
+    assert_eq!(1, FIELD_SOFT_LIMIT_CALLS.load(Ordering::SeqCst));
+}
+
+#[test]
+fn ParserStateName() {
+    let mut t = Test::new(TestConfig());
+    assert_eq!("REQ_IDLE", t.connp.request_state_name());
+    assert!(t.feed("215-parser-state-request-headers.t").is_ok());
+    assert_eq!("REQ_HEADERS", t.connp.request_state_name());
+    assert!(t.feed("216-parser-state-response-headers.t").is_ok());
+    assert_eq!("RES_HEADERS", t.connp.response_state_name());
+    assert!(t.feed("217-parser-state-response-body.t").is_ok());
+    assert_eq!("RES_BODY_IDENTITY_CL_KNOWN", t.connp.response_state_name());
+}
+
+#[test]
+fn CompressedResponseDeflateRaw() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("18-compressed-response-deflate.t").is_ok());
+
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.is_complete());
+    assert!(tx.flags2.is_set(HtpFlags2::DEFLATE_RAW));
+}
+
+#[test]
+fn ParseQueryString() {
+    for parse_urlencoded in [false, true].iter() {
+        let mut cfg = TestConfig();
+        cfg.set_parse_urlencoded(*parse_urlencoded);
+        let mut t = Test::new(cfg);
+        assert!(t.run("218-request-query-string.t").is_ok());
+        let tx = t.connp.tx_mut(0).unwrap();
+        assert_eq!(*parse_urlencoded, tx.request_params.size() == 3);
+        assert!(tx.parse_query_string().is_ok());
+        assert_eq!(3, tx.request_params.size());
+        assert_contains_param_source!(&tx.request_params, HtpDataSource::QUERY_STRING, "a", "b");
+        assert_contains_param_source!(&tx.request_params, HtpDataSource::QUERY_STRING, "c", " ");
+        assert_contains_param_source!(&tx.request_params, HtpDataSource::QUERY_STRING, "d", "");
+    }
+}
+
+static ERROR_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn ErrorCallback(_connp: &ConnectionParser, log: Option<&Log>) -> Result<()> {
+    assert_eq!(
+        HtpLogCode::INVALID_CONTENT_LENGTH_FIELD_IN_RESPONSE,
+        log.unwrap().msg.code
+    );
+    ERROR_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+#[test]
+fn ErrorCallbackOnce() {
+    let mut cfg = TestConfig();
+    cfg.register_error(ErrorCallback);
+    let mut t = Test::new(cfg);
+    assert!(t.feed("219-response-invalid-content-length.t").is_err());
+    assert_eq!(1, ERROR_CALLS.load(Ordering::SeqCst));
+    // Data that arrives after the failure is rejected without another report.
+    assert!(t.feed("220-response-more-data.t").is_err());
+    assert_eq!(1, ERROR_CALLS.load(Ordering::SeqCst));
+}
+
+#[test]
+fn TransactionTimestamps() {
+    let ts = |secs| {
+        Some(DateTime::<Utc>::from(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        ))
+    };
+    let mut t = Test::new(TestConfig());
+    let files = [
+        "221-timestamps-request-line.t",
+        "222-timestamps-request-headers.t",
+        "223-timestamps-request-body.t",
+        "224-timestamps-response-line.t",
+        "225-timestamps-response-rest.t",
+    ];
+    for (secs, file) in (1..).zip(files.iter()) {
+        assert!(t.feed_at(file, ts(secs).unwrap()).is_ok());
+    }
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(ts(1), tx.request_line_ts());
+    assert_eq!(ts(3), tx.request_complete_ts());
+    assert_eq!(ts(4), tx.response_line_ts());
+    assert_eq!(ts(5), tx.response_complete_ts());
+}
+
+#[test]
+fn ConnectionDisposition() {
+    let cases = [
+        (
+            "226-connection-close.t",
+            HtpConnectionDisposition::CLOSE,
+            HtpConnectionDisposition::CLOSE,
+        ),
+        (
+            "227-connection-keep-alive-http10.t",
+            HtpConnectionDisposition::KEEP_ALIVE,
+            HtpConnectionDisposition::KEEP_ALIVE,
+        ),
+        (
+            "228-connection-default-http10.t",
+            HtpConnectionDisposition::CLOSE,
+            HtpConnectionDisposition::CLOSE,
+        ),
+        (
+            "229-connection-default-http11.t",
+            HtpConnectionDisposition::KEEP_ALIVE,
+            HtpConnectionDisposition::KEEP_ALIVE,
+        ),
+        (
+            "230-connection-upgrade.t",
+            HtpConnectionDisposition::UPGRADE,
+            HtpConnectionDisposition::UNKNOWN,
+        ),
+    ];
+    for (file, request_connection, response_connection) in cases.iter() {
+        let mut t = Test::new(TestConfig());
+        assert!(t.run(file).is_ok());
+        let tx = t.connp.tx(0).unwrap();
+        assert_eq!(*request_connection, tx.request_connection());
+        assert_eq!(*response_connection, tx.response_connection());
+    }
+}
+
+#[test]
+fn UriPathLimit() {
+    let mut cfg = TestConfig();
+    cfg.set_uri_path_limit(8192);
+    let mut t = Test::new(cfg);
+    // The request path is 70 KiB long.
+    assert!(t.run("231-request-uri-path-too-long.t").is_ok());
+    let path = format!("/{}", "a".repeat(70 * 1024));
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.request_path_too_long());
+    assert_eq!(
+        8192,
+        tx.parsed_uri.as_ref().unwrap().path.as_ref().unwrap().len()
+    );
+    assert!(tx
+        .parsed_uri_raw
+        .as_ref()
+        .unwrap()
+        .path
+        .as_ref()
+        .unwrap()
+        .eq(path.as_bytes()));
+    assert!(t
+        .connp
+        .conn
+        .get_logs()
+        .iter()
+        .any(|log| log.msg.code == HtpLogCode::PATH_TOO_LONG));
+
+    // Paths within the limit are left alone.
+    let mut cfg = TestConfig();
+    cfg.set_uri_path_limit(8192);
+    let mut t = Test::new(cfg);
+    assert!(t.run("232-request-uri-path-short.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.request_path_too_long());
+    assert!(tx
+        .parsed_uri
+        .as_ref()
+        .unwrap()
+        .path
+        .as_ref()
+        .unwrap()
+        .eq("/short"));
+}
+
+#[test]
+fn TxMatchStrict() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("233-response-without-request.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert_eq!(
+        tx.request_uri,
+        Some(Bstr::from("/libhtp::request_uri_not_seen"))
+    );
+    assert_eq!(HtpResponseProgress::COMPLETE, tx.response_progress);
+
+    let mut cfg = TestConfig();
+    cfg.set_tx_match_strict(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("233-response-without-request.t").is_err());
+    assert!(t.connp.tx(0).unwrap().request_uri.is_none());
+}
+
+#[test]
+fn ResponseSplitting() {
+    // A bare CR is not a fold, so it stays in the value.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("234-response-header-bare-cr.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_splitting());
+    assert!(tx
+        .response_headers
+        .elements
+        .iter()
+        .any(|(_, h)| h.value.eq("X-Injected\rSet-Cookie")
+            && h.flags.is_set(HtpFlags::FIELD_INVALID)));
+    assert!(t
+        .connp
+        .conn
+        .get_logs()
+        .iter()
+        .any(|log| log.msg.code == HtpLogCode::RESPONSE_SPLITTING));
+
+    // A CR after a named header's line ending is not a fold when another header follows.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("235-response-header-cr-after-line-ending.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.response_splitting());
+    assert!(tx
+        .response_headers
+        .get_nocase_nozero("X-Test")
+        .unwrap()
+        .1
+        .flags
+        .is_set(HtpFlags::FIELD_INVALID));
+
+    // A CR left at the end of a value does not start another line.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("236-response-header-trailing-cr.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.response_splitting());
+
+    // Proper folding is joined with a space.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("237-response-header-folded.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(!tx.response_splitting());
+    assert!(tx
+        .response_headers
+        .get_nocase_nozero("X-Folded")
+        .unwrap()
+        .1
+        .value
+        .eq("a b"));
+}
+
+#[test]
+fn AutoPersonality() {
+    let mut cfg = TestConfig();
+    cfg.set_auto_personality(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("238-auto-personality-iis-7-5.t").is_ok());
+    assert_eq!(
+        HtpServerPersonality::IIS_7_5,
+        t.connp.cfg.server_personality
+    );
+    // The first request was decoded the Apache way, the second one the IIS way.
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx
+        .parsed_uri
+        .as_ref()
+        .unwrap()
+        .path
+        .as_ref()
+        .unwrap()
+        .eq("/a\\b"));
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx
+        .parsed_uri
+        .as_ref()
+        .unwrap()
+        .path
+        .as_ref()
+        .unwrap()
+        .eq("/a/b"));
+
+    // Disabled by default.
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("238-auto-personality-iis-7-5.t").is_ok());
+    assert_eq!(
+        HtpServerPersonality::APACHE_2,
+        t.connp.cfg.server_personality
+    );
+    let tx = t.connp.tx(1).unwrap();
+    assert!(tx
+        .parsed_uri
+        .as_ref()
+        .unwrap()
+        .path
+        .as_ref()
+        .unwrap()
+        .eq("/a\\b"));
+}
+
+#[test]
+fn AutoPersonalityReplacesPreviousPersonality() {
+    let defaults = Config::default();
+
+    // Nothing set by the Apache personality survives the switch to IIS 5.1.
+    let mut cfg = TestConfig();
+    cfg.set_auto_personality(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("239-auto-personality-iis-5-1.t").is_ok());
+    assert_eq!(
+        HtpServerPersonality::IIS_5_1,
+        t.connp.cfg.server_personality
+    );
+    assert_eq!(
+        defaults.decoder_cfg.url_encoding_invalid_unwanted,
+        t.connp.cfg.decoder_cfg.url_encoding_invalid_unwanted
+    );
+    assert!(t.connp.cfg.parse_urlencoded);
+
+    // Neither does anything set by the IDS personality, while settings that no
+    // personality controls are kept.
+    let mut cfg = Config::default();
+    cfg.set_server_personality(HtpServerPersonality::IDS)
+        .unwrap();
+    cfg.set_nul_raw_terminates(true);
+    cfg.set_auto_personality(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("239-auto-personality-iis-5-1.t").is_ok());
+    assert_eq!(
+        HtpServerPersonality::IIS_5_1,
+        t.connp.cfg.server_personality
+    );
+    assert!(!t.connp.cfg.decoder_cfg.convert_lowercase);
+    assert!(!t.connp.cfg.decoder_cfg.utf8_convert_bestfit);
+    assert!(t.connp.cfg.decoder_cfg.backslash_convert_slashes);
+    assert_eq!(
+        HtpUnwanted::IGNORE,
+        t.connp.cfg.requestline_leading_whitespace_unwanted
+    );
+    assert!(t.connp.cfg.decoder_cfg.nul_raw_terminates);
+}
+
+#[test]
+fn ServerPersonalityFromServerHeader() {
+    assert_eq!(
+        Some(HtpServerPersonality::IIS_7_5),
+        HtpServerPersonality::from_server_header(b"microsoft-iis/7.5")
+    );
+    assert_eq!(
+        Some(HtpServerPersonality::APACHE_2),
+        HtpServerPersonality::from_server_header(b"Apache/2.4.41 (Unix)")
+    );
+    assert_eq!(
+        Some(HtpServerPersonality::GENERIC),
+        HtpServerPersonality::from_server_header(b"NGINX")
+    );
+    assert_eq!(
+        None,
+        HtpServerPersonality::from_server_header(b"Microsoft-IIS/10.0")
+    );
+    assert_eq!(None, HtpServerPersonality::from_server_header(b"lighttpd"));
+    assert_eq!(None, HtpServerPersonality::from_server_header(b""));
+}
+
+#[test]
+fn ConfigCopy() {
+    use htp::c_api::config::{htp_config_copy, htp_config_destroy};
+
+    fn request_line(_tx: &mut Transaction) -> Result<()> {
+        Ok(())
+    }
+
+    let mut base = TestConfig();
+    base.register_request_line(request_line);
+    unsafe {
+        let copy = htp_config_copy(&base);
+        assert!(!copy.is_null());
+        (*copy).register_request_line(request_line);
+        assert_eq!(2, (*copy).hook_request_line.callbacks.len());
+        assert_eq!(1, base.hook_request_line.callbacks.len());
+        htp_config_destroy(copy);
+        assert!(htp_config_copy(std::ptr::null()).is_null());
+    }
+    assert_eq!(1, base.hook_request_line.callbacks.len());
+
+    // A clone is just as independent.
+    let mut clone = base.clone();
+    clone.register_request_line(request_line);
+    drop(clone);
+    assert_eq!(1, base.hook_request_line.callbacks.len());
+}
+
+#[test]
+fn StrictHeaderNames() {
+    let mut t = Test::new(TestConfig());
+    assert!(t.run("240-request-header-name-trailing-space.t").is_ok());
+    let tx = t.connp.tx(0).unwrap();
+    assert!(tx.request_headers.get_nocase_nozero("User-Agent").is_some());
+
+    let mut cfg = TestConfig();
+    cfg.set_strict_header_names(true);
+    let mut t = Test::new(cfg);
+    assert!(t.run("240-request-header-name-trailing-space.t").is_err());
+}
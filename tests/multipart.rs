@@ -9,6 +9,7 @@ use htp::{
     HtpStatus,
 };
 use std::{
+    cell::RefCell,
     fs,
     net::{IpAddr, Ipv4Addr},
     rc::Rc,
@@ -995,6 +996,33 @@ fn CompleteRequest() {
     assert!(!t.body().flags.is_set(Flags::PART_HEADER_FOLDING));
 }
 
+#[test]
+fn QuotedBoundaryWithEscape() {
+    let mut t = Test::new(TestConfig());
+    let headers = vec![
+        "POST / HTTP/1.0\r\n\
+         Content-Type: multipart/form-data; charset=utf-8; boundary=\"01234\\\"56789\"\r\n",
+    ];
+
+    let data = vec![
+        "--01234\"56789\r\n\
+         Content-Disposition: form-data; name=\"field1\"\r\n\
+         \r\n\
+         ABCDEF\
+         \r\n--01234\"56789\r\n\
+         Content-Disposition: form-data; name=\"file1\"; filename=\"file.bin\"\r\n\
+         \r\n\
+         FILEDATA\
+         \r\n--01234\"56789\r\n\
+         Content-Disposition: form-data; name=\"field2\"\r\n\
+         \r\n\
+         GHIJKL\
+         \r\n--01234\"56789--",
+    ];
+
+    t.parseRequestThenVerify(&headers, &data);
+}
+
 #[test]
 fn InvalidHeader1() {
     let mut t = Test::new(TestConfig());
@@ -1349,6 +1377,7 @@ fn BoundaryUnusual() {
         let mut flags: u64 = 0;
         assert!(find_boundary(input, &mut flags).is_some());
         assert!(flags.is_set(Flags::HBOUNDARY_UNUSUAL));
+        assert_eq!(input.contains(&b'"'), flags.is_set(Flags::HBOUNDARY_QUOTED));
     }
 }
 
@@ -1802,3 +1831,189 @@ fn HeaderValueTrim() {
         .1;
     assert_eq!(header.value, "form-data; name=\"field1\"");
 }
+
+thread_local! {
+    static COMPLETED_PARTS: RefCell<Vec<(Bstr, Bstr, Option<Bstr>)>> = RefCell::new(Vec::new());
+}
+
+fn PartCompleteCallback(part: &Part) -> htp::error::Result<()> {
+    let content_type = part
+        .headers
+        .get_nocase_nozero("content-type")
+        .map(|(_, h)| h.value.clone());
+    COMPLETED_PARTS.with(|parts| {
+        parts
+            .borrow_mut()
+            .push((part.name.clone(), part.value.clone(), content_type))
+    });
+    Ok(())
+}
+
+#[test]
+fn PartCompleteCallbackInvoked() {
+    let mut cfg = TestConfig();
+    cfg.register_multipart_part_complete(PartCompleteCallback);
+    let mut t = Test::new(cfg);
+    let headers = vec![
+        "POST / HTTP/1.0\r\n\
+         Content-Type: multipart/form-data; boundary=0123456789\r\n",
+    ];
+
+    let data = vec![
+        "--0123456789\r\n\
+         Content-Disposition: form-data; name=\"field1\"\r\n\
+         \r\n\
+         ABCDEF\
+         \r\n--0123456789\r\n\
+         Content-Disposition: form-data; name=\"field2\"\r\n\
+         Content-Type: text/plain\r\n\
+         \r\n\
+         GHIJKL\
+         \r\n--0123456789--",
+    ];
+
+    COMPLETED_PARTS.with(|parts| parts.borrow_mut().clear());
+    t.parseRequest(&headers, &data);
+
+    COMPLETED_PARTS.with(|parts| {
+        let parts = parts.borrow();
+        assert_eq!(2, parts.len());
+        assert!(parts[0].0.eq("field1"));
+        assert!(parts[0].1.eq("ABCDEF"));
+        assert!(parts[0].2.is_none());
+        assert!(parts[1].0.eq("field2"));
+        assert!(parts[1].1.eq("GHIJKL"));
+        assert!(parts[1].2.as_ref().unwrap().eq("text/plain"));
+    });
+}
+
+#[test]
+fn MultipartParts() {
+    let mut t = Test::new(TestConfig());
+    let headers = vec![
+        "POST / HTTP/1.0\r\n\
+         Content-Type: multipart/form-data; boundary=0123456789\r\n",
+    ];
+
+    let data = vec![
+        "--0123456789\r\n\
+         Content-Disposition: form-data; name=\"field1\"\r\n\
+         \r\n\
+         ABCDEF\
+         \r\n--0123456789\r\n\
+         Content-Disposition: form-data; name=\"file1\"; filename=\"file.bin\"\r\n\
+         Content-Type: application/octet-stream\r\n\
+         \r\n\
+         FILEDATA\
+         \r\n--0123456789--",
+    ];
+
+    t.parseRequest(&headers, &data);
+
+    let parts = t.tx().multipart_parts();
+    assert_eq!(2, parts.len());
+    assert_eq!(Some(Bstr::from("field1")), parts[0].name);
+    assert_eq!(None, parts[0].filename);
+    assert_eq!(None, parts[0].content_type);
+    assert_eq!(Some(Bstr::from("ABCDEF")), parts[0].body);
+    assert_eq!(Some(Bstr::from("file1")), parts[1].name);
+    assert_eq!(Some(Bstr::from("file.bin")), parts[1].filename);
+    assert_eq!(
+        Some(Bstr::from("application/octet-stream")),
+        parts[1].content_type
+    );
+    assert_eq!(None, parts[1].body);
+}
+
+#[test]
+fn MultipartPartsExtendedFilename() {
+    let mut t = Test::new(TestConfig());
+    let headers = vec![
+        "POST / HTTP/1.0\r\n\
+         Content-Type: multipart/form-data; boundary=0123456789\r\n",
+    ];
+
+    let data = vec![
+        "--0123456789\r\n\
+         Content-Disposition: form-data; name=\"file1\"; filename*=UTF-8''%e2%82%ac%20rates.txt; filename=\"rates.txt\"\r\n\
+         \r\n\
+         FILEDATA\
+         \r\n--0123456789\r\n\
+         Content-Disposition: form-data; name=\"file2\"; filename*=iso-8859-1'en'%A3%20rates.txt\r\n\
+         \r\n\
+         FILEDATA\
+         \r\n--0123456789--",
+    ];
+
+    t.parseRequest(&headers, &data);
+
+    assert!(!t.body().flags.is_set(Flags::CD_INVALID));
+    // The two parameters of the first part name different files.
+    assert!(t.body().flags.is_set(Flags::CD_FILENAME_MISMATCH));
+    let parts = t.tx().multipart_parts();
+    assert_eq!(2, parts.len());
+    assert_eq!(Some(Bstr::from("file1")), parts[0].name);
+    assert_eq!(Some(Bstr::from("€ rates.txt")), parts[0].filename);
+    assert_eq!(Some(Bstr::from("file2")), parts[1].name);
+    assert_eq!(Some(Bstr::from("£ rates.txt")), parts[1].filename);
+}
+
+#[test]
+fn ExtendedFilenameMatch() {
+    let inputs = vec![
+        (
+            "form-data; name=\"file1\"; filename*=UTF-8''a%20b.txt; filename=\"a b.txt\"",
+            false,
+        ),
+        (
+            "form-data; name=\"file1\"; filename=\"a b.txt\"; filename*=UTF-8''a%20b.txt",
+            false,
+        ),
+        (
+            "form-data; name=\"file1\"; filename=\"a.txt\"; filename*=UTF-8''a.php",
+            true,
+        ),
+        ("form-data; name=\"file1\"; filename*=UTF-8''a.php", false),
+    ];
+    let cfg = Rc::new(Config::default());
+    for (input, mismatch) in inputs {
+        let parser = &mut Parser::new(&cfg, b"123", 0);
+        parser.multipart.parts.push(Part::default());
+        parser.current_part_idx = Some(0);
+        let part = parser.get_current_part().unwrap();
+        let header = Header::new(b"Content-Disposition".to_vec().into(), input.into());
+        part.headers.add(header.name.clone(), header);
+        assert!(parser.parse_c_d().is_ok());
+        assert_eq!(
+            mismatch,
+            parser.multipart.flags.is_set(Flags::CD_FILENAME_MISMATCH)
+        );
+    }
+}
+
+#[test]
+fn InvalidExtendedFilename() {
+    let inputs = vec![
+        // Unsupported charset.
+        "form-data; name=\"file1\"; filename*=UTF-16''%00a",
+        // Missing language field.
+        "form-data; name=\"file1\"; filename*=UTF-8'a.txt",
+        // Invalid percent-encoding.
+        "form-data; name=\"file1\"; filename*=UTF-8''%zz.txt",
+        // Invalid UTF-8.
+        "form-data; name=\"file1\"; filename*=UTF-8''%ff.txt",
+        // Empty value.
+        "form-data; name=\"file1\"; filename*=",
+    ];
+    let cfg = Rc::new(Config::default());
+    for input in inputs {
+        let parser = &mut Parser::new(&cfg, b"123", 0);
+        parser.multipart.parts.push(Part::default());
+        parser.current_part_idx = Some(0);
+        let part = parser.get_current_part().unwrap();
+        let header = Header::new(b"Content-Disposition".to_vec().into(), input.into());
+        part.headers.add(header.name.clone(), header);
+        assert_err!(parser.parse_c_d(), HtpStatus::DECLINED);
+        assert!(parser.multipart.flags.is_set(Flags::CD_SYNTAX_INVALID));
+    }
+}